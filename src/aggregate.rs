@@ -0,0 +1,222 @@
+//! Оценка документа по частям (--aggregate-by): сначала оценка каждого абзаца или предложения,
+//! затем одна функция над ними (--aggregate), чтобы большой лёгкий раздел не скрывал трудный.
+
+use clap::ValueEnum;
+
+use crate::dict::FrequencyDict;
+use crate::{metrics, token_weight, PreparedText, ScoreOptions};
+
+/// На какие части делится текст перед агрегацией
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AggregateBy {
+    /// Без деления: среднее по всем словам (обычная оценка)
+    Document,
+    /// Абзацы, разделённые пустой строкой
+    Paragraph,
+    /// Предложения, как у --top-text-sentences
+    Sentence,
+}
+
+/// Функция над оценками частей
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aggregate {
+    Mean,
+    Median,
+    /// Самая трудная часть
+    Min,
+}
+
+impl AggregateBy {
+    pub fn name(self) -> &'static str {
+        match self {
+            AggregateBy::Document => "document",
+            AggregateBy::Paragraph => "paragraph",
+            AggregateBy::Sentence => "sentence",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::value_variants().iter().copied().find(|v| v.name() == name)
+    }
+}
+
+impl Aggregate {
+    pub fn name(self) -> &'static str {
+        match self {
+            Aggregate::Mean => "mean",
+            Aggregate::Median => "median",
+            Aggregate::Min => "min",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::value_variants().iter().copied().find(|v| v.name() == name)
+    }
+
+    /// Значение функции; None для пустого набора
+    pub fn apply(self, scores: &[f64]) -> Option<f64> {
+        if scores.is_empty() {
+            return None;
+        }
+        Some(match self {
+            Aggregate::Mean => scores.iter().sum::<f64>() / scores.len() as f64,
+            Aggregate::Median => {
+                let mut sorted = scores.to_vec();
+                sorted.sort_by(f64::total_cmp);
+                crate::bootstrap::percentile(&sorted, 0.5)
+            }
+            Aggregate::Min => scores.iter().copied().fold(f64::INFINITY, f64::min),
+        })
+    }
+}
+
+/// Параметры агрегации
+#[derive(Clone, Copy, Debug)]
+pub struct AggregateOptions {
+    pub by: AggregateBy,
+    pub function: Aggregate,
+}
+
+/// Оценка одной части текста
+#[derive(Clone, Debug)]
+pub struct UnitScore {
+    /// Байтовый диапазон части во входном тексте: от начала первого слова до конца последнего
+    pub start: usize,
+    pub end: usize,
+    pub tokens: usize,
+    pub score: f64,
+    /// Меньше --min-tokens слов: в агрегацию не вошла
+    pub excluded: bool,
+}
+
+/// Оценки частей и их агрегат
+#[derive(Clone, Debug)]
+pub struct AggregateResult {
+    pub by: AggregateBy,
+    pub function: Aggregate,
+    /// Агрегат по невыключенным частям; None, если таких частей нет
+    pub score: Option<f64>,
+    /// Обычная оценка по всем словам, для сравнения
+    pub token_score: f64,
+    pub units: Vec<UnitScore>,
+}
+
+impl AggregateResult {
+    pub fn excluded(&self) -> usize {
+        self.units.iter().filter(|u| u.excluded).count()
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let units: Vec<serde_json::Value> = self
+            .units
+            .iter()
+            .map(|u| {
+                let mut json = serde_json::json!({
+                    "start": u.start,
+                    "end": u.end,
+                    "tokens": u.tokens,
+                    "score": u.score,
+                });
+                if u.excluded {
+                    json["excluded"] = true.into();
+                }
+                json
+            })
+            .collect();
+        serde_json::json!({
+            "by": self.by.name(),
+            "function": self.function.name(),
+            "score": self.score,
+            "token_score": self.token_score,
+            "units_excluded": self.excluded(),
+            "units": units,
+        })
+    }
+
+    /// Обратно из `to_json`
+    pub fn from_json(v: &serde_json::Value) -> Option<AggregateResult> {
+        let units = v["units"]
+            .as_array()?
+            .iter()
+            .map(|u| {
+                Some(UnitScore {
+                    start: u["start"].as_u64()? as usize,
+                    end: u["end"].as_u64()? as usize,
+                    tokens: u["tokens"].as_u64()? as usize,
+                    score: u["score"].as_f64()?,
+                    excluded: u["excluded"] == true,
+                })
+            })
+            .collect::<Option<_>>()?;
+        Some(AggregateResult {
+            by: AggregateBy::from_name(v["by"].as_str()?)?,
+            function: Aggregate::from_name(v["function"].as_str()?)?,
+            score: v["score"].as_f64(),
+            token_score: v["token_score"].as_f64()?,
+            units,
+        })
+    }
+}
+
+/// Концы абзацев в байтах: позиция перевода строки перед пустой строкой; последний — конец текста
+fn paragraph_ends(text: &str) -> Vec<usize> {
+    let mut ends = Vec::new();
+    let mut line_start = 0;
+    let mut blank_run = false;
+    for line in text.split_inclusive('\n') {
+        let blank = line.trim().is_empty();
+        if blank && !blank_run {
+            ends.push(line_start);
+        }
+        blank_run = blank;
+        line_start += line.len();
+    }
+    ends.push(text.len());
+    ends
+}
+
+/// Оценивает части оцениваемой части текста (с учётом --top-text-words и --top-text-sentences)
+/// и агрегирует их. Части без слов не выводятся; части короче `opts.min_tokens` выводятся
+/// с пометкой и в агрегат не входят. Для `AggregateBy::Document` — None.
+pub fn aggregate(
+    prepared: &PreparedText,
+    dict: &FrequencyDict,
+    opts: &ScoreOptions,
+    aggregate: AggregateOptions,
+    token_score: f64,
+) -> Option<AggregateResult> {
+    let ends = match aggregate.by {
+        AggregateBy::Document => return None,
+        AggregateBy::Paragraph => paragraph_ends(&prepared.text),
+        AggregateBy::Sentence => metrics::sentence_ends(&prepared.text, opts.typographic)
+            .chain(std::iter::once(prepared.text.len()))
+            .collect(),
+    };
+    let tokens = prepared.analyzed_tokens();
+    let spans = prepared.input_spans(tokens);
+    let mut units = Vec::new();
+    let mut first = 0;
+    for end in ends {
+        let last = first + tokens[first..].partition_point(|t| t.start < end);
+        if last > first {
+            let sum: f64 = tokens[first..last].iter().map(|t| token_weight(t, dict, opts)).sum();
+            let count = last - first;
+            units.push(UnitScore {
+                start: spans[first].0,
+                end: spans[last - 1].1,
+                tokens: count,
+                score: sum / count as f64,
+                excluded: opts.insufficient(count),
+            });
+        }
+        first = last;
+    }
+    let included: Vec<f64> = units.iter().filter(|u| !u.excluded).map(|u| u.score).collect();
+    Some(AggregateResult {
+        by: aggregate.by,
+        function: aggregate.function,
+        score: aggregate.function.apply(&included),
+        token_score,
+        units,
+    })
+}
@@ -0,0 +1,53 @@
+//! Текст с пометками трудных слов (--annotate): `sesquipedalian[0.0002]`, `[OOV]` для незнакомых.
+
+use crate::dict::FrequencyDict;
+use crate::{token_weight, PreparedText, ScoreOptions};
+
+/// Какие слова помечать и с какой точностью писать вес
+#[derive(Clone, Copy, Debug)]
+pub struct AnnotateOptions {
+    /// Помечаются слова с весом строго меньше порога; слова вне словаря — всегда
+    pub threshold: f64,
+    /// Знаков после запятой в пометке
+    pub precision: usize,
+}
+
+/// Входной текст, в который после трудных слов вставлены пометки; всё остальное, включая
+/// регистр, пробелы и пунктуацию, копируется без изменений. Слова из одного раскрытого
+/// сокращения делят его диапазон и получают одну пометку — по самому трудному из них.
+pub fn annotate(prepared: &PreparedText, dict: &FrequencyDict, opts: &ScoreOptions, aopts: AnnotateOptions) -> String {
+    let input = prepared.input;
+    let spans = prepared.input_spans(&prepared.tokens);
+    // (конец слова во входном тексте, наименьший вес, есть ли слово вне словаря)
+    let mut marks: Vec<(usize, f64, bool)> = Vec::new();
+    let mut last_span = None;
+    for (t, span) in prepared.tokens.iter().zip(spans) {
+        let weight = token_weight(t, dict, opts);
+        let oov = !dict.contains(&t.word);
+        if last_span == Some(span) {
+            let mark = marks.last_mut().expect("диапазон уже встречался");
+            mark.1 = mark.1.min(weight);
+            mark.2 |= oov;
+            continue;
+        }
+        last_span = Some(span);
+        marks.push((span.1, weight, oov));
+    }
+
+    let mut out = String::with_capacity(input.len() + marks.len() * 4);
+    let mut copied = 0;
+    for (end, weight, oov) in marks {
+        if !oov && weight >= aopts.threshold {
+            continue;
+        }
+        out.push_str(&input[copied..end]);
+        copied = end;
+        if oov {
+            out.push_str("[OOV]");
+        } else {
+            out.push_str(&format!("[{weight:.*}]", aopts.precision));
+        }
+    }
+    out.push_str(&input[copied..]);
+    out
+}
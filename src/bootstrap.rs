@@ -0,0 +1,81 @@
+//! Доверительный интервал оценки бутстрепом (--bootstrap N).
+
+use crate::rng::SplitMix64;
+
+/// Параметры бутстрепа
+#[derive(Clone, Copy, Debug)]
+pub struct BootstrapOptions {
+    pub resamples: usize,
+    pub seed: u64,
+}
+
+/// Перцентили 2.5/50/97.5 распределения средних по повторным выборкам
+#[derive(Clone, Copy, Debug)]
+pub struct BootstrapResult {
+    pub resamples: usize,
+    pub seed: u64,
+    pub lo: f64,
+    pub median: f64,
+    pub hi: f64,
+}
+
+/// Перцентиль отсортированного массива с линейной интерполяцией между соседями
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let pos = p * (sorted.len() - 1) as f64;
+    let i = pos.floor() as usize;
+    let frac = pos - i as f64;
+    match sorted.get(i + 1) {
+        Some(next) => sorted[i] + (next - sorted[i]) * frac,
+        None => sorted[i],
+    }
+}
+
+/// Повторные выборки с возвращением из уже отфильтрованных весов слов. Выборка не
+/// материализуется: на каждой итерации веса суммируются по случайным индексам прямо
+/// из исходного массива, так что память — только под N средних.
+pub fn bootstrap(weights: &[f64], opts: BootstrapOptions) -> Option<BootstrapResult> {
+    if weights.is_empty() || opts.resamples == 0 {
+        return None;
+    }
+    let mut rng = SplitMix64::new(opts.seed);
+    let n = weights.len();
+    let mut means = Vec::with_capacity(opts.resamples);
+    for _ in 0..opts.resamples {
+        let mut sum = 0.0;
+        for _ in 0..n {
+            sum += weights[rng.below(n)];
+        }
+        means.push(sum / n as f64);
+    }
+    means.sort_by(f64::total_cmp);
+    Some(BootstrapResult {
+        resamples: opts.resamples,
+        seed: opts.seed,
+        lo: percentile(&means, 0.025),
+        median: percentile(&means, 0.5),
+        hi: percentile(&means, 0.975),
+    })
+}
+
+impl BootstrapResult {
+    pub fn to_json(self) -> serde_json::Value {
+        serde_json::json!({
+            "resamples": self.resamples,
+            "seed": self.seed,
+            "p2_5": self.lo,
+            "p50": self.median,
+            "p97_5": self.hi,
+        })
+    }
+
+    /// Обратно из `to_json`
+    pub fn from_json(v: &serde_json::Value) -> Option<BootstrapResult> {
+        Some(BootstrapResult {
+            resamples: v["resamples"].as_u64()? as usize,
+            seed: v["seed"].as_u64()?,
+            lo: v["p2_5"].as_f64()?,
+            median: v["p50"].as_f64()?,
+            hi: v["p97_5"].as_f64()?,
+        })
+    }
+}
@@ -0,0 +1,181 @@
+//! Кэш оценок по содержимому файлов (--cache-scores): неизменившиеся файлы не оцениваются заново.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+
+use readability::{Lang, ScoreOptions, Warning};
+
+use crate::corpus::FileOutcome;
+use crate::i18n::Msg;
+use crate::{warnings, write_file_atomically};
+
+/// Версия формата файла кэша; записи другой версии не читаются
+const CACHE_VERSION: u64 = 2;
+
+/// Отпечаток всего, от чего зависит оценка помимо текста: версия программы, содержимое
+/// словарей и таблиц, параметры. Части разделяются нулевым байтом, чтобы не склеиваться.
+pub struct Fingerprint(Sha256);
+
+impl Fingerprint {
+    pub fn new() -> Self {
+        let mut h = Sha256::new();
+        h.update(env!("CARGO_PKG_VERSION"));
+        Fingerprint(h)
+    }
+
+    pub fn add(&mut self, part: impl AsRef<[u8]>) {
+        self.0.update([0]);
+        self.0.update(part);
+    }
+
+    pub fn add_file(&mut self, path: &Path) -> Result<()> {
+        let content = std::fs::read(path).with_context(|| Msg::CacheFingerprint(path).to_string())?;
+        self.add(content);
+        Ok(())
+    }
+
+    /// Параметры оценки. Таблицы сокращений и синонимов входят лишь признаком: их файлы
+    /// добавляются отдельно.
+    pub fn add_options(&mut self, opts: &ScoreOptions) {
+        self.add(format!(
+            "lang={:?} top_words={:?} top_sentences={:?} contractions={} acronyms={} acronym_weight={} \
+             pipeline={} typographic={} min_tokens={} aggregate={:?} oov={:?} metrics={:?} \
+             max_clauses={} overlap={:?}/{} tertiles={} locale={:?} max_token_len={} thresholds={:?} \
+             suggest={:?}",
+            opts.lang,
+            opts.top_text_words,
+            opts.top_text_sentences,
+            opts.contractions.is_some(),
+            opts.keep_acronyms,
+            opts.acronym_weight,
+            opts.pipeline.names(),
+            opts.typographic,
+            opts.min_tokens,
+            opts.aggregate,
+            opts.oov_weights,
+            opts.metrics,
+            opts.max_clauses,
+            opts.overlap,
+            opts.overlap_delta,
+            opts.tertiles,
+            opts.locale,
+            opts.max_token_len,
+            opts.thresholds,
+            opts.suggest.as_ref().map(|s| (s.threshold, s.limit)),
+        ));
+    }
+
+    /// Отпечаток в шестнадцатеричном виде; сам отпечаток можно дополнять и дальше
+    pub fn hex(&self) -> String {
+        hex(&self.0.clone().finalize())
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.0.finalize().to_vec()
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Кэш в одном JSON-файле: `{"version": 2, "entries": {"<sha256>": {...}}}`, где ключ —
+/// хэш отпечатка параметров вместе с содержимым файла, а запись — полный `FileOutcome::to_record`. Записи не зависят от пути: переименованный
+/// файл тоже попадает в кэш. Потоки оценки обращаются к кэшу через общий замок.
+pub struct ScoreCache {
+    path: PathBuf,
+    fingerprint: Vec<u8>,
+    read: bool,
+    write: bool,
+    lang: Lang,
+    entries: Mutex<HashMap<String, serde_json::Value>>,
+    /// Новые записи: без них файл кэша не перезаписывается
+    added: AtomicUsize,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl ScoreCache {
+    /// Читает кэш; отсутствующий файл — пустой кэш, повреждённый — пустой кэш с предупреждением
+    /// (всё будет посчитано заново и записано поверх).
+    pub fn open(path: &Path, fingerprint: Fingerprint, read: bool, write: bool, opts: &ScoreOptions) -> Self {
+        let entries = match std::fs::read_to_string(path) {
+            Ok(text) => {
+                let parsed = serde_json::from_str::<serde_json::Value>(&text).ok().and_then(|json| {
+                    let serde_json::Value::Object(entries) = &json["entries"] else {
+                        return None;
+                    };
+                    (json["version"] == CACHE_VERSION).then(|| entries.clone().into_iter().collect())
+                });
+                parsed.unwrap_or_else(|| {
+                    warnings::emit_run(vec![Warning::CacheCorrupt { path: path.to_path_buf() }]);
+                    HashMap::new()
+                })
+            }
+            Err(_) => HashMap::new(),
+        };
+        ScoreCache {
+            path: path.to_path_buf(),
+            fingerprint: fingerprint.finish(),
+            read,
+            write,
+            lang: opts.lang,
+            entries: Mutex::new(entries),
+            added: AtomicUsize::new(0),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    fn key(&self, text: &str) -> String {
+        let mut h = Sha256::new();
+        h.update(&self.fingerprint);
+        h.update(text);
+        hex(&h.finalize())
+    }
+
+    /// Ключ текста и исход из кэша, если он там есть (и чтение не отключено --no-cache-read)
+    pub fn lookup(&self, text: &str) -> (String, Option<FileOutcome>) {
+        let key = self.key(text);
+        let found = if self.read {
+            let entries = self.entries.lock().unwrap();
+            entries.get(&key).and_then(FileOutcome::from_record)
+        } else {
+            None
+        };
+        let counter = if found.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        (key, found)
+    }
+
+    /// Запоминает исход; ошибки и превышения --timeout не кэшируются, как и всё при --no-cache-write
+    pub fn store(&self, key: String, outcome: &FileOutcome) {
+        if !self.write {
+            return;
+        }
+        let Some(rec) = outcome.to_record(self.lang) else { return };
+        self.entries.lock().unwrap().insert(key, rec);
+        self.added.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Попадания и промахи
+    pub fn stats(&self) -> (usize, usize) {
+        (self.hits.load(Ordering::Relaxed), self.misses.load(Ordering::Relaxed))
+    }
+
+    /// Записывает кэш, если в нём появились новые записи
+    pub fn save(self) -> Result<()> {
+        if self.added.into_inner() == 0 {
+            return Ok(());
+        }
+        let entries: serde_json::Map<String, serde_json::Value> =
+            self.entries.into_inner().unwrap().into_iter().collect();
+        let json = serde_json::json!({ "version": CACHE_VERSION, "entries": entries });
+        write_file_atomically(&self.path, &format!("{json}\n"))
+    }
+}
@@ -0,0 +1,66 @@
+//! Свёртка регистра для поиска в словаре: одна и та же функция применяется к ключам словаря
+//! при загрузке и к словам текста на стадии lowercase, так что «STRASSE» и «straße»,
+//! «ΛΟΓΟΣ» и «λογος» сходятся к одной форме.
+
+use std::borrow::Cow;
+
+use clap::ValueEnum;
+
+/// Правила свёртки
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Locale {
+    /// Общие правила Unicode
+    #[default]
+    Default,
+    /// Турецкие и азербайджанские: I → ı, İ → i
+    Tr,
+}
+
+/// Полная свёртка, которую не даёт `char::to_lowercase`: символы, раскладывающиеся в несколько
+/// букв, и варианты греческих букв (выборка из CaseFolding.txt для букв, встречающихся в словах)
+const FOLDS: &[(char, &str)] = &[
+    ('ß', "ss"),
+    ('ẞ', "ss"),
+    ('ſ', "s"),
+    ('ς', "σ"),
+    ('ϐ', "β"),
+    ('ϑ', "θ"),
+    ('ϕ', "φ"),
+    ('ϖ', "π"),
+    ('ϰ', "κ"),
+    ('ϱ', "ρ"),
+    ('ϵ', "ε"),
+    ('ﬀ', "ff"),
+    ('ﬁ', "fi"),
+    ('ﬂ', "fl"),
+    ('ﬃ', "ffi"),
+    ('ﬄ', "ffl"),
+    ('ﬅ', "st"),
+    ('ﬆ', "st"),
+];
+
+/// Слово в свёрнутом регистре. ASCII-слова без заглавных возвращаются как есть, прочие
+/// ASCII-слова — через `to_ascii_lowercase`; полная свёртка нужна только для остальных.
+/// İ сворачивается в i при любых правилах (по Unicode было бы i с комбинируемой точкой,
+/// которое не совпадёт ни с одним ключом словаря), I в ı — только с `Locale::Tr`.
+pub fn fold(word: &str, locale: Locale) -> Cow<'_, str> {
+    let turkish_i = locale == Locale::Tr && word.contains('I');
+    if word.is_ascii() && !turkish_i {
+        return match word.bytes().any(|b| b.is_ascii_uppercase()) {
+            true => Cow::Owned(word.to_ascii_lowercase()),
+            false => Cow::Borrowed(word),
+        };
+    }
+    let mut out = String::with_capacity(word.len());
+    for c in word.chars() {
+        match c {
+            'I' if locale == Locale::Tr => out.push('ı'),
+            'İ' => out.push('i'),
+            _ => match FOLDS.iter().find(|(from, _)| *from == c) {
+                Some((_, to)) => out.push_str(to),
+                None => out.extend(c.to_lowercase()),
+            },
+        }
+    }
+    Cow::Owned(out)
+}
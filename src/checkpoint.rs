@@ -0,0 +1,157 @@
+//! Файл результатов корпуса (--results-out) и продолжение прерванной оценки (--resume).
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use anyhow::{bail, Context, Result};
+
+use readability::Lang;
+
+use crate::corpus::{FileOutcome, FileResult};
+use crate::i18n::Msg;
+
+/// Размер и время изменения файла: по ним видно, что файл не менялся с прошлой оценки
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileStamp {
+    pub size: u64,
+    pub mtime_ns: u64,
+}
+
+impl FileStamp {
+    pub fn of(path: &Path) -> Option<Self> {
+        let meta = std::fs::metadata(path).ok()?;
+        let mtime = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(FileStamp {
+            size: meta.len(),
+            mtime_ns: u64::try_from(mtime.as_nanos()).ok()?,
+        })
+    }
+}
+
+/// Результат из прошлого запуска; переиспользуется, только если файл с тех пор не менялся
+struct Previous {
+    stamp: FileStamp,
+    outcome: FileOutcome,
+    provenance: Option<serde_json::Value>,
+}
+
+/// Версия формата файла результатов; файл другой версии не продолжается
+const CHECKPOINT_VERSION: u64 = 1;
+
+/// Файл результатов: записи дописываются по мере готовности, по одной строке JSON на файл.
+/// Кроме обычного вывода строка несёт полную запись исхода `"record"`: по ней --resume
+/// восстанавливает отчёт без потерь. Потоки пишут через общий замок, поэтому строки разных
+/// файлов не перемешиваются. Первая строка — заголовок
+/// `{"checkpoint": {"version": 1, "fingerprint": "<sha256>"}}` с отпечатком словарей и настроек.
+pub struct Checkpoint {
+    out: Mutex<File>,
+    /// Первая ошибка записи: потоки оценки её не обрабатывают, её возвращает `finish`
+    error: Mutex<Option<io::Error>>,
+    previous: HashMap<PathBuf, Previous>,
+    lang: Lang,
+}
+
+impl Checkpoint {
+    /// Без `resume` файл перезаписывается; с `resume` прежние записи читаются (последняя запись
+    /// о файле главнее, нечитаемые строки пропускаются) и новые дописываются в конец. Файл,
+    /// записанный с другим отпечатком `fingerprint` (словари и настройки), не продолжается: его
+    /// оценки уже не соответствуют тому, что посчитал бы этот запуск.
+    pub fn open(path: &Path, resume: bool, fingerprint: &str, lang: Lang) -> Result<Self> {
+        let header = serde_json::json!({ "checkpoint": { "version": CHECKPOINT_VERSION, "fingerprint": fingerprint } });
+        // None — заголовок файла не совпал с этим запуском
+        let open = || -> io::Result<Option<(File, HashMap<PathBuf, Previous>)>> {
+            if !resume {
+                let mut f = File::create(path)?;
+                writeln!(f, "{header}")?;
+                return Ok(Some((f, HashMap::new())));
+            }
+            let mut f = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+            let mut text = String::new();
+            f.read_to_string(&mut text)?;
+            let mut lines = text.lines();
+            match lines.next() {
+                None => {
+                    writeln!(f, "{header}")?;
+                    return Ok(Some((f, HashMap::new())));
+                }
+                Some(first) if serde_json::from_str::<serde_json::Value>(first).ok() != Some(header.clone()) => return Ok(None),
+                Some(_) => {}
+            }
+            let mut previous = HashMap::new();
+            for rec in lines.filter_map(|l| serde_json::from_str::<serde_json::Value>(l).ok()) {
+                let (Some(p), Some(size), Some(mtime_ns)) =
+                    (rec["path"].as_str(), rec["size"].as_u64(), rec["mtime_ns"].as_u64())
+                else {
+                    continue;
+                };
+                let stamp = FileStamp { size, mtime_ns };
+                match FileOutcome::from_record(&rec["record"]) {
+                    Some(outcome) => {
+                        let provenance = rec.get("provenance").cloned();
+                        previous.insert(PathBuf::from(p), Previous { stamp, outcome, provenance })
+                    }
+                    None => previous.remove(Path::new(p)),
+                };
+            }
+            // Оборванная при сбое последняя строка не должна склеиться с первой новой записью
+            if !text.is_empty() && !text.ends_with('\n') {
+                f.seek(SeekFrom::End(0))?;
+                f.write_all(b"\n")?;
+            }
+            Ok(Some((f, previous)))
+        };
+        let Some((out, previous)) = open().with_context(|| Msg::FileWrite(path).to_string())? else {
+            bail!("{}", Msg::ResumeMismatch(path));
+        };
+        Ok(Checkpoint {
+            out: Mutex::new(out),
+            error: Mutex::new(None),
+            previous,
+            lang,
+        })
+    }
+
+    /// Результат прошлого запуска, если файл не менялся с тех пор
+    pub fn reuse(&self, path: &Path) -> Option<FileResult> {
+        let prev = self.previous.get(path)?;
+        (FileStamp::of(path)? == prev.stamp).then(|| FileResult {
+            path: path.to_path_buf(),
+            outcome: prev.outcome.clone(),
+            deduped: None,
+            reused: true,
+            cached: false,
+            provenance: prev.provenance.clone(),
+        })
+    }
+
+    /// Дописывает запись о файле; `stamp` снят до оценки, чтобы правка во время оценки
+    /// не выдала старую оценку за актуальную
+    pub fn record(&self, result: &FileResult, stamp: Option<FileStamp>) {
+        let mut json = result.to_json(self.lang, false);
+        if let Some(rec) = result.outcome.to_record(self.lang) {
+            json["record"] = rec;
+        }
+        if let Some(stamp) = stamp {
+            json["size"] = stamp.size.into();
+            json["mtime_ns"] = stamp.mtime_ns.into();
+        }
+        let line = format!("{json}\n");
+        let written = self.out.lock().unwrap().write_all(line.as_bytes());
+        if let Err(e) = written {
+            self.error.lock().unwrap().get_or_insert(e);
+        }
+    }
+
+    pub fn finish(self, path: &Path) -> Result<()> {
+        let error = self.error.into_inner().unwrap();
+        let synced = match error {
+            Some(e) => Err(e),
+            None => self.out.into_inner().unwrap().sync_data(),
+        };
+        synced.with_context(|| Msg::FileWrite(path).to_string())
+    }
+}
@@ -0,0 +1,317 @@
+//! Структурная сложность предложений без синтаксического разбора (--metric clause-density):
+//! оценка числа клауз по служебным словам и знакам препинания и глубины вставок.
+//!
+//! Предложение делится на отрезки границами — запятой, точкой с запятой, двоеточием, скобкой
+//! или тире между словами. Клаузы считаются так:
+//! - первое слово предложения начинает главную клаузу, если это не подчинительный союз;
+//! - подчинительный союз или относительное местоимение (`because`, `which`, `что`, `который`,
+//!   `weil`) начинает клаузу в любом месте; подряд идущие («потому что») считаются одной;
+//! - сочинительный союз сразу после границы (`, and`, `, но`) или любое слово после точки
+//!   с запятой начинает независимую клаузу; без запятой союз считается однородными членами;
+//!   так же после границы считаются немецкие относительные `der`, `die`, `das`;
+//! - отрезок без такого начала, в котором есть вспомогательный глагол или связка (`is`, `was`,
+//!   `будет`, `hat`), — клауза, если главная ещё не встретилась (`Although it rained, we were
+//!   happy`) или предыдущий отрезок — главная или независимая клауза (`It rained, we were sad`).
+//!   После придаточного или вставки без глагола такой отрезок — продолжение главной
+//!   (`The cat, which was old, is here` — две клаузы).
+//!
+//! Глубина вставок — наибольшая вложенность скобок `()` и `[]` и пар тире внутри предложения.
+//! Тире — `—`, отбитое пробелом `–` и `--`; тире перед первым словом (реплика диалога)
+//! и непарное последнее тире (пояснение в конце) вставку не образуют.
+//!
+//! Правила заведомо приблизительны, но детерминированы: один и тот же текст всегда даёт
+//! одни и те же числа.
+
+use crate::{metrics, Lang, PreparedText, ScoreOptions, Token};
+
+/// Оценка одного предложения
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SentenceClauses {
+    /// Байтовый диапазон предложения во входном тексте: от начала первого слова до конца последнего
+    pub start: usize,
+    pub end: usize,
+    pub clauses: usize,
+    pub depth: usize,
+}
+
+/// Средняя сложность и разбор по предложениям
+#[derive(Clone, Debug)]
+pub struct ClauseReport {
+    pub mean: f64,
+    pub max_clauses: usize,
+    /// Предложений, в которых клауз больше `max_clauses`
+    pub over: usize,
+    pub sentences: Vec<SentenceClauses>,
+}
+
+impl ClauseReport {
+    pub fn to_json(&self) -> serde_json::Value {
+        let sentences: Vec<serde_json::Value> = self
+            .sentences
+            .iter()
+            .map(|s| {
+                serde_json::json!({
+                    "start": s.start,
+                    "end": s.end,
+                    "clauses": s.clauses,
+                    "depth": s.depth,
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "mean": self.mean,
+            "max_clauses": self.max_clauses,
+            "over": self.over,
+            "sentences": sentences,
+        })
+    }
+
+    /// Обратно из `to_json`
+    pub fn from_json(v: &serde_json::Value) -> Option<ClauseReport> {
+        let sentences = v["sentences"]
+            .as_array()?
+            .iter()
+            .map(|s| {
+                let field = |key: &str| s[key].as_u64().map(|n| n as usize);
+                Some(SentenceClauses {
+                    start: field("start")?,
+                    end: field("end")?,
+                    clauses: field("clauses")?,
+                    depth: field("depth")?,
+                })
+            })
+            .collect::<Option<_>>()?;
+        Some(ClauseReport {
+            mean: v["mean"].as_f64()?,
+            max_clauses: v["max_clauses"].as_u64()? as usize,
+            over: v["over"].as_u64()? as usize,
+            sentences,
+        })
+    }
+
+    /// Предложения, в которых клауз больше `max_clauses`
+    pub fn offenders(&self) -> impl Iterator<Item = &SentenceClauses> {
+        self.sentences.iter().filter(|s| s.clauses > self.max_clauses)
+    }
+}
+
+/// Служебные слова языка (в нижнем регистре, как `Token::word`)
+struct Markers {
+    /// Начинают клаузу в любом месте
+    subordinators: &'static [&'static str],
+    /// Начинают клаузу только сразу после границы отрезка
+    after_boundary: &'static [&'static str],
+    /// Вспомогательные глаголы и связки: признак клаузы в отрезке без союза
+    verb_cues: &'static [&'static str],
+}
+
+fn markers(lang: Lang) -> &'static Markers {
+    // after, before, since и once чаще предлоги, чем союзы, поэтому в список не входят
+    const EN: Markers = Markers {
+        subordinators: &[
+            "because", "although", "though", "whereas", "while", "unless", "until", "if", "when", "whenever", "where",
+            "wherever", "whether", "which", "who", "whom", "whose",
+        ],
+        after_boundary: &["and", "but", "or", "so", "yet", "nor"],
+        verb_cues: &[
+            "is", "are", "was", "were", "am", "has", "have", "had", "do", "does", "did", "will", "would", "can",
+            "could", "shall", "should", "may", "might", "must",
+        ],
+    };
+    const DE: Markers = Markers {
+        subordinators: &[
+            "dass", "weil", "obwohl", "obgleich", "wenn", "als", "ob", "während", "damit", "bevor", "nachdem", "seit",
+            "seitdem", "bis", "falls", "sobald", "solange", "sodass", "indem", "wo", "welche", "welcher", "welches",
+            "welchem", "welchen",
+        ],
+        after_boundary: &[
+            "und", "oder", "aber", "denn", "sondern", "der", "die", "das", "dem", "den", "dessen", "deren",
+        ],
+        verb_cues: &[
+            "ist", "sind", "war", "waren", "bin", "bist", "hat", "haben", "hatte", "hatten", "wird", "werden", "wurde",
+            "wurden", "kann", "können", "konnte", "muss", "müssen", "soll", "sollen", "will", "wollen", "darf",
+            "dürfen",
+        ],
+    };
+    const RU: Markers = Markers {
+        subordinators: &[
+            "что", "чтобы", "потому", "поскольку", "если", "когда", "хотя", "пока", "где", "куда", "откуда", "чей",
+            "чья", "чьё", "чьи", "который", "которая", "которое", "которые", "которого", "которой", "которому",
+            "котором", "которым", "которую", "которых", "которыми",
+        ],
+        after_boundary: &["и", "а", "но", "или", "да", "однако", "зато"],
+        verb_cues: &[
+            "есть", "был", "была", "было", "были", "буду", "будешь", "будет", "будем", "будете", "будут", "может",
+            "могут", "мог", "могла", "могли", "нужно", "надо", "можно", "нельзя",
+        ],
+    };
+    match lang {
+        Lang::En => &EN,
+        Lang::De => &DE,
+        Lang::Ru => &RU,
+    }
+}
+
+/// Тире-граница в позиции `i` строки `s`: его длина в байтах
+fn dash_at(s: &str, i: usize) -> Option<usize> {
+    let rest = &s[i..];
+    if rest.starts_with('—') {
+        return Some('—'.len_utf8());
+    }
+    if rest.starts_with("--") {
+        return Some(2);
+    }
+    let after = rest.strip_prefix('–')?;
+    let spaced = s[..i].ends_with(char::is_whitespace) || after.starts_with(char::is_whitespace);
+    spaced.then_some('–'.len_utf8())
+}
+
+fn is_boundary(gap: &str) -> bool {
+    gap.contains([',', ';', ':', '(', ')', '[', ']']) || gap.char_indices().any(|(i, _)| dash_at(gap, i).is_some())
+}
+
+/// Вид отрезка для правила о вспомогательном глаголе
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Segment {
+    /// Главная или независимая клауза
+    Main,
+    /// Придаточное
+    Subordinate,
+    /// Отрезок без союза и глагола: перечисление, приложение, вставка
+    Other,
+}
+
+/// Число клауз предложения из слов `tokens`; `text` — токенизированный текст, `start` — начало
+/// предложения в нём
+fn count_clauses(text: &str, start: usize, tokens: &[Token], m: &Markers) -> usize {
+    let mut clauses = 0;
+    let mut main = false;
+    let mut prev = Segment::Other;
+    let mut cur = Segment::Other;
+    let mut counted = false;
+    let mut last_marker = false;
+    let mut prev_end = start;
+    for (i, t) in tokens.iter().enumerate() {
+        // У слов из одного раскрытого сокращения промежутка нет
+        let gap = text.get(prev_end..t.start).unwrap_or("");
+        prev_end = prev_end.max(t.end);
+        let boundary = i > 0 && is_boundary(gap);
+        if boundary {
+            prev = cur;
+            cur = Segment::Other;
+            counted = false;
+            last_marker = false;
+        }
+        let word = t.word.as_str();
+        if m.subordinators.contains(&word) {
+            if !last_marker {
+                clauses += 1;
+            }
+            if !counted {
+                cur = Segment::Subordinate;
+                counted = true;
+            }
+            last_marker = true;
+            continue;
+        }
+        last_marker = false;
+        if boundary && (m.after_boundary.contains(&word) || gap.contains(';')) {
+            clauses += 1;
+            cur = Segment::Main;
+            counted = true;
+            last_marker = m.after_boundary.contains(&word);
+            continue;
+        }
+        if i == 0 {
+            clauses += 1;
+            main = true;
+            cur = Segment::Main;
+            counted = true;
+            continue;
+        }
+        if !counted && m.verb_cues.contains(&word) && (!main || prev == Segment::Main) {
+            clauses += 1;
+            main = true;
+            cur = Segment::Main;
+            counted = true;
+        }
+    }
+    clauses.max(1)
+}
+
+/// Глубина вставок в `sentence`; `first_word` — смещение первого слова в нём
+fn nesting_depth(sentence: &str, first_word: usize) -> usize {
+    // (позиция, изменение глубины); закрывающее событие в той же позиции идёт после открывающего
+    let mut events: Vec<(usize, i32)> = Vec::new();
+    let mut dashes = Vec::new();
+    let mut chars = sentence.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '(' | '[' => events.push((i, 1)),
+            ')' | ']' => events.push((i, -1)),
+            _ => {
+                if let Some(len) = dash_at(sentence, i) {
+                    if i >= first_word {
+                        dashes.push(i);
+                    }
+                    // `--` — одно тире
+                    if len == 2 {
+                        chars.next();
+                    }
+                }
+            }
+        }
+    }
+    for pair in dashes.chunks_exact(2) {
+        events.push((pair[0], 1));
+        events.push((pair[1], -1));
+    }
+    events.sort_by_key(|&(pos, delta)| (pos, -delta));
+    let mut depth = 0usize;
+    let mut max = 0;
+    for (_, delta) in events {
+        depth = if delta > 0 { depth + 1 } else { depth.saturating_sub(1) };
+        max = max.max(depth);
+    }
+    max
+}
+
+/// Разбор предложений оцениваемой части текста (с учётом --top-text-words и --top-text-sentences).
+/// Предложения без слов пропускаются; None — если не осталось ни одного.
+pub fn analyze(prepared: &PreparedText, opts: &ScoreOptions) -> Option<ClauseReport> {
+    let tokens = prepared.analyzed_tokens();
+    let text = &prepared.text;
+    let spans = prepared.input_spans(tokens);
+    let m = markers(opts.lang);
+    let mut sentences = Vec::new();
+    let mut first = 0;
+    let mut sentence_start = 0;
+    let ends = metrics::sentence_ends(text, opts.typographic).chain(std::iter::once(text.len()));
+    for end in ends {
+        let last = first + tokens[first..].partition_point(|t| t.start < end);
+        if last > first {
+            let words = &tokens[first..last];
+            sentences.push(SentenceClauses {
+                start: spans[first].0,
+                end: spans[last - 1].1,
+                clauses: count_clauses(text, words[0].start, words, m),
+                depth: nesting_depth(&text[sentence_start..end], words[0].start - sentence_start),
+            });
+        }
+        first = last;
+        sentence_start = end;
+        if first == tokens.len() {
+            break;
+        }
+    }
+    if sentences.is_empty() {
+        return None;
+    }
+    let total: usize = sentences.iter().map(|s| s.clauses).sum();
+    Some(ClauseReport {
+        mean: total as f64 / sentences.len() as f64,
+        max_clauses: opts.max_clauses,
+        over: sentences.iter().filter(|s| s.clauses > opts.max_clauses).count(),
+        sentences,
+    })
+}
@@ -0,0 +1,126 @@
+//! Раскрытие английских сокращений (won't → will not) перед поиском в словаре.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::{Error, FileKind, Result, Token};
+
+/// Встроенная таблица: форма → раскрытие (всё в нижнем регистре, как и токены)
+const BUILTIN: &[(&str, &str)] = &[
+    ("won't", "will not"),
+    ("can't", "cannot"),
+    ("shan't", "shall not"),
+    ("ain't", "is not"),
+    ("let's", "let us"),
+    ("i'm", "i am"),
+    ("isn't", "is not"),
+    ("aren't", "are not"),
+    ("wasn't", "was not"),
+    ("weren't", "were not"),
+    ("don't", "do not"),
+    ("doesn't", "does not"),
+    ("didn't", "did not"),
+    ("haven't", "have not"),
+    ("hasn't", "has not"),
+    ("hadn't", "had not"),
+    ("couldn't", "could not"),
+    ("wouldn't", "would not"),
+    ("shouldn't", "should not"),
+    ("mustn't", "must not"),
+    ("needn't", "need not"),
+];
+
+/// Общие правила для клитик, не попавших в таблицу: основа + раскрытие клитики.
+/// 's неоднозначно (is/has/притяжательное), поэтому клитика просто отбрасывается.
+const CLITICS: &[(&str, Option<&str>)] = &[
+    ("n't", Some("not")),
+    ("'ll", Some("will")),
+    ("'ve", Some("have")),
+    ("'re", Some("are")),
+    ("'d", Some("would")),
+    ("'m", Some("am")),
+    ("'s", None),
+];
+
+#[derive(Clone, Debug)]
+pub struct Contractions {
+    table: HashMap<String, Vec<String>>,
+}
+
+impl Contractions {
+    pub fn builtin() -> Self {
+        let table = BUILTIN
+            .iter()
+            .map(|(form, exp)| (form.to_string(), exp.split(' ').map(str::to_string).collect()))
+            .collect();
+        Contractions { table }
+    }
+
+    /// Дополняет встроенную таблицу записями из TSV-файла `форма<TAB>раскрытие`;
+    /// записи файла имеют приоритет. Пустые строки и строки с # пропускаются.
+    pub fn with_file(mut self, path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|source| Error::Io {
+            kind: FileKind::Contractions,
+            path: path.to_path_buf(),
+            source,
+        })?;
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((form, expansion)) = line.split_once('\t') else {
+                return Err(Error::ContractionsBadLine {
+                    path: path.to_path_buf(),
+                    line: i + 1,
+                });
+            };
+            let words: Vec<String> = expansion.split_whitespace().map(str::to_lowercase).collect();
+            if words.is_empty() {
+                return Err(Error::ContractionsEmptyExpansion {
+                    path: path.to_path_buf(),
+                    line: i + 1,
+                });
+            }
+            self.table.insert(form.trim().to_lowercase(), words);
+        }
+        Ok(self)
+    }
+
+    fn expand_one(&self, token: &str) -> Option<Vec<String>> {
+        if let Some(words) = self.table.get(token) {
+            return Some(words.clone());
+        }
+        for (clitic, expansion) in CLITICS {
+            if let Some(stem) = token.strip_suffix(clitic) {
+                if stem.is_empty() {
+                    continue;
+                }
+                let mut words = vec![stem.to_string()];
+                words.extend(expansion.map(str::to_string));
+                return Some(words);
+            }
+        }
+        None
+    }
+
+    /// Заменяет сокращения их раскрытием; возвращает новый поток токенов и число замен.
+    pub fn expand(&self, tokens: Vec<Token>) -> (Vec<Token>, usize) {
+        let mut out = Vec::with_capacity(tokens.len());
+        let mut expanded = 0;
+        for t in tokens {
+            if !t.word.contains('\'') {
+                out.push(t);
+                continue;
+            }
+            match self.expand_one(&t.word) {
+                Some(words) => {
+                    expanded += 1;
+                    out.extend(words.into_iter().map(|w| Token::word(w, t.start, t.end)));
+                }
+                None => out.push(t),
+            }
+        }
+        (out, expanded)
+    }
+}
@@ -0,0 +1,819 @@
+//! Режим корпуса: оценка нескольких файлов и каталогов, в том числе параллельно.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+
+use readability::dict::FrequencyDict;
+use readability::timings::{self, DictTimings};
+use readability::{
+    insufficient_json, language_mismatch, prepare_text, read_input_hashed, read_input_limited, read_input_text, score_prepared, tokenize,
+    Lang, ScoreOptions, ScoreReport,
+};
+
+use crate::cache::ScoreCache;
+use crate::checkpoint::{Checkpoint, FileStamp};
+use crate::i18n::{self, Msg};
+use crate::self_relative::CorpusCounts;
+use crate::template::Template;
+use crate::{csv_field, report_json, verbose_lines, warning_text, warnings, OutputFormat};
+
+/// Что получилось с одним файлом корпуса
+#[derive(Clone, Debug)]
+pub enum FileOutcome {
+    Scored(Box<ScoreReport>),
+    /// Слов меньше --min-tokens: оценка не выставляется, но это не ошибка
+    Insufficient { tokens: usize },
+    /// Файл прочитан, но оценивать нечего (например, нет ни одного слова)
+    Skipped(String),
+    /// Двоичный файл (см. `BinaryFilter`): пропущен без чтения целиком и без ошибки
+    Binary,
+    Failed(String),
+    /// Файл больше --max-input-bytes или --max-tokens: ошибка с "reason", как в ответе демона
+    TooLarge(String),
+    /// Оценка не уложилась в --timeout; считается ошибкой, но в выводе отмечена отдельно
+    TimedOut(String),
+}
+
+impl FileOutcome {
+    /// Исход для ошибки библиотеки: превышения ограничений отделяются от прочих
+    fn from_error(e: &readability::Error) -> Self {
+        match e {
+            readability::Error::InputTooLarge(_) => FileOutcome::TooLarge(i18n::describe(e)),
+            readability::Error::Timeout(_) => FileOutcome::TimedOut(i18n::describe(e)),
+            _ => FileOutcome::Failed(i18n::describe(e)),
+        }
+    }
+
+    /// Ошибка: влияет на код выхода и не кэшируется
+    pub fn is_error(&self) -> bool {
+        matches!(self, FileOutcome::Failed(_) | FileOutcome::TooLarge(_) | FileOutcome::TimedOut(_))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FileResult {
+    pub path: PathBuf,
+    pub outcome: FileOutcome,
+    /// Сколько строк-шаблонов исключено (--dedupe-lines)
+    pub deduped: Option<DedupeStats>,
+    /// Результат взят из файла результатов прошлого запуска (--resume), а не посчитан заново
+    pub reused: bool,
+    /// Результат взят из кэша оценок (--cache-scores)
+    pub cached: bool,
+    /// Объект "provenance" записи (--with-provenance): хэш файла и общие для корпуса сведения
+    pub provenance: Option<serde_json::Value>,
+}
+
+/// Исключённые повторяющиеся строки одного файла
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DedupeStats {
+    pub lines: usize,
+    pub tokens: usize,
+}
+
+/// Строки, повторяющиеся в большой доле файлов корпуса (навигация, подвалы и т. п.).
+/// Хранятся только хэши строк, чтобы предварительный проход не держал тексты в памяти.
+#[derive(Clone, Debug, Default)]
+pub struct Boilerplate {
+    lines: HashSet<u64>,
+}
+
+fn line_hash(line: &str) -> u64 {
+    let mut h = DefaultHasher::new();
+    line.hash(&mut h);
+    h.finish()
+}
+
+impl Boilerplate {
+    /// Предварительный проход: строка (без пробелов по краям, непустая) считается шаблоном,
+    /// если встречается больше чем в `threshold` доле прочитанных файлов. Нечитаемые файлы
+    /// пропускаются — ошибку о них сообщит сама оценка.
+    pub fn scan(files: &[PathBuf], threshold: f64) -> Self {
+        let mut counts: HashMap<u64, usize> = HashMap::new();
+        let mut read = 0usize;
+        for path in files {
+            let Ok(text) = read_input_text(Some(path)) else { continue };
+            read += 1;
+            let distinct: HashSet<u64> = text
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(line_hash)
+                .collect();
+            for h in distinct {
+                *counts.entry(h).or_insert(0) += 1;
+            }
+        }
+        let lines = counts
+            .into_iter()
+            .filter(|&(_, n)| n as f64 > threshold * read as f64)
+            .map(|(h, _)| h)
+            .collect();
+        Boilerplate { lines }
+    }
+
+    /// Набор строк-шаблонов в воспроизводимом виде: от него зависит оценка, а значит, и ключ кэша
+    pub fn digest(&self) -> Vec<u64> {
+        let mut lines: Vec<u64> = self.lines.iter().copied().collect();
+        lines.sort_unstable();
+        lines
+    }
+
+    /// Текст без строк-шаблонов и сколько строк и слов убрано
+    fn strip(&self, text: &str, opts: &ScoreOptions) -> (String, DedupeStats) {
+        let mut out = String::with_capacity(text.len());
+        let mut stats = DedupeStats::default();
+        for line in text.split_inclusive('\n') {
+            let trimmed = line.trim();
+            if !trimmed.is_empty() && self.lines.contains(&line_hash(trimmed)) {
+                stats.lines += 1;
+                stats.tokens += tokenize(trimmed, opts.lang, opts.keep_acronyms).len();
+            } else {
+                out.push_str(line);
+            }
+        }
+        (out, stats)
+    }
+}
+
+/// Причина пропуска двоичного файла в JSON и CSV; по ней же --resume восстанавливает исход
+pub const BINARY_REASON: &str = "binary";
+
+/// Сколько первых байт файла проверяется на нулевой байт
+const BINARY_SNIFF_BYTES: u64 = 8 * 1024;
+
+/// Распознавание двоичных файлов корпуса (PNG, PDF и т. п.), чтобы не оценивать их как текст:
+/// файл двоичный, если его расширение в списке --binary-extensions или в первых 8 КБ есть нулевой байт.
+#[derive(Clone, Debug)]
+pub struct BinaryFilter {
+    extensions: HashSet<String>,
+}
+
+impl BinaryFilter {
+    pub fn new(extensions: &[String]) -> Self {
+        let extensions = extensions
+            .iter()
+            .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+            .filter(|e| !e.is_empty())
+            .collect();
+        BinaryFilter { extensions }
+    }
+
+    /// Нечитаемый файл двоичным не считается: ошибку сообщит чтение текста
+    pub fn is_binary(&self, path: &Path) -> bool {
+        let ext = path.extension().and_then(|e| e.to_str()).map(str::to_lowercase);
+        if ext.is_some_and(|e| self.extensions.contains(&e)) {
+            return true;
+        }
+        let mut head = Vec::new();
+        match std::fs::File::open(path) {
+            Ok(file) => file.take(BINARY_SNIFF_BYTES).read_to_end(&mut head).is_ok() && head.contains(&0),
+            Err(_) => false,
+        }
+    }
+}
+
+impl FileOutcome {
+    /// Запись об исходе без пути к файлу
+    pub fn to_json(&self, lang: Lang, with_timings: bool) -> serde_json::Value {
+        match self {
+            FileOutcome::Scored(report) => {
+                let mut json = report_json(report, lang);
+                if with_timings {
+                    json["timings"] = report.timings.to_json(report.tokens);
+                }
+                json
+            }
+            FileOutcome::Insufficient { tokens } => insufficient_json(*tokens, lang),
+            FileOutcome::Skipped(reason) => serde_json::json!({ "skipped": reason }),
+            FileOutcome::Binary => serde_json::json!({ "skipped": BINARY_REASON }),
+            FileOutcome::Failed(err) => serde_json::json!({ "error": err }),
+            FileOutcome::TooLarge(err) => serde_json::json!({ "error": err, "reason": "input_too_large" }),
+            FileOutcome::TimedOut(err) => serde_json::json!({ "error": err, "reason": "timeout" }),
+        }
+    }
+
+    /// Полная запись исхода для --cache-scores и --results-out (см. `ScoreReport::to_record`);
+    /// у ошибок записи нет — такие файлы оцениваются заново
+    pub fn to_record(&self, lang: Lang) -> Option<serde_json::Value> {
+        match self {
+            FileOutcome::Scored(report) => Some(report.to_record(lang)),
+            FileOutcome::Failed(_) | FileOutcome::TooLarge(_) | FileOutcome::TimedOut(_) => None,
+            FileOutcome::Insufficient { .. } | FileOutcome::Skipped(_) | FileOutcome::Binary => Some(self.to_json(lang, false)),
+        }
+    }
+
+    /// Обратно из `to_record`
+    pub fn from_record(rec: &serde_json::Value) -> Option<FileOutcome> {
+        if rec["skipped"] == BINARY_REASON {
+            return Some(FileOutcome::Binary);
+        }
+        if let Some(reason) = rec["skipped"].as_str() {
+            return Some(FileOutcome::Skipped(reason.to_string()));
+        }
+        if rec["reason"] == "insufficient_tokens" {
+            let tokens = rec["tokens"].as_u64()? as usize;
+            return Some(FileOutcome::Insufficient { tokens });
+        }
+        ScoreReport::from_record(rec).map(|r| FileOutcome::Scored(Box::new(r)))
+    }
+}
+
+impl FileResult {
+    pub fn to_json(&self, lang: Lang, with_timings: bool) -> serde_json::Value {
+        let mut json = self.outcome.to_json(lang, with_timings);
+        json["path"] = serde_json::Value::from(self.path.display().to_string());
+        if self.reused {
+            json["reused"] = serde_json::Value::Bool(true);
+        }
+        if self.cached {
+            json["cached"] = serde_json::Value::Bool(true);
+        }
+        if let Some(p) = &self.provenance {
+            json["provenance"] = p.clone();
+        }
+        json
+    }
+
+    /// Строка CSV: `path,score,tokens,status,message`, с `tertiles` — и `,front,middle,back`
+    fn to_csv(&self, tertiles: bool) -> String {
+        let mut line = self.csv_base();
+        if tertiles {
+            for field in tertile_fields(&self.outcome, |s| s.to_string(), "") {
+                line.push(',');
+                line.push_str(&field);
+            }
+        }
+        line
+    }
+
+    fn csv_base(&self) -> String {
+        let path = self.path.display().to_string();
+        match &self.outcome {
+            FileOutcome::Scored(report) => {
+                format!("{},{},{},ok,", csv_field(&path), report.score, report.tokens)
+            }
+            FileOutcome::Insufficient { tokens } => {
+                format!("{},,{tokens},insufficient_tokens,", csv_field(&path))
+            }
+            FileOutcome::Skipped(reason) => format!("{},,,skipped,{}", csv_field(&path), csv_field(reason)),
+            FileOutcome::Binary => format!("{},,,skipped,{BINARY_REASON}", csv_field(&path)),
+            FileOutcome::Failed(err) | FileOutcome::TooLarge(err) => {
+                format!("{},,,error,{}", csv_field(&path), csv_field(err))
+            }
+            FileOutcome::TimedOut(err) => format!("{},,,timeout,{}", csv_field(&path), csv_field(err)),
+        }
+    }
+}
+
+/// Оценки третей файла для колонок --tertile-report; `missing` — для пустой трети и файла без оценки
+fn tertile_fields(outcome: &FileOutcome, format: impl Fn(f64) -> String, missing: &str) -> [String; 3] {
+    let scores = match outcome {
+        FileOutcome::Scored(report) => report.tertiles.map_or([None; 3], |t| t.scores),
+        _ => [None; 3],
+    };
+    scores.map(|s| s.map_or_else(|| missing.to_string(), &format))
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Упорядочивает оценённые файлы по оценке (при равенстве — по пути) и оставляет срез
+/// --top (первые N) или --bottom (последние N). Файлы без оценки не ранжируются:
+/// они идут после среза в исходном порядке.
+pub fn rank_results(
+    results: &[FileResult],
+    order: SortOrder,
+    top: Option<usize>,
+    bottom: Option<usize>,
+) -> Vec<FileResult> {
+    let (mut scored, rest): (Vec<&FileResult>, Vec<&FileResult>) = results
+        .iter()
+        .partition(|r| matches!(r.outcome, FileOutcome::Scored(_)));
+    let score = |r: &FileResult| match &r.outcome {
+        FileOutcome::Scored(report) => report.score,
+        _ => unreachable!("в ранжировании только оценённые файлы"),
+    };
+    scored.sort_by(|a, b| {
+        let by_score = score(a).total_cmp(&score(b));
+        let by_score = match order {
+            SortOrder::Asc => by_score,
+            SortOrder::Desc => by_score.reverse(),
+        };
+        by_score.then_with(|| a.path.cmp(&b.path))
+    });
+    if let Some(n) = top {
+        scored.truncate(n);
+    }
+    if let Some(n) = bottom {
+        let skip = scored.len().saturating_sub(n);
+        scored.drain(..skip);
+    }
+    scored.into_iter().chain(rest).cloned().collect()
+}
+
+/// Как обходить каталоги корпуса
+#[derive(Clone, Debug, Default)]
+pub struct WalkOptions {
+    /// Не учитывать .gitignore/.ignore (--no-ignore)
+    pub no_ignore: bool,
+    /// Глобы исключаемых путей (--exclude)
+    pub exclude: Vec<String>,
+}
+
+/// Разворачивает пути в список файлов: файлы берутся как есть, каталоги обходятся рекурсивно.
+/// Скрытые файлы и каталоги пропускаются, как и игнорируемые .gitignore/.ignore (если не
+/// --no-ignore) и --exclude; порядок сортируется, чтобы вывод был воспроизводимым.
+pub fn collect_inputs(paths: &[PathBuf], walk: &WalkOptions) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            walk_dir(path, walk, &mut files)?;
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+fn walk_dir(dir: &Path, walk: &WalkOptions, files: &mut Vec<PathBuf>) -> Result<()> {
+    let mut overrides = OverrideBuilder::new(dir);
+    for glob in &walk.exclude {
+        // В overrides глоб без `!` — белый список, с `!` — исключение
+        overrides
+            .add(&format!("!{glob}"))
+            .with_context(|| Msg::BadExcludeGlob(glob).to_string())?;
+    }
+    let overrides = overrides
+        .build()
+        .with_context(|| Msg::BadExcludeGlob(&walk.exclude.join(" ")).to_string())?;
+    let walker = WalkBuilder::new(dir)
+        .hidden(true)
+        .git_ignore(!walk.no_ignore)
+        .git_exclude(!walk.no_ignore)
+        .git_global(!walk.no_ignore)
+        .ignore(!walk.no_ignore)
+        .parents(!walk.no_ignore)
+        // .gitignore действует и вне git-репозитория (например, в распакованном архиве)
+        .require_git(false)
+        .overrides(overrides)
+        .sort_by_file_path(|a, b| a.cmp(b))
+        .build();
+    for entry in walker {
+        let entry = entry.with_context(|| Msg::DirRead(dir).to_string())?;
+        if entry.file_type().is_some_and(|t| t.is_file()) {
+            files.push(entry.into_path());
+        }
+    }
+    Ok(())
+}
+
+/// Общие для всех файлов корпуса параметры оценки
+pub struct CorpusJob<'a> {
+    pub dict: &'a FrequencyDict,
+    pub opts: &'a ScoreOptions,
+    /// Число потоков; 0 — по числу ядер
+    pub jobs: usize,
+    pub strict_lang: bool,
+    pub boilerplate: Option<&'a Boilerplate>,
+    /// Куда дописывать результаты по мере готовности (--results-out)
+    pub checkpoint: Option<&'a Checkpoint>,
+    pub cache: Option<&'a ScoreCache>,
+    /// С --self-relative-exclude-self: каждый файл оценивается по словарю корпуса без него самого
+    pub leave_one_out: Option<&'a CorpusCounts>,
+    /// Пропуск двоичных файлов; None при --force-text
+    pub binary: Option<&'a BinaryFilter>,
+    /// Общая часть "provenance" (--with-provenance): словарь, версия, отпечаток настроек;
+    /// к ней добавляется хэш каждого прочитанного файла
+    pub provenance: Option<&'a serde_json::Value>,
+}
+
+/// Исход оценки файла и признак того, что он взят из кэша. С `job.provenance` в `sha256`
+/// попадает хэш байт файла, посчитанный тем же проходом, которым он читается.
+fn score_file(
+    path: &Path,
+    job: &CorpusJob,
+    deduped: &mut Option<DedupeStats>,
+    sha256: &mut Option<String>,
+) -> (FileOutcome, bool) {
+    let opts = job.opts;
+    if job.binary.is_some_and(|b| b.is_binary(path)) {
+        return (FileOutcome::Binary, false);
+    }
+    let read_started = Instant::now();
+    let read = match job.provenance {
+        Some(_) => read_input_hashed(Some(path), opts.max_input_bytes).map(|(text, hash)| (text, Some(hash))),
+        None => read_input_limited(Some(path), opts.max_input_bytes).map(|text| (text, None)),
+    };
+    let mut text = match read {
+        Ok((text, hash)) => {
+            *sha256 = hash;
+            text
+        }
+        Err(e) => return (FileOutcome::from_error(&e), false),
+    };
+    let cache_key = match job.cache.map(|c| (c, c.lookup(&text))) {
+        Some((_, (_, Some(outcome)))) => {
+            if let FileOutcome::Scored(report) = &outcome {
+                warnings::emit(&report.warnings, Some(path));
+            }
+            return (outcome, true);
+        }
+        Some((c, (key, None))) => Some((c, key)),
+        None => None,
+    };
+    if let Some(boilerplate) = job.boilerplate {
+        let (stripped, stats) = boilerplate.strip(&text, opts);
+        text = stripped;
+        *deduped = Some(stats);
+    }
+    let read_time = read_started.elapsed();
+    let mismatch = language_mismatch(&text, opts.lang);
+    if let Some(warning) = mismatch.as_ref().filter(|_| job.strict_lang) {
+        let msg = warning_text(warning);
+        return (FileOutcome::Failed(Msg::StrictLangMismatch(&msg).to_string()), false);
+    }
+    let mut prepared = match prepare_text(&text, Some(job.dict), opts) {
+        Ok(prepared) => prepared,
+        Err(e) => return (FileOutcome::from_error(&e), false),
+    };
+    prepared.warnings.splice(0..0, mismatch);
+    let own_dict;
+    let dict = match job.leave_one_out {
+        Some(counts) => {
+            own_dict = match counts.dict_without(&prepared.tokens) {
+                Ok(d) => d,
+                Err(e) => return (FileOutcome::Failed(i18n::describe(&e)), false),
+            };
+            &own_dict
+        }
+        None => job.dict,
+    };
+    let report = match score_prepared(&prepared, dict, opts) {
+        Ok(report) => report,
+        Err(e) => return (FileOutcome::from_error(&e), false),
+    };
+    let tokens = report.as_ref().map_or(0, |r| r.tokens);
+    let outcome = if opts.insufficient(tokens) {
+        FileOutcome::Insufficient { tokens }
+    } else {
+        match report {
+            Some(mut report) => {
+                report.timings.read = read_time;
+                warnings::emit(&report.warnings, Some(path));
+                FileOutcome::Scored(Box::new(report))
+            }
+            None => FileOutcome::Skipped(i18n::no_words(&prepared.warnings)),
+        }
+    };
+    if let Some((cache, key)) = cache_key {
+        cache.store(key, &outcome);
+    }
+    (outcome, false)
+}
+
+/// Оценивает файлы в `job.jobs` потоках. Результаты возвращаются в порядке входа,
+/// независимо от того, в каком порядке их закончили потоки; в `job.checkpoint` каждый результат
+/// дописывается сразу по готовности.
+pub fn score_files(files: &[PathBuf], job: &CorpusJob) -> Vec<FileResult> {
+    let jobs = if job.jobs == 0 {
+        std::thread::available_parallelism().map_or(1, |n| n.get())
+    } else {
+        job.jobs
+    };
+    let jobs = jobs.min(files.len()).max(1);
+
+    let next = AtomicUsize::new(0);
+    let slots: Mutex<Vec<Option<FileResult>>> = Mutex::new(vec![None; files.len()]);
+    std::thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = files.get(i) else { break };
+                let stamp = job.checkpoint.and_then(|_| FileStamp::of(path));
+                let (mut deduped, mut sha256) = (None, None);
+                let (outcome, cached) = score_file(path, job, &mut deduped, &mut sha256);
+                let result = FileResult {
+                    path: path.clone(),
+                    outcome,
+                    deduped,
+                    reused: false,
+                    cached,
+                    provenance: job.provenance.zip(sha256).map(|(shared, sha256)| {
+                        let mut p = shared.clone();
+                        p["input"] = serde_json::json!({ "path": path.display().to_string(), "sha256": sha256 });
+                        p
+                    }),
+                };
+                if let Some(c) = job.checkpoint {
+                    c.record(&result, stamp);
+                }
+                slots.lock().unwrap()[i] = Some(result);
+            });
+        }
+    });
+
+    slots
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|slot| slot.expect("каждый файл обработан одним из потоков"))
+        .collect()
+}
+
+/// Подробности обработки каждого оценённого файла (--verbose) в STDERR.
+pub fn print_verbose(results: &[FileResult], opts: &ScoreOptions) {
+    for r in results {
+        if let Some(d) = r.deduped {
+            let msg = Msg::VerboseDeduped {
+                lines: d.lines,
+                tokens: d.tokens,
+            };
+            eprintln!("{}: {msg}", r.path.display());
+        }
+        match &r.outcome {
+            FileOutcome::Scored(report) => {
+                for line in verbose_lines(report, opts) {
+                    eprintln!("{}: {line}", r.path.display());
+                }
+            }
+            FileOutcome::Binary => eprintln!("{}: {}", r.path.display(), Msg::Skipped(BINARY_REASON)),
+            _ => {}
+        }
+    }
+}
+
+/// Оценка вместе с файлом, к которому она относится
+#[derive(Clone, Debug)]
+pub struct ScoredPath {
+    pub score: f64,
+    pub path: PathBuf,
+}
+
+/// Итоговая статистика по корпусу
+#[derive(Clone, Debug)]
+pub struct Summary {
+    pub scored: usize,
+    pub skipped: usize,
+    /// Пропущено двоичных файлов (в `skipped` не входят)
+    pub binary: usize,
+    pub errored: usize,
+    pub mean: Option<f64>,
+    pub median: Option<f64>,
+    pub min: Option<ScoredPath>,
+    pub max: Option<ScoredPath>,
+    pub tokens: usize,
+    /// Сколько результатов взято из прошлого запуска (--resume), остальные посчитаны заново
+    pub reused: usize,
+}
+
+/// Считает статистику по тем же результатам, что печатаются построчно.
+pub fn summarize(results: &[FileResult]) -> Summary {
+    let mut scored: Vec<ScoredPath> = Vec::new();
+    let mut skipped = 0;
+    let mut binary = 0;
+    let mut errored = 0;
+    let mut tokens = 0;
+    let reused = results.iter().filter(|r| r.reused).count();
+    for r in results {
+        match &r.outcome {
+            FileOutcome::Scored(report) => {
+                tokens += report.tokens;
+                scored.push(ScoredPath {
+                    score: report.score,
+                    path: r.path.clone(),
+                });
+            }
+            FileOutcome::Skipped(_) | FileOutcome::Insufficient { .. } => skipped += 1,
+            FileOutcome::Binary => binary += 1,
+            FileOutcome::Failed(_) | FileOutcome::TooLarge(_) | FileOutcome::TimedOut(_) => errored += 1,
+        }
+    }
+
+    // Сортировка по оценке, при равенстве — по пути, чтобы min/max не зависели от порядка входа
+    scored.sort_by(|a, b| a.score.total_cmp(&b.score).then_with(|| a.path.cmp(&b.path)));
+    let n = scored.len();
+    let mean = (n > 0).then(|| scored.iter().map(|s| s.score).sum::<f64>() / n as f64);
+    let median = (n > 0).then(|| {
+        if n % 2 == 1 {
+            scored[n / 2].score
+        } else {
+            (scored[n / 2 - 1].score + scored[n / 2].score) / 2.0
+        }
+    });
+
+    Summary {
+        scored: n,
+        skipped,
+        binary,
+        errored,
+        mean,
+        median,
+        min: scored.first().cloned(),
+        max: scored.last().cloned(),
+        tokens,
+        reused,
+    }
+}
+
+impl Summary {
+    fn to_json(&self) -> serde_json::Value {
+        let extreme = |s: &Option<ScoredPath>| {
+            s.as_ref().map(|s| {
+                serde_json::json!({ "score": s.score, "path": s.path.display().to_string() })
+            })
+        };
+        let mut json = serde_json::json!({
+            "files": self.scored,
+            "skipped": self.skipped,
+            "errored": self.errored,
+            "mean": self.mean,
+            "median": self.median,
+            "min": extreme(&self.min),
+            "max": extreme(&self.max),
+            "tokens": self.tokens,
+        });
+        if self.binary > 0 {
+            json["binary"] = self.binary.into();
+        }
+        if self.reused > 0 {
+            json["reused"] = self.reused.into();
+        }
+        json
+    }
+
+    fn write_plain(&self, out: &mut impl Write) -> io::Result<()> {
+        let num = |v: Option<f64>| v.map_or_else(|| "-".to_string(), |v| format!("{v:.6}"));
+        let extreme = |s: &Option<ScoredPath>| match s {
+            Some(s) => format!("{:.6}\t{}", s.score, s.path.display()),
+            None => "-".to_string(),
+        };
+        writeln!(out, "files: {}", self.scored)?;
+        writeln!(out, "skipped: {}", self.skipped)?;
+        if self.binary > 0 {
+            writeln!(out, "binary: {}", self.binary)?;
+        }
+        writeln!(out, "errored: {}", self.errored)?;
+        writeln!(out, "mean: {}", num(self.mean))?;
+        writeln!(out, "median: {}", num(self.median))?;
+        writeln!(out, "min: {}", extreme(&self.min))?;
+        writeln!(out, "max: {}", extreme(&self.max))?;
+        writeln!(out, "tokens: {}", self.tokens)?;
+        if self.reused > 0 {
+            writeln!(out, "reused: {}", self.reused)?;
+        }
+        Ok(())
+    }
+
+    /// В CSV статистика идёт строками-комментариями `# ...`, чтобы не ломать таблицу
+    fn write_csv_comment(&self, out: &mut impl Write) -> io::Result<()> {
+        let mut buf = Vec::new();
+        self.write_plain(&mut buf)?;
+        for line in String::from_utf8_lossy(&buf).lines() {
+            writeln!(out, "# {line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Печатает итоговую статистику отдельно (для режима одного входа с --summary=always).
+pub fn print_summary(summary: &Summary, format: OutputFormat) -> Result<()> {
+    let mut out = io::stdout().lock();
+    match format {
+        OutputFormat::Plain => summary.write_plain(&mut out)?,
+        OutputFormat::Csv => summary.write_csv_comment(&mut out)?,
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            writeln!(out, "{}", serde_json::json!({ "summary": summary.to_json() }))?
+        }
+    }
+    Ok(())
+}
+
+/// Печатает построчные результаты и, если передана, итоговую статистику последней записью.
+/// В plain-режиме строка файла — `оценка<TAB>путь` (с --auto-thresholds после оценки — метка,
+/// с --tertile-report — оценки третей), пропуски и ошибки уходят в STDERR; в CSV они остаются
+/// строками таблицы со статусом. С --template строка оценённого файла строится по шаблону;
+/// номер входа — его место в выводе, с нуля. В JSON предупреждения запуска (загрузка словаря)
+/// идут отдельной записью {"warnings": [...]} перед статистикой.
+pub fn print_results(
+    results: &[FileResult],
+    summary: Option<&Summary>,
+    timings: Option<(DictTimings, Duration)>,
+    format: OutputFormat,
+    lang: Lang,
+    template: Option<&Template>,
+    tertiles: bool,
+) -> Result<()> {
+    let mut out = io::stdout().lock();
+    let with_timings = timings.is_some();
+    let file_timings: Vec<timings::TextTimings> = results
+        .iter()
+        .filter_map(|r| match &r.outcome {
+            FileOutcome::Scored(report) => Some(report.timings),
+            _ => None,
+        })
+        .collect();
+    match format {
+        OutputFormat::Plain | OutputFormat::Csv => {
+            let csv = format == OutputFormat::Csv;
+            if csv {
+                let extra = if tertiles { ",front,middle,back" } else { "" };
+                writeln!(out, "path,score,tokens,status,message{extra}")?;
+            }
+            for (index, r) in results.iter().enumerate() {
+                if csv {
+                    writeln!(out, "{}", r.to_csv(tertiles))?;
+                    continue;
+                }
+                match &r.outcome {
+                    FileOutcome::Scored(report) if template.is_some() => {
+                        let template = template.expect("проверено в условии");
+                        writeln!(out, "{}", template.render(&r.path, index, report))?
+                    }
+                    FileOutcome::Scored(report) => {
+                        let mut fields = vec![format!("{:.6}", report.score)];
+                        fields.extend(report.label().map(|l| l.name().to_string()));
+                        if tertiles {
+                            fields.extend(tertile_fields(&r.outcome, |s| format!("{s:.6}"), "-"));
+                        }
+                        writeln!(out, "{}\t{}", fields.join("\t"), r.path.display())?
+                    }
+                    FileOutcome::Insufficient { tokens } => {
+                        eprintln!("{}: {}", r.path.display(), Msg::SkippedInsufficient(*tokens))
+                    }
+                    FileOutcome::Skipped(reason) => {
+                        eprintln!("{}: {}", r.path.display(), Msg::Skipped(reason))
+                    }
+                    // Двоичные файлы в plain-режиме не шумят: о них сообщает --verbose
+                    FileOutcome::Binary => {}
+                    FileOutcome::Failed(err) | FileOutcome::TooLarge(err) | FileOutcome::TimedOut(err) => {
+                        eprintln!("{}: {}", r.path.display(), Msg::Failed(err))
+                    }
+                }
+            }
+            if let Some((dict_t, wall)) = timings {
+                eprintln!("timings:");
+                for r in results {
+                    if let FileOutcome::Scored(report) = &r.outcome {
+                        eprintln!("  {}: {}", r.path.display(), report.timings.short_line());
+                    }
+                }
+                for line in timings::corpus_total_lines(&dict_t, &file_timings, wall) {
+                    eprintln!("  {line}");
+                }
+            }
+            match summary {
+                Some(summary) if csv => summary.write_csv_comment(&mut out)?,
+                Some(summary) => {
+                    writeln!(out)?;
+                    summary.write_plain(&mut out)?;
+                }
+                None => {}
+            }
+        }
+        OutputFormat::Jsonl => {
+            for r in results {
+                writeln!(out, "{}", r.to_json(lang, with_timings))?;
+            }
+            if let Some((dict_t, wall)) = timings {
+                let total = timings::corpus_total_json(&dict_t, &file_timings, wall);
+                writeln!(out, "{}", serde_json::json!({ "timings": total }))?;
+            }
+            if let Some(run) = warnings::to_json(&warnings::run_warnings()) {
+                writeln!(out, "{}", serde_json::json!({ "warnings": run }))?;
+            }
+            if let Some(summary) = summary {
+                writeln!(out, "{}", serde_json::json!({ "summary": summary.to_json() }))?;
+            }
+        }
+        OutputFormat::Json => {
+            let mut records: Vec<serde_json::Value> =
+                results.iter().map(|r| r.to_json(lang, with_timings)).collect();
+            if let Some((dict_t, wall)) = timings {
+                let total = timings::corpus_total_json(&dict_t, &file_timings, wall);
+                records.push(serde_json::json!({ "timings": total }));
+            }
+            if let Some(run) = warnings::to_json(&warnings::run_warnings()) {
+                records.push(serde_json::json!({ "warnings": run }));
+            }
+            if let Some(summary) = summary {
+                records.push(serde_json::json!({ "summary": summary.to_json() }));
+            }
+            writeln!(out, "{}", serde_json::Value::Array(records))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,177 @@
+//! Словарь демона с перезагрузкой без остановки: {"cmd": "reload"} читает словарь заново
+//! в фоновом потоке и подменяет его целиком, {"cmd": "status"} показывает действующий.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+use anyhow::Result;
+
+use readability::dict::{self, DictOptions, FrequencyDict};
+use readability::labels::Thresholds;
+use readability::ScoreOptions;
+
+use crate::dict_cmd::DictBlend;
+use crate::history;
+
+/// Откуда и как демон читает словарь — те же параметры, что при запуске
+#[derive(Clone, Debug)]
+pub struct DictSource {
+    pub path: PathBuf,
+    pub blend: Option<DictBlend>,
+    pub opts: DictOptions,
+    pub quantize: bool,
+    /// Имя словаря в ответах, как в поле "dict" отчёта
+    pub name: String,
+    /// Параметры оценки для порогов --auto-thresholds; None — без меток
+    pub thresholds: Option<ScoreOptions>,
+}
+
+impl DictSource {
+    pub fn load(&self) -> Result<FrequencyDict> {
+        let (dict, _) = match &self.blend {
+            Some(blend) => dict::load_blended_dict(&blend.0, self.opts)?,
+            None => dict::load_frequency_dict(&self.path, self.opts)?,
+        };
+        Ok(if self.quantize { dict.quantized() } else { dict })
+    }
+}
+
+/// Загруженный словарь, его пороги меток и время загрузки (UTC)
+pub struct ActiveDict {
+    pub dict: FrequencyDict,
+    pub thresholds: Option<Thresholds>,
+    pub loaded_at: String,
+}
+
+impl ActiveDict {
+    /// Пороги --auto-thresholds пересчитываются для каждого загруженного словаря
+    fn new(dict: FrequencyDict, source: &DictSource) -> Self {
+        let thresholds = source.thresholds.as_ref().and_then(|opts| Thresholds::derive(&dict, opts));
+        ActiveDict { dict, thresholds, loaded_at: history::utc_now() }
+    }
+}
+
+/// Действующий словарь. Запрос берёт `Arc` на словарь под коротким замком чтения и оценивает
+/// уже без замка: перезагрузка меняет только указатель, начатые оценки дочитывают старый словарь.
+pub struct DaemonDict {
+    active: RwLock<Arc<ActiveDict>>,
+    source: DictSource,
+    reloading: AtomicBool,
+}
+
+impl DaemonDict {
+    pub fn new(dict: FrequencyDict, source: DictSource) -> Self {
+        DaemonDict {
+            active: RwLock::new(Arc::new(ActiveDict::new(dict, &source))),
+            source,
+            reloading: AtomicBool::new(false),
+        }
+    }
+
+    pub fn current(&self) -> Arc<ActiveDict> {
+        Arc::clone(&self.active.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Начинает перезагрузку, если другая ещё не идёт; false — уже идёт
+    pub fn begin_reload(&self) -> bool {
+        !self.reloading.swap(true, Ordering::AcqRel)
+    }
+
+    /// Читает словарь заново и при успехе подменяет действующий; при ошибке прежний остаётся.
+    /// Вызывается после успешного `begin_reload`.
+    pub fn reload(&self) -> Result<Arc<ActiveDict>> {
+        let loaded = self.source.load().map(|dict| Arc::new(ActiveDict::new(dict, &self.source)));
+        if let Ok(fresh) = &loaded {
+            *self.active.write().unwrap_or_else(|e| e.into_inner()) = Arc::clone(fresh);
+        }
+        self.reloading.store(false, Ordering::Release);
+        loaded
+    }
+
+    /// Ответ на {"cmd": "status"} и на успешную перезагрузку: путь, число слов, время загрузки
+    /// и, с --auto-thresholds, пороги меток
+    pub fn status_json(&self, active: &ActiveDict) -> serde_json::Value {
+        let mut json = serde_json::json!({
+            "dict": self.source.name,
+            "entries": active.dict.len(),
+            "loaded_at": active.loaded_at,
+            "reloading": self.reloading.load(Ordering::Acquire),
+        });
+        if let Some(t) = &active.thresholds {
+            json["thresholds"] = t.to_json();
+        }
+        json
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use readability::dict::{DuplicatePolicy, Weighting};
+    use readability::score_text;
+
+    use super::*;
+
+    /// Оценки «cat dog» по двум версиям словаря: `dog` встречается в 10 или в 50 раз реже `cat`
+    const SCORES: [f64; 2] = [0.55, 0.75];
+
+    fn write_dict(path: &std::path::Path, version: usize) {
+        let dog = [10, 50][version];
+        std::fs::write(path, format!(r#"[["cat", 100], ["dog", {dog}]]"#)).unwrap();
+    }
+
+    #[test]
+    fn requests_are_scored_while_the_dict_reloads() {
+        let path = std::env::temp_dir().join(format!("readability-daemon-dict-{}.json", std::process::id()));
+        write_dict(&path, 0);
+        let source = DictSource {
+            path: path.clone(),
+            blend: None,
+            opts: DictOptions {
+                top_k: None,
+                coverage_target: None,
+                duplicates: DuplicatePolicy::Error,
+                weighting: Weighting::Linear,
+                retain_stats: false,
+                lenient: false,
+                locale: None,
+            },
+            quantize: false,
+            name: "test".into(),
+            thresholds: None,
+        };
+        let dicts = DaemonDict::new(source.load().unwrap(), source);
+        let opts = ScoreOptions::default();
+        let scored = AtomicUsize::new(0);
+        let done = AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    while !done.load(Ordering::Acquire) {
+                        let active = dicts.current();
+                        let score = score_text("cat dog", &active.dict, &opts).unwrap().unwrap().score;
+                        assert!(SCORES.iter().any(|s| (s - score).abs() < 1e-12), "{score}");
+                        scored.fetch_add(1, Ordering::Relaxed);
+                    }
+                });
+            }
+            while scored.load(Ordering::Relaxed) == 0 {
+                std::thread::yield_now();
+            }
+            for i in 1..=20 {
+                write_dict(&path, i % 2);
+                assert!(dicts.begin_reload());
+                // Вторая перезагрузка, пока идёт первая, не начинается
+                assert!(!dicts.begin_reload());
+                let active = dicts.reload().unwrap();
+                let score = score_text("cat dog", &active.dict, &opts).unwrap().unwrap().score;
+                assert!((score - SCORES[i % 2]).abs() < 1e-12);
+            }
+            done.store(true, Ordering::Release);
+        });
+        std::fs::remove_file(&path).unwrap();
+        assert!(!dicts.reloading.load(Ordering::Acquire));
+    }
+}
@@ -0,0 +1,32 @@
+//! Ограничение времени на один текст (--timeout): срок отсчитывается от начала токенизации
+//! и проверяется между стадиями и раз в `CHECK_EVERY` слов, так что даже огромный текст
+//! прерывается вскоре после срока, а не после полной оценки.
+
+use std::time::{Duration, Instant};
+
+use crate::{Error, Result};
+
+/// Через сколько слов токенизации сверять часы
+pub const CHECK_EVERY: usize = 4096;
+
+/// Срок обработки текста
+#[derive(Clone, Copy, Debug)]
+pub struct Deadline {
+    at: Instant,
+    limit: Duration,
+}
+
+impl Deadline {
+    /// Срок `limit` от текущего момента; None — без ограничения
+    pub fn start(limit: Option<Duration>) -> Option<Deadline> {
+        limit.map(|limit| Deadline { at: Instant::now() + limit, limit })
+    }
+}
+
+/// `Error::Timeout`, если срок прошёл
+pub fn check(deadline: Option<Deadline>) -> Result<()> {
+    match deadline {
+        Some(d) if Instant::now() >= d.at => Err(Error::Timeout(d.limit)),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,81 @@
+//! Склейка слов, перенесённых через строку с дефисом (OCR, выгрузки EPUB): «read-\nability».
+//! Отключается --no-dehyphenate.
+
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::dict::FrequencyDict;
+use crate::offsets::OffsetMap;
+use crate::Lang;
+
+/// Буквы, дефис, перевод строки (в том числе `\r\n`) и буквы; пробелы у переноса допускаются
+fn wrap_regex(lang: Lang) -> &'static Regex {
+    static EN: OnceLock<Regex> = OnceLock::new();
+    static DE: OnceLock<Regex> = OnceLock::new();
+    static RU: OnceLock<Regex> = OnceLock::new();
+    let cell = match lang {
+        Lang::En => &EN,
+        Lang::De => &DE,
+        Lang::Ru => &RU,
+    };
+    cell.get_or_init(|| {
+        let l = lang.letters();
+        Regex::new(&format!(r"([{l}]+)-[ \t]*\r?\n[ \t]*([{l}]+)")).unwrap()
+    })
+}
+
+/// Как склеить перенос `head-` / `tail`: None — оставить как есть
+fn join(head: &str, tail: &str, lang: Lang, dict: Option<&FrequencyDict>) -> Option<String> {
+    let known = |w: &str| dict.is_some_and(|d| d.contains(&lang.lowercase(w)));
+    let solid = format!("{head}{tail}");
+    if known(&solid) {
+        return Some(solid);
+    }
+    // Настоящий дефис («twenty-\nfirst»): слово остаётся через дефис и дальше делится
+    // или не делится по шаблону слова языка
+    let hyphenated = format!("{head}-{tail}");
+    if known(&hyphenated) || (known(head) && known(tail)) {
+        return Some(hyphenated);
+    }
+    // С заглавной буквы после переноса обычно начинается новое слово списка, а не продолжение
+    tail.chars().next().is_some_and(char::is_lowercase).then_some(solid)
+}
+
+/// Склеивает переносы: слитно, если слитное слово есть в словаре или вторая часть начинается
+/// со строчной буквы; через дефис, если в словаре есть дефисное слово или обе части по отдельности.
+/// Без словаря остаётся только правило строчной буквы. Возвращает текст, соответствие смещений
+/// и число склеенных слов.
+pub fn dehyphenate<'a>(text: &'a str, lang: Lang, dict: Option<&FrequencyDict>) -> (Cow<'a, str>, OffsetMap, usize) {
+    let mut offsets = OffsetMap::default();
+    if !text.contains('-') {
+        return (Cow::Borrowed(text), offsets, 0);
+    }
+    let mut out = String::new();
+    let mut joined = 0;
+    let (mut processed, mut original) = (0usize, 0usize);
+    let mut pos = 0;
+    for caps in wrap_regex(lang).captures_iter(text) {
+        let (head, tail) = (caps.get(1).unwrap(), caps.get(2).unwrap());
+        let Some(word) = join(head.as_str(), tail.as_str(), lang, dict) else {
+            continue;
+        };
+        let before = text[pos..head.start()].chars().count();
+        out.push_str(&text[pos..head.start()]);
+        out.push_str(&word);
+        // Начало второй части в новом тексте соответствует её началу во входном
+        processed += before + word.chars().count() - tail.as_str().chars().count();
+        original += before + text[head.start()..tail.start()].chars().count();
+        offsets.push(processed, original);
+        processed += tail.as_str().chars().count();
+        original += tail.as_str().chars().count();
+        pos = tail.end();
+        joined += 1;
+    }
+    if joined == 0 {
+        return (Cow::Borrowed(text), offsets, 0);
+    }
+    out.push_str(&text[pos..]);
+    (Cow::Owned(out), offsets, joined)
+}
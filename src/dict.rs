@@ -0,0 +1,810 @@
+//! Загрузка частотных словарей и перевод частот в веса.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use sha2::{Digest, Sha256};
+
+use crate::casefold::{self, Locale};
+use crate::hashing::{self, HashingReader};
+use crate::timings;
+use crate::{Error, FileKind, Result, Warning};
+
+/// Читает пары (слово, частота) в порядке файла. Формат определяется по расширению:
+/// .csv — строки `word,count`, иначе JSON-массив вида [["the", 199660765], ...].
+pub fn read_dict_entries(path: &Path) -> Result<Vec<(String, u64)>> {
+    let mut items = Vec::new();
+    stream_dict_entries(path, |word, count| items.push((word, count)))?;
+    Ok(items)
+}
+
+/// Сколько номеров пропущенных записей сохранять для предупреждения
+const MALFORMED_EXAMPLES: usize = 5;
+
+/// Некорректные записи, пропущенные при нестрогой загрузке (--dict-lenient)
+#[derive(Clone, Debug, Default)]
+pub struct Malformed {
+    pub count: usize,
+    /// Номера (с 1) первых пропущенных записей JSON или строк CSV
+    pub first: Vec<usize>,
+}
+
+impl Malformed {
+    /// Нестрогий режим пропускает запись, строгий возвращает ошибку как есть
+    fn skip(lenient: Option<&mut Self>, err: Error, entry: usize) -> Result<()> {
+        let Some(m) = lenient else {
+            return Err(err);
+        };
+        m.count += 1;
+        if m.first.len() < MALFORMED_EXAMPLES {
+            m.first.push(entry);
+        }
+        Ok(())
+    }
+}
+
+/// Частота: число или строка с целым числом ("12345") — так её часто пишут при экспорте в JSON
+fn json_count(v: &serde_json::Value) -> Option<u64> {
+    v.as_u64().or_else(|| v.as_str()?.trim().parse().ok())
+}
+
+fn open_dict(path: &Path) -> Result<File> {
+    File::open(path).map_err(|source| Error::Io {
+        kind: FileKind::Dict,
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Читатель, копящий в `elapsed` время, проведённое в чтении: при потоковом разборе чтение
+/// и разбор перемежаются, а --timings показывает их отдельно
+struct TimedReader<'a, R> {
+    inner: R,
+    elapsed: &'a mut Duration,
+}
+
+impl<R: Read> Read for TimedReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let started = Instant::now();
+        let n = self.inner.read(buf);
+        *self.elapsed += started.elapsed();
+        n
+    }
+}
+
+/// Запись JSON-словаря `["word", count]`; `entry` — её номер с 1 для ошибок
+fn json_entry(v: &serde_json::Value, entry: usize) -> Result<(String, u64)> {
+    match v.as_array() {
+        Some(a) if a.len() >= 2 => match (a[0].as_str(), json_count(&a[1])) {
+            (Some(word), Some(count)) => Ok((word.to_string(), count)),
+            (None, _) => Err(Error::DictWordNotString { entry }),
+            (_, None) => Err(Error::DictCountNotNumber { entry }),
+        },
+        Some(_) => Err(Error::DictEntryTooShort { entry }),
+        None => Err(Error::DictEntryNotArray { entry }),
+    }
+}
+
+/// Передаёт записи словаря в `f` по одной, в порядке файла и без загрузки файла целиком:
+/// CSV читается построчно, JSON-массив — по элементам (в памяти только текущая запись).
+/// Разбор строгий, как у `read_dict_entries`.
+pub fn stream_dict_entries(path: &Path, mut f: impl FnMut(String, u64)) -> Result<()> {
+    stream_file(path, None, &mut Duration::default(), None, |word, count| {
+        f(word, count);
+        Ok(ControlFlow::Continue(()))
+    })
+}
+
+/// Проверяет начало словаря, не читая его целиком: файл открывается, формат (по расширению)
+/// совпадает с содержимым и первая запись разбирается (--check-config)
+pub fn check_dict_header(path: &Path) -> Result<()> {
+    let mut seen = false;
+    stream_file(path, None, &mut Duration::default(), None, |_, _| {
+        seen = true;
+        Ok(ControlFlow::Break(()))
+    })?;
+    if !seen {
+        return Err(Error::DictEmpty);
+    }
+    Ok(())
+}
+
+/// Открывает словарь и разбирает его потоком (`stream_entries`). С `hasher` файл хэшируется
+/// целиком: если `f` прервал разбор, остаток дочитывается без разбора. Время чтения
+/// прибавляется к `read_time`.
+fn stream_file(
+    path: &Path,
+    hasher: Option<&mut Sha256>,
+    read_time: &mut Duration,
+    lenient: Option<&mut Malformed>,
+    f: impl FnMut(String, u64) -> Result<ControlFlow<()>>,
+) -> Result<()> {
+    let drain = hasher.is_some();
+    let mut reader = TimedReader { inner: HashingReader::new(open_dict(path)?, hasher), elapsed: read_time };
+    stream_entries(path, BufReader::new(&mut reader), lenient, f)?;
+    if drain {
+        io::copy(&mut reader, &mut io::sink()).map_err(|source| Error::Io {
+            kind: FileKind::Dict,
+            path: path.to_path_buf(),
+            source,
+        })?;
+    }
+    Ok(())
+}
+
+/// Разбор словаря по одной записи для `f`, в памяти только текущая запись. `f` может
+/// прервать чтение, тогда остаток файла не проверяется. `lenient` — куда учитывать
+/// пропущенные некорректные записи; None — строгий разбор. Синтаксические ошибки JSON
+/// и незакрытый массив не пропускаются и в нестрогом режиме.
+fn stream_entries<R: BufRead>(
+    path: &Path,
+    reader: R,
+    mut lenient: Option<&mut Malformed>,
+    mut f: impl FnMut(String, u64) -> Result<ControlFlow<()>>,
+) -> Result<()> {
+    let io_err = |source| Error::Io {
+        kind: FileKind::Dict,
+        path: path.to_path_buf(),
+        source,
+    };
+    let is_csv = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+    if is_csv {
+        for (i, line) in reader.lines().enumerate() {
+            let line = line.map_err(io_err)?;
+            let line = line.trim_end_matches('\r');
+            if line.is_empty() {
+                continue;
+            }
+            match parse_csv_line(line, i + 1) {
+                Ok((word, count)) => {
+                    if f(word, count)?.is_break() {
+                        return Ok(());
+                    }
+                }
+                Err(e) => Malformed::skip(lenient.as_deref_mut(), e, i + 1)?,
+            }
+        }
+        return Ok(());
+    }
+    let mut bytes = reader.bytes();
+    let next_significant = |bytes: &mut io::Bytes<R>| -> Result<Option<u8>> {
+        loop {
+            match bytes.next().transpose().map_err(io_err)? {
+                Some(b) if b.is_ascii_whitespace() => continue,
+                other => return Ok(other),
+            }
+        }
+    };
+    if next_significant(&mut bytes)? != Some(b'[') {
+        return Err(Error::DictNotArray);
+    }
+    let mut element = Vec::new();
+    let mut entry = 0;
+    loop {
+        // Байты одного элемента: до запятой или ] на верхнем уровне, вне строк
+        element.clear();
+        let (mut depth, mut in_string, mut escaped) = (0usize, false, false);
+        let end = loop {
+            let Some(b) = bytes.next().transpose().map_err(io_err)? else {
+                break None;
+            };
+            if in_string {
+                match b {
+                    _ if escaped => escaped = false,
+                    b'\\' => escaped = true,
+                    b'"' => in_string = false,
+                    _ => {}
+                }
+            } else {
+                match b {
+                    b'"' => in_string = true,
+                    b'[' | b'{' => depth += 1,
+                    b']' | b'}' if depth > 0 => depth -= 1,
+                    b',' | b']' if depth == 0 => break Some(b),
+                    _ => {}
+                }
+            }
+            element.push(b);
+        };
+        if entry == 0 && end == Some(b']') && element.iter().all(u8::is_ascii_whitespace) {
+            return Ok(());
+        }
+        // Обрыв файла посреди элемента — ошибка разбора этого элемента
+        let value: serde_json::Value = serde_json::from_slice(&element).map_err(Error::DictJson)?;
+        entry += 1;
+        match json_entry(&value, entry) {
+            Ok((word, count)) => {
+                if f(word, count)?.is_break() {
+                    return Ok(());
+                }
+            }
+            Err(e) => Malformed::skip(lenient.as_deref_mut(), e, entry)?,
+        }
+        match end {
+            Some(b',') => {}
+            Some(_) => break,
+            None => return Err(Error::DictUnterminated),
+        }
+    }
+    match next_significant(&mut bytes)? {
+        None => Ok(()),
+        Some(_) => Err(Error::DictTrailingData),
+    }
+}
+
+/// Одна непустая строка CSV-словаря; `line_no` — её номер с 1 для ошибок
+fn parse_csv_line(line: &str, line_no: usize) -> Result<(String, u64)> {
+    let (word, rest) = if let Some(quoted) = line.strip_prefix('"') {
+        let mut word = String::new();
+        let mut chars = quoted.char_indices();
+        let mut end = None;
+        while let Some((j, c)) = chars.next() {
+            if c == '"' {
+                if quoted[j + 1..].starts_with('"') {
+                    word.push('"');
+                    chars.next();
+                } else {
+                    end = Some(j + 1);
+                    break;
+                }
+            } else {
+                word.push(c);
+            }
+        }
+        let end = end.ok_or(Error::CsvUnclosedQuote { line: line_no })?;
+        (word, &quoted[end..])
+    } else {
+        match line.find(',') {
+            Some(j) => (line[..j].to_string(), &line[j..]),
+            None => (line.to_string(), ""),
+        }
+    };
+    let count = rest
+        .strip_prefix(',')
+        .and_then(|c| c.trim().parse::<u64>().ok())
+        .ok_or(Error::CsvBadLine { line: line_no })?;
+    Ok((word, count))
+}
+
+/// Функция, переводящая частоту слова в вес
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Weighting {
+    /// count / max_count
+    Linear,
+    /// ln(1 + count) / ln(1 + max_count): сглаживает разрыв между служебными и обычными словами
+    Log,
+    /// (N - rank + 1) / N, где rank 1 — самое частое слово: зависит только от порядка
+    Rank,
+}
+
+/// Частота и ранг слова в словаре
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WordStats {
+    pub count: u64,
+    /// 1 — самое частое слово; равные частоты упорядочены по алфавиту
+    pub rank: usize,
+}
+
+/// Загруженный словарь: нормированные веса в [0, 1] и, по запросу, исходные частоты с рангами
+#[derive(Clone, Debug)]
+pub struct FrequencyDict {
+    weights: Weights,
+    stats: Option<HashMap<String, WordStats>>,
+}
+
+/// Хранение весов: f64 или, после `quantized`, 16-битная фиксированная точка (вес × 65535)
+/// с ключами без запаса ёмкости
+#[derive(Clone, Debug)]
+enum Weights {
+    Full(HashMap<String, f64>),
+    Fixed16(HashMap<Box<str>, u16>),
+}
+
+/// Наибольшая ошибка веса (а значит, и оценки — среднего весов) после `quantized`:
+/// половина шага 1/65535
+pub const QUANTIZE_MAX_ERROR: f64 = 0.5 / u16::MAX as f64;
+
+impl FrequencyDict {
+    pub fn weight(&self, word: &str) -> Option<f64> {
+        match &self.weights {
+            Weights::Full(w) => w.get(word).copied(),
+            Weights::Fixed16(w) => w.get(word).map(|&q| f64::from(q) / f64::from(u16::MAX)),
+        }
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        match &self.weights {
+            Weights::Full(w) => w.contains_key(word),
+            Weights::Fixed16(w) => w.contains_key(word),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.weights {
+            Weights::Full(w) => w.len(),
+            Weights::Fixed16(w) => w.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Все слова с весами, в произвольном порядке
+    fn iter(&self) -> Box<dyn Iterator<Item = (&str, f64)> + '_> {
+        match &self.weights {
+            Weights::Full(w) => Box::new(w.iter().map(|(k, &v)| (k.as_str(), v))),
+            Weights::Fixed16(w) => Box::new(w.iter().map(|(k, &q)| (&**k, f64::from(q) / f64::from(u16::MAX)))),
+        }
+    }
+
+    /// Словарь с весами в 16-битной фиксированной точке (--quantize-weights 16): около вдвое
+    /// меньше памяти на запись, ошибка каждого веса — не больше `QUANTIZE_MAX_ERROR`.
+    /// 0 и 1 представляются точно.
+    pub fn quantized(self) -> Self {
+        let weights = match self.weights {
+            Weights::Full(w) => {
+                let mut fixed = HashMap::with_capacity(w.len());
+                for (k, v) in w {
+                    fixed.insert(k.into_boxed_str(), (v * f64::from(u16::MAX)).round() as u16);
+                }
+                Weights::Fixed16(fixed)
+            }
+            fixed => fixed,
+        };
+        FrequencyDict { weights, ..self }
+    }
+
+    pub fn is_quantized(&self) -> bool {
+        matches!(self.weights, Weights::Fixed16(_))
+    }
+
+    /// До `limit` слов словаря на расстоянии правки (Левенштейна) не больше `max_distance`
+    /// от `word`: ближайшие первыми, при равном расстоянии — более частые, затем по алфавиту.
+    /// Перебирает весь словарь, поэтому рассчитано на отдельные запросы, а не на оценку текста.
+    pub fn nearest(&self, word: &str, max_distance: usize, limit: usize) -> Vec<(&str, usize)> {
+        let target: Vec<char> = word.chars().collect();
+        let mut found: Vec<(&str, usize, f64)> = self
+            .iter()
+            .filter_map(|(w, weight)| edit_distance(&target, w, max_distance).map(|d| (w, d, weight)))
+            .filter(|&(w, _, _)| w != word)
+            .collect();
+        found.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| b.2.total_cmp(&a.2)).then_with(|| a.0.cmp(b.0)));
+        found.into_iter().take(limit).map(|(w, d, _)| (w, d)).collect()
+    }
+
+    /// Частота и ранг; None, если слова нет или словарь загружен без retain_stats
+    pub fn stats(&self, word: &str) -> Option<WordStats> {
+        self.stats.as_ref()?.get(word).copied()
+    }
+}
+
+/// Расстояние Левенштейна между `a` и `b` в символах, если оно не больше `max`
+fn edit_distance(a: &[char], b: &str, max: usize) -> Option<usize> {
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, &ca) in a.iter().enumerate() {
+        cur[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        // Строка целиком больше порога — дальше расстояние только растёт
+        if cur.iter().all(|&d| d > max) {
+            return None;
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    Some(prev[b.len()]).filter(|&d| d <= max)
+}
+
+/// Ранги записей (по индексу): по убыванию частоты, при равенстве — по алфавиту
+fn compute_ranks(items: &[(String, u64)]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by(|&a, &b| items[b].1.cmp(&items[a].1).then_with(|| items[a].0.cmp(&items[b].0)));
+    let mut ranks = vec![0; items.len()];
+    for (pos, i) in order.into_iter().enumerate() {
+        ranks[i] = pos + 1;
+    }
+    ranks
+}
+
+/// Вес записи в [0, 1], максимум = 1.0. Второй элемент — false, если результат пришлось
+/// исправлять (не число или вне диапазона после округления f64).
+fn normalize_weight(weighting: Weighting, count: u64, max_count: u64, rank: usize, n: usize) -> (f64, bool) {
+    let weight = match weighting {
+        Weighting::Linear => count as f64 / max_count as f64,
+        Weighting::Log => (count as f64).ln_1p() / (max_count as f64).ln_1p(),
+        Weighting::Rank => (n - rank + 1) as f64 / n as f64,
+    };
+    if weight.is_nan() {
+        (0.0, false)
+    } else if !(0.0..=1.0).contains(&weight) {
+        (weight.clamp(0.0, 1.0), false)
+    } else {
+        (weight, true)
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Завершиться с ошибкой
+    Error,
+    /// Оставить первую запись
+    First,
+    /// Оставить последнюю запись
+    Last,
+    /// Сложить частоты
+    Sum,
+    /// Взять наибольшую частоту
+    Max,
+}
+
+/// Параметры загрузки словаря
+#[derive(Clone, Copy, Debug)]
+pub struct DictOptions {
+    pub top_k: Option<usize>,
+    /// Оставить самые частые записи, пока их частоты не наберут эту долю суммы всех частот
+    /// (--dict-coverage-target); вместо `top_k`
+    pub coverage_target: Option<f64>,
+    pub duplicates: DuplicatePolicy,
+    pub weighting: Weighting,
+    /// Сохранить исходные частоты и ранги (для `dict weight` и подобного)
+    pub retain_stats: bool,
+    /// Пропускать некорректные записи вместо ошибки (--dict-lenient)
+    pub lenient: bool,
+    /// Правила свёртки регистра ключей (--locale): ключи сворачиваются так же, как слова текста,
+    /// а совпавшие после свёртки слова сливаются с суммой частот; None — ключи как есть
+    pub locale: Option<Locale>,
+}
+
+/// Сведения о загрузке словаря для --verbose и --timings
+#[derive(Clone, Debug, Default)]
+pub struct DictLoadInfo {
+    /// Записей после усечения и до слияния повторов
+    pub entries: usize,
+    /// Доля суммы частот, набранная оставленными записями (с --dict-coverage-target; у смеси —
+    /// наименьшая по компонентам)
+    pub coverage: Option<f64>,
+    /// Сколько записей оказались повторами уже встреченных слов
+    pub duplicates: usize,
+    /// Сколько различных слов совпали с другими после свёртки регистра (daß и dass); их частоты
+    /// складываются при любом --dict-duplicates, и повторами они не считаются
+    pub folded: usize,
+    /// Сколько весов вне [0, 1] пришлось привести к границам
+    pub clamped: usize,
+    /// Пропущенные некорректные записи (только с lenient)
+    pub malformed: Malformed,
+    /// SHA-256 прочитанных файлов словаря в порядке чтения (у смеси — по компоненту)
+    pub sha256: Vec<String>,
+    pub timings: timings::DictTimings,
+}
+
+impl DictLoadInfo {
+    /// Предупреждения загрузки: сведённые повторы, пропущенные записи, приведённые веса
+    pub fn warnings(&self) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        if self.duplicates > 0 {
+            warnings.push(Warning::DictDuplicates { count: self.duplicates });
+        }
+        if self.malformed.count > 0 {
+            warnings.push(Warning::DictMalformedSkipped { count: self.malformed.count, first: self.malformed.first.clone() });
+        }
+        if self.clamped > 0 {
+            warnings.push(Warning::WeightsClamped { count: self.clamped });
+        }
+        warnings
+    }
+}
+
+/// Сборка словаря из записей по одной, в порядке файла: усечение (`top_k` или доля частот)
+/// и слияние повторов идут по ходу разбора, так что в памяти только различные оставленные
+/// слова, а не все записи файла
+struct DictBuilder {
+    opts: DictOptions,
+    /// С --dict-coverage-target: сколько частот набрать и сумма частот всех записей
+    coverage: Option<(f64, u128)>,
+    /// Номер каждого слова в `items`; порядок в `items` — порядок первых вхождений
+    index: HashMap<String, usize>,
+    items: Vec<(String, u64)>,
+    /// Принятые записи до слияния повторов и сумма их частот
+    entries: usize,
+    mass: u128,
+    duplicates: usize,
+}
+
+impl DictBuilder {
+    /// `total` — сумма частот всех записей словаря; нужна только с `coverage_target`
+    fn new(opts: DictOptions, total: Option<u128>) -> Self {
+        DictBuilder {
+            opts,
+            coverage: opts.coverage_target.zip(total).map(|(target, total)| (target * total as f64, total)),
+            index: HashMap::new(),
+            items: Vec::new(),
+            entries: 0,
+            mass: 0,
+            duplicates: 0,
+        }
+    }
+
+    /// Добавляет запись; повтор уже встреченного слова сводится по --dict-duplicates.
+    /// Break — лимит уже набран: эта и все последующие записи не нужны. С долей частот
+    /// записи должны идти по убыванию частоты; хотя бы одна запись принимается.
+    fn push(&mut self, word: String, count: u64) -> Result<ControlFlow<()>> {
+        let full = self.opts.top_k.is_some_and(|k| self.entries >= k)
+            || self.coverage.is_some_and(|(need, _)| self.entries > 0 && self.mass as f64 >= need);
+        if full {
+            return Ok(ControlFlow::Break(()));
+        }
+        self.entries += 1;
+        self.mass += u128::from(count);
+        let Some(&j) = self.index.get(&word) else {
+            self.index.insert(word.clone(), self.items.len());
+            self.items.push((word, count));
+            return Ok(ControlFlow::Continue(()));
+        };
+        self.duplicates += 1;
+        let existing = &mut self.items[j].1;
+        match self.opts.duplicates {
+            DuplicatePolicy::Error => return Err(Error::DuplicateWord { word, entry: self.entries }),
+            DuplicatePolicy::First => {}
+            DuplicatePolicy::Last => *existing = count,
+            DuplicatePolicy::Sum => *existing = existing.saturating_add(count),
+            DuplicatePolicy::Max => *existing = (*existing).max(count),
+        }
+        Ok(ControlFlow::Continue(()))
+    }
+
+    /// Свёртка регистра и нормировка принятых записей. В сведениях заполнено только время
+    /// нормировки.
+    fn finish(self) -> Result<(FrequencyDict, DictLoadInfo)> {
+        let started = Instant::now();
+        let DictBuilder { opts, coverage, index, items, entries, mass, duplicates } = self;
+        drop(index);
+        if items.is_empty() {
+            return Err(Error::DictEmpty);
+        }
+        let coverage = coverage.map(|(_, total)| if total == 0 { 0.0 } else { mass as f64 / total as f64 });
+
+        // Максимум для нормировки считаем уже после слияния повторов
+        let (items, folded) = match opts.locale {
+            Some(locale) => fold_keys(items, locale),
+            None => (items, 0),
+        };
+
+        let max_count = items.iter().map(|(_, c)| *c).max().unwrap_or(0);
+        if max_count == 0 {
+            return Err(Error::DictZeroCounts);
+        }
+        let ranks = if opts.weighting == Weighting::Rank || opts.retain_stats {
+            compute_ranks(&items)
+        } else {
+            Vec::new()
+        };
+        let n = items.len();
+
+        let mut weights = HashMap::with_capacity(n);
+        let mut stats = opts.retain_stats.then(|| HashMap::with_capacity(n));
+        let mut clamped = 0usize;
+        for (i, (w, c)) in items.into_iter().enumerate() {
+            let rank = ranks.get(i).copied().unwrap_or(0);
+            let (weight, ok) = normalize_weight(opts.weighting, c, max_count, rank, n);
+            if !ok {
+                clamped += 1;
+            }
+            if let Some(stats) = &mut stats {
+                stats.insert(w.clone(), WordStats { count: c, rank });
+            }
+            weights.insert(w, weight);
+        }
+        let info = DictLoadInfo {
+            entries,
+            coverage,
+            duplicates,
+            folded,
+            clamped,
+            malformed: Malformed::default(),
+            sha256: Vec::new(),
+            timings: timings::DictTimings {
+                normalize: started.elapsed(),
+                ..Default::default()
+            },
+        };
+        Ok((FrequencyDict { weights: Weights::Full(weights), stats }, info))
+    }
+}
+
+/// Загружает словарь одним потоковым проходом: записи по мере разбора уходят в `DictBuilder`,
+/// так что ни текст файла, ни список всех его записей в памяти не держатся, а с
+/// --top-dict-entries разбор останавливается на K-й записи (остаток файла только хэшируется;
+/// некорректные записи за ним не проверяются и не учитываются).
+///
+/// Долю частот (--dict-coverage-target) считают от суммы частот всего файла, поэтому проходов
+/// два, оба потоковые: первый только суммирует частоты, хэширует файл и проверяет, что записи
+/// идут по убыванию частоты, второй принимает записи, пока доля не набрана. Лишь словарь не по
+/// убыванию частот приходится собрать в памяти целиком и упорядочить: самые частые записи
+/// в нём могут оказаться в самом конце.
+pub fn load_frequency_dict(path: &Path, opts: DictOptions) -> Result<(FrequencyDict, DictLoadInfo)> {
+    let started = Instant::now();
+    let mut hasher = Sha256::new();
+    let mut read = Duration::ZERO;
+    let mut malformed = Malformed::default();
+    let built = match opts.coverage_target {
+        None => {
+            let mut builder = DictBuilder::new(opts, None);
+            let lenient = opts.lenient.then_some(&mut malformed);
+            stream_file(path, Some(&mut hasher), &mut read, lenient, |word, count| builder.push(word, count))?;
+            builder.finish()
+        }
+        Some(_) => {
+            // Первый проход: сумма частот, порядок записей и SHA-256; записи не сохраняются
+            let (mut total, mut sorted, mut prev) = (0u128, true, u64::MAX);
+            let lenient = opts.lenient.then_some(&mut malformed);
+            stream_file(path, Some(&mut hasher), &mut read, lenient, |_, count| {
+                total += u128::from(count);
+                sorted &= count <= prev;
+                prev = count;
+                Ok(ControlFlow::Continue(()))
+            })?;
+            // Пропущенные записи уже учтены первым проходом
+            let mut skipped = Malformed::default();
+            let lenient = opts.lenient.then_some(&mut skipped);
+            if sorted {
+                let mut builder = DictBuilder::new(opts, Some(total));
+                stream_file(path, None, &mut read, lenient, |word, count| builder.push(word, count))?;
+                builder.finish()
+            } else {
+                let mut items = Vec::new();
+                stream_file(path, None, &mut read, lenient, |word, count| {
+                    items.push((word, count));
+                    Ok(ControlFlow::Continue(()))
+                })?;
+                build_frequency_dict(items, opts)
+            }
+        }
+    };
+    let (dict, mut info) = built?;
+    info.malformed = malformed;
+    info.sha256.push(hashing::hex(hasher));
+    info.timings.read = read;
+    info.timings.parse = started.elapsed().saturating_sub(read + info.timings.normalize);
+    Ok((dict, info))
+}
+
+/// Словарь из уже прочитанных пар (слово, частота): усечение, слияние повторов и нормировка,
+/// как при загрузке из файла. В сведениях заполнено только время нормировки.
+pub fn build_frequency_dict(mut items: Vec<(String, u64)>, opts: DictOptions) -> Result<(FrequencyDict, DictLoadInfo)> {
+    let total = opts.coverage_target.map(|_| order_by_count(&mut items));
+    let mut builder = DictBuilder::new(opts, total);
+    for (word, count) in items {
+        if builder.push(word, count)?.is_break() {
+            break;
+        }
+    }
+    builder.finish()
+}
+
+/// Сворачивает регистр ключей (`casefold::fold`); записи, совпавшие после свёртки, сливаются
+/// в первую с суммой частот. Возвращает записи и число слившихся.
+fn fold_keys(items: Vec<(String, u64)>, locale: Locale) -> (Vec<(String, u64)>, usize) {
+    // Обычно все ключи уже в нижнем регистре: тогда второй индекс слов не строится
+    if items.iter().all(|(w, _)| matches!(casefold::fold(w, locale), Cow::Borrowed(_))) {
+        return (items, 0);
+    }
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut out: Vec<(String, u64)> = Vec::with_capacity(items.len());
+    let mut folded = 0;
+    for (word, count) in items {
+        let word = match casefold::fold(&word, locale) {
+            Cow::Owned(f) => f,
+            Cow::Borrowed(_) => word,
+        };
+        match index.get(&word) {
+            Some(&j) => {
+                out[j].1 = out[j].1.saturating_add(count);
+                folded += 1;
+            }
+            None => {
+                index.insert(word.clone(), out.len());
+                out.push((word, count));
+            }
+        }
+    }
+    (out, folded)
+}
+
+/// Упорядочивает записи по убыванию частоты (равные — в порядке файла) для отбора по доле
+/// частот; уже упорядоченный словарь (обычный случай) не пересортировывается. Возвращает
+/// сумму частот всех записей.
+fn order_by_count(items: &mut [(String, u64)]) -> u128 {
+    if !items.is_sorted_by(|a, b| a.1 >= b.1) {
+        items.sort_by_key(|(_, c)| std::cmp::Reverse(*c));
+    }
+    items.iter().map(|(_, c)| u128::from(*c)).sum()
+}
+
+/// Частоты и ранги всех слов словаря, прочитанного потоком (`stream_dict_entries`);
+/// частоты повторяющихся слов складываются, как при --dict-duplicates sum
+pub fn read_word_stats(path: &Path) -> Result<HashMap<String, WordStats>> {
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut items: Vec<(String, u64)> = Vec::new();
+    stream_dict_entries(path, |word, count| match index.get(&word) {
+        Some(&i) => items[i].1 = items[i].1.saturating_add(count),
+        None => {
+            index.insert(word.clone(), items.len());
+            items.push((word, count));
+        }
+    })?;
+    drop(index);
+    let ranks = compute_ranks(&items);
+    Ok(items
+        .into_iter()
+        .zip(ranks)
+        .map(|((word, count), rank)| (word, WordStats { count, rank }))
+        .collect())
+}
+
+/// Компонент смеси словарей (--dict-blend): словарь и его доля в итоговом весе
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlendComponent {
+    pub path: PathBuf,
+    pub share: f64,
+}
+
+/// Смешивает независимо нормированные словари: вес слова — сумма `доля × вес` по компонентам,
+/// где слова нет — компонент даёт 0. Частоты и ранги в смеси не определены и не сохраняются.
+pub fn blend(components: &[(FrequencyDict, f64)]) -> FrequencyDict {
+    let mut weights: HashMap<String, f64> = HashMap::new();
+    for (dict, share) in components {
+        for (word, w) in dict.iter() {
+            *weights.entry(word.to_string()).or_insert(0.0) += share * w;
+        }
+    }
+    // Сумма долей равна 1 лишь с точностью до округления: не даём весу выйти за 1.0
+    for w in weights.values_mut() {
+        *w = w.min(1.0);
+    }
+    FrequencyDict { weights: Weights::Full(weights), stats: None }
+}
+
+/// Загружает каждый компонент с общими параметрами и смешивает их; сведения о загрузке
+/// суммируются по компонентам.
+pub fn load_blended_dict(components: &[BlendComponent], opts: DictOptions) -> Result<(FrequencyDict, DictLoadInfo)> {
+    let mut loaded = Vec::with_capacity(components.len());
+    let mut info = DictLoadInfo::default();
+    for c in components {
+        let (dict, part) = load_frequency_dict(&c.path, opts)?;
+        info.entries += part.entries;
+        info.coverage = match (info.coverage, part.coverage) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        info.duplicates += part.duplicates;
+        info.folded += part.folded;
+        info.clamped += part.clamped;
+        info.malformed.count += part.malformed.count;
+        let room = MALFORMED_EXAMPLES - info.malformed.first.len();
+        info.malformed.first.extend(part.malformed.first.into_iter().take(room));
+        info.sha256.extend(part.sha256);
+        info.timings.read += part.timings.read;
+        info.timings.parse += part.timings.parse;
+        info.timings.normalize += part.timings.normalize;
+        loaded.push((dict, c.share));
+    }
+    let started = Instant::now();
+    let dict = blend(&loaded);
+    info.timings.normalize += started.elapsed();
+    Ok((dict, info))
+}
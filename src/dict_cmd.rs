@@ -0,0 +1,478 @@
+//! Подкоманды `dict ...`: обслуживание частотных словарей.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Write as _};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand, ValueEnum};
+
+use readability::dict::{
+    self, read_dict_entries, BlendComponent, DictOptions, DuplicatePolicy, Weighting, WordStats,
+};
+
+use crate::i18n::Msg;
+use crate::{csv_field, parse_unit_weight};
+
+#[derive(Subcommand, Debug)]
+pub enum DictCommand {
+    /// Урезать словарь и сохранить его с исходными частотами
+    Prune(PruneArgs),
+    /// Показать итоговый вес, ранг и частоту слов после загрузки словаря
+    Weight(WeightArgs),
+    /// Сравнить два словаря: слова только в одном из них, сдвиги рангов, корреляция рангов
+    Diff(DiffArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct PruneArgs {
+    /// Исходный словарь (JSON или .csv)
+    #[arg(long = "dict")]
+    dict_path: PathBuf,
+
+    /// Куда сохранить результат
+    #[arg(long = "out")]
+    out_path: PathBuf,
+
+    /// Оставить только K самых частых записей (после остальных фильтров)
+    #[arg(long = "top")]
+    top: Option<usize>,
+
+    /// Удалить записи с частотой меньше N
+    #[arg(long = "min-count")]
+    min_count: Option<u64>,
+
+    /// Файл со словами для удаления, по одному на строку
+    #[arg(long = "drop-words")]
+    drop_words: Option<PathBuf>,
+
+    /// Формат результата; по умолчанию определяется по расширению --out (.csv — csv, иначе json)
+    #[arg(long = "format", value_enum)]
+    format: Option<DictFormat>,
+
+    /// Разрешить перезапись исходного словаря
+    #[arg(long = "force")]
+    force: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct WeightArgs {
+    /// Словарь (JSON или .csv)
+    #[arg(long = "dict", required_unless_present = "dict_blend")]
+    dict_path: Option<PathBuf>,
+
+    /// Смесь словарей, как у --dict-blend оценки; для каждого слова печатается и вклад компонентов
+    #[arg(long = "dict-blend", value_parser = parse_blend, conflicts_with = "dict_path")]
+    dict_blend: Option<DictBlend>,
+
+    /// Использовать только первые K записей словаря
+    #[arg(long = "top-dict-entries")]
+    top_dict_entries: Option<usize>,
+
+    /// Что делать со словом, встретившимся в словаре несколько раз
+    #[arg(long = "dict-duplicates", value_enum, default_value_t = DuplicatePolicy::Sum)]
+    dict_duplicates: DuplicatePolicy,
+
+    /// Функция перевода частоты в вес
+    #[arg(long = "weight", value_enum, default_value_t = Weighting::Linear)]
+    weight: Weighting,
+
+    /// Слова для проверки (ищутся как есть); если не указаны — читаются из STDIN по одному на строку
+    words: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// Прежний словарь (JSON или .csv)
+    old_path: PathBuf,
+
+    /// Новый словарь (JSON или .csv)
+    new_path: PathBuf,
+
+    /// Сдвигом считается изменение ранга общего слова больше чем на N позиций
+    #[arg(long = "rank-delta", default_value_t = 1000)]
+    rank_delta: usize,
+
+    /// Сколько слов выводить в каждом списке (только в старом, только в новом, сдвинутые)
+    #[arg(long = "limit", default_value_t = 20)]
+    limit: usize,
+
+    #[arg(long = "format", value_enum, default_value_t = DiffFormat::Plain)]
+    format: DiffFormat,
+
+    /// Завершиться с кодом 1, если слов только в старом словаре больше N
+    #[arg(long = "max-removed")]
+    max_removed: Option<usize>,
+
+    /// Завершиться с кодом 1, если слов только в новом словаре больше N
+    #[arg(long = "max-added")]
+    max_added: Option<usize>,
+
+    /// Завершиться с кодом 1, если сдвинутых слов больше N
+    #[arg(long = "max-moved")]
+    max_moved: Option<usize>,
+
+    /// Завершиться с кодом 1, если корреляция рангов общих слов ниже R
+    #[arg(long = "min-correlation")]
+    min_correlation: Option<f64>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffFormat {
+    Plain,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DictFormat {
+    Json,
+    Csv,
+}
+
+/// Смесь словарей из `--dict-blend a.json:0.7,b.json:0.3`
+#[derive(Clone, Debug)]
+pub struct DictBlend(pub Vec<BlendComponent>);
+
+impl DictBlend {
+    /// Запись смеси для вывода, в том же виде, что и в командной строке
+    pub fn label(&self) -> String {
+        self.0
+            .iter()
+            .map(|c| format!("{}:{}", c.path.display(), c.share))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Разбор `путь:доля,путь:доля,...`; доля отделяется последним двоеточием, доли в сумме дают 1
+pub fn parse_blend(s: &str) -> Result<DictBlend, String> {
+    let components = s
+        .split(',')
+        .map(|part| {
+            let (path, share) = part
+                .rsplit_once(':')
+                .filter(|(p, _)| !p.is_empty())
+                .ok_or_else(|| Msg::BlendBadComponent(part).to_string())?;
+            Ok(BlendComponent {
+                path: PathBuf::from(path),
+                share: parse_unit_weight(share)?,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let sum: f64 = components.iter().map(|c| c.share).sum();
+    if (sum - 1.0).abs() > 1e-6 {
+        return Err(Msg::BlendSum(sum).to_string());
+    }
+    Ok(DictBlend(components))
+}
+
+pub fn run(cmd: &DictCommand) -> Result<()> {
+    match cmd {
+        DictCommand::Prune(args) => prune(args),
+        DictCommand::Weight(args) => weight(args),
+        DictCommand::Diff(args) => diff(args),
+    }
+}
+
+/// Детерминированный порядок: по убыванию частоты, при равенстве — по алфавиту
+pub fn sort_entries(items: &mut [(String, u64)]) {
+    items.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn prune(args: &PruneArgs) -> Result<()> {
+    if !args.force && same_file(&args.dict_path, &args.out_path) {
+        bail!("{}", Msg::PruneOverwrite(&args.dict_path));
+    }
+
+    let mut items = read_dict_entries(&args.dict_path)?;
+    let entries_in = items.len();
+
+    if let Some(path) = &args.drop_words {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| Msg::WordListOpen(path).to_string())?;
+        let drop: HashSet<&str> = text.lines().map(str::trim).filter(|w| !w.is_empty()).collect();
+        items.retain(|(w, _)| !drop.contains(w.as_str()));
+    }
+    if let Some(min) = args.min_count {
+        items.retain(|(_, c)| *c >= min);
+    }
+    sort_entries(&mut items);
+    if let Some(k) = args.top {
+        items.truncate(k);
+    }
+
+    let format = args.format.unwrap_or_else(|| {
+        let is_csv = args
+            .out_path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+        if is_csv { DictFormat::Csv } else { DictFormat::Json }
+    });
+    let content = render_entries(&items, format)?;
+    std::fs::write(&args.out_path, &content)
+        .with_context(|| Msg::DictWrite(&args.out_path).to_string())?;
+
+    let report = Msg::PruneReport {
+        entries_in,
+        entries_out: items.len(),
+        bytes: content.len(),
+    };
+    eprintln!("{report}");
+    Ok(())
+}
+
+/// Печатает `слово<TAB>вес<TAB>ранг<TAB>частота` или `слово<TAB>MISSING`;
+/// если хотя бы одного слова нет в словаре, завершается с кодом 1. С --dict-blend — см. `blend_weight`.
+fn weight(args: &WeightArgs) -> Result<()> {
+    let opts = DictOptions {
+        top_k: args.top_dict_entries,
+        coverage_target: None,
+        duplicates: args.dict_duplicates,
+        weighting: args.weight,
+        retain_stats: true,
+        lenient: false,
+        locale: None,
+    };
+    if let Some(blend) = &args.dict_blend {
+        return blend_weight(blend, opts, &read_words(&args.words)?);
+    }
+    // clap требует --dict, если не задан --dict-blend
+    let dict_path = args.dict_path.as_deref().expect("--dict или --dict-blend");
+    let (dict, _) = dict::load_frequency_dict(dict_path, opts)?;
+    let words = read_words(&args.words)?;
+
+    let mut out = io::stdout().lock();
+    let mut missing = false;
+    for w in &words {
+        match (dict.weight(w), dict.stats(w)) {
+            (Some(weight), Some(stats)) => {
+                writeln!(out, "{w}\t{weight}\t{}\t{}", stats.rank, stats.count)?
+            }
+            _ => {
+                missing = true;
+                writeln!(out, "{w}\tMISSING")?;
+            }
+        }
+    }
+    out.flush()?;
+    if missing {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Слова из аргументов или, если их нет, из STDIN по одному на строку
+pub fn read_words(words: &[String]) -> Result<Vec<String>> {
+    if !words.is_empty() {
+        return Ok(words.to_vec());
+    }
+    io::stdin()
+        .lock()
+        .lines()
+        .map(|l| l.map(|l| l.trim().to_string()))
+        .filter(|l| !matches!(l, Ok(w) if w.is_empty()))
+        .collect::<io::Result<_>>()
+        .with_context(|| Msg::WordsStdin.to_string())
+}
+
+/// `слово<TAB>вес<TAB>путь:доля=вес<TAB>...` — итоговый вес и вес слова в каждом компоненте
+/// (MISSING, если в компоненте слова нет); слова, которого нет ни в одном компоненте, — MISSING
+fn blend_weight(blend: &DictBlend, opts: DictOptions, words: &[String]) -> Result<()> {
+    let components = blend
+        .0
+        .iter()
+        .map(|c| Ok((dict::load_frequency_dict(&c.path, opts)?.0, c.share)))
+        .collect::<Result<Vec<_>>>()?;
+    let mixed = dict::blend(&components);
+
+    let mut out = io::stdout().lock();
+    let mut missing = false;
+    for w in words {
+        let Some(weight) = mixed.weight(w) else {
+            missing = true;
+            writeln!(out, "{w}\tMISSING")?;
+            continue;
+        };
+        write!(out, "{w}\t{weight}")?;
+        for (c, (dict, _)) in blend.0.iter().zip(&components) {
+            match dict.weight(w) {
+                Some(cw) => write!(out, "\t{}:{}={cw}", c.path.display(), c.share)?,
+                None => write!(out, "\t{}:{}=MISSING", c.path.display(), c.share)?,
+            }
+        }
+        writeln!(out)?;
+    }
+    out.flush()?;
+    if missing {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Различия двух словарей; списки отсортированы и ещё не урезаны до --limit
+struct DictDiff {
+    /// Слова только в старом словаре и их ранг там
+    removed: Vec<(String, usize)>,
+    /// Слова только в новом словаре и их ранг там
+    added: Vec<(String, usize)>,
+    /// Общие слова со сдвигом больше --rank-delta: (слово, старый ранг, новый ранг)
+    moved: Vec<(String, usize, usize)>,
+    shared: usize,
+    /// Коэффициент Спирмена по рангам общих слов; None, если общих слов меньше двух
+    correlation: Option<f64>,
+}
+
+fn compare(old: &HashMap<String, WordStats>, new: &HashMap<String, WordStats>, rank_delta: usize) -> DictDiff {
+    let only_in = |a: &HashMap<String, WordStats>, b: &HashMap<String, WordStats>| {
+        let mut words: Vec<(String, usize)> = a
+            .iter()
+            .filter(|(w, _)| !b.contains_key(*w))
+            .map(|(w, st)| (w.clone(), st.rank))
+            .collect();
+        words.sort_by_key(|&(_, rank)| rank);
+        words
+    };
+    // Общие слова в порядке старого словаря; по нему же и второй ряд рангов для корреляции
+    let mut shared: Vec<(&str, usize, usize)> = old
+        .iter()
+        .filter_map(|(w, st)| Some((w.as_str(), st.rank, new.get(w)?.rank)))
+        .collect();
+    shared.sort_by_key(|&(_, old_rank, _)| old_rank);
+    let mut moved: Vec<(String, usize, usize)> = shared
+        .iter()
+        .filter(|&&(_, o, n)| o.abs_diff(n) > rank_delta)
+        .map(|&(w, o, n)| (w.to_string(), o, n))
+        .collect();
+    moved.sort_by(|a, b| b.1.abs_diff(b.2).cmp(&a.1.abs_diff(a.2)).then_with(|| a.0.cmp(&b.0)));
+
+    let n = shared.len();
+    let correlation = (n >= 2).then(|| {
+        // Ранги заново среди общих слов: 1..n в каждом словаре, без совпадений
+        let mut by_new: Vec<usize> = (0..n).collect();
+        by_new.sort_by_key(|&i| shared[i].2);
+        let mut d2 = 0.0;
+        for (new_pos, &i) in by_new.iter().enumerate() {
+            let d = i as f64 - new_pos as f64;
+            d2 += d * d;
+        }
+        let n = n as f64;
+        1.0 - 6.0 * d2 / (n * (n * n - 1.0))
+    });
+    DictDiff {
+        removed: only_in(old, new),
+        added: only_in(new, old),
+        moved,
+        shared: n,
+        correlation,
+    }
+}
+
+/// Печатает различия двух словарей; оба читаются потоком, в памяти — только слова с рангами.
+/// Превышение любого из порогов --max-* / --min-correlation — код выхода 1.
+fn diff(args: &DiffArgs) -> Result<()> {
+    let old = dict::read_word_stats(&args.old_path)?;
+    let new = dict::read_word_stats(&args.new_path)?;
+    let d = compare(&old, &new, args.rank_delta);
+    let limit = args.limit;
+
+    let mut out = io::stdout().lock();
+    match args.format {
+        DiffFormat::Plain => {
+            writeln!(out, "removed\t{}", d.removed.len())?;
+            writeln!(out, "added\t{}", d.added.len())?;
+            writeln!(out, "moved\t{}", d.moved.len())?;
+            writeln!(out, "shared\t{}", d.shared)?;
+            match d.correlation {
+                Some(r) => writeln!(out, "rank_correlation\t{r:.6}")?,
+                None => writeln!(out, "rank_correlation\t-")?,
+            }
+            for (w, rank) in d.removed.iter().take(limit) {
+                writeln!(out, "-\t{w}\t{rank}")?;
+            }
+            for (w, rank) in d.added.iter().take(limit) {
+                writeln!(out, "+\t{w}\t{rank}")?;
+            }
+            for (w, old_rank, new_rank) in d.moved.iter().take(limit) {
+                writeln!(out, "~\t{w}\t{old_rank}\t{new_rank}")?;
+            }
+        }
+        DiffFormat::Json => {
+            let ranked = |words: &[(String, usize)]| -> serde_json::Value {
+                words
+                    .iter()
+                    .take(limit)
+                    .map(|(w, rank)| serde_json::json!({ "word": w, "rank": rank }))
+                    .collect()
+            };
+            let moved: serde_json::Value = d
+                .moved
+                .iter()
+                .take(limit)
+                .map(|(w, o, n)| serde_json::json!({ "word": w, "old_rank": o, "new_rank": n }))
+                .collect();
+            let json = serde_json::json!({
+                "removed": { "count": d.removed.len(), "words": ranked(&d.removed) },
+                "added": { "count": d.added.len(), "words": ranked(&d.added) },
+                "moved": { "count": d.moved.len(), "rank_delta": args.rank_delta, "words": moved },
+                "shared": d.shared,
+                "rank_correlation": d.correlation,
+            });
+            writeln!(out, "{json}")?;
+        }
+    }
+    out.flush()?;
+
+    let counts = [
+        ("--max-removed", args.max_removed, d.removed.len()),
+        ("--max-added", args.max_added, d.added.len()),
+        ("--max-moved", args.max_moved, d.moved.len()),
+    ];
+    let mut exceeded = false;
+    for (flag, max, actual) in counts {
+        if let Some(max) = max.filter(|&max| actual > max) {
+            let (actual, limit) = (actual.to_string(), max.to_string());
+            eprintln!("{}", Msg::DiffThreshold { flag, actual: &actual, limit: &limit });
+            exceeded = true;
+        }
+    }
+    if let Some(min) = args.min_correlation {
+        // Без общих слов корреляции нет — это тоже нарушение порога
+        if d.correlation.is_none_or(|r| r < min) {
+            let actual = d.correlation.map_or_else(|| "-".to_string(), |r| format!("{r:.6}"));
+            let limit = min.to_string();
+            eprintln!("{}", Msg::DiffThreshold { flag: "--min-correlation", actual: &actual, limit: &limit });
+            exceeded = true;
+        }
+    }
+    if exceeded {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Сериализует записи так, чтобы результат снова читался через --dict.
+pub fn render_entries(items: &[(String, u64)], format: DictFormat) -> Result<String> {
+    Ok(match format {
+        DictFormat::Json => {
+            let json: serde_json::Value = items.iter().map(|(w, c)| serde_json::json!([w, c])).collect();
+            let mut s = serde_json::to_string_pretty(&json)?;
+            s.push('\n');
+            s
+        }
+        DictFormat::Csv => {
+            let mut s = String::new();
+            for (w, c) in items {
+                writeln!(s, "{},{c}", csv_field(w))?;
+            }
+            s
+        }
+    })
+}
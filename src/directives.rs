@@ -0,0 +1,139 @@
+//! Директивы исключения в самом тексте: HTML-комментарии `<!-- readability:ignore-start -->` …
+//! `<!-- readability:ignore-end -->` и `<!-- readability:ignore-line -->`.
+
+use crate::offsets::OffsetMap;
+use crate::Warning;
+
+/// Результат удаления исключённых фрагментов
+#[derive(Clone, Debug)]
+pub struct StrippedDirectives {
+    /// Текст без исключённых фрагментов; каждый заменён пробелом, чтобы не склеивать соседние слова
+    pub text: String,
+    /// Исключённый текст по фрагментам, без самих директив
+    pub removed: Vec<String>,
+    /// Непарные ignore-start и ignore-end
+    pub warnings: Vec<Warning>,
+    /// Смещения в `text` → смещения во входном тексте
+    pub offsets: OffsetMap,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Directive {
+    Start,
+    End,
+    Line,
+}
+
+/// Директива в комментарии `<!-- ... -->`, начинающемся с позиции `i`: её вид и конец комментария.
+/// Пробелы внутри комментария не важны; прочие комментарии директивами не считаются.
+fn directive_at(text: &str, i: usize) -> Option<(Directive, usize)> {
+    let body_start = i + "<!--".len();
+    let body_len = text[body_start..].find("-->")?;
+    let end = body_start + body_len + "-->".len();
+    let directive = match text[body_start..body_start + body_len].trim() {
+        "readability:ignore-start" => Directive::Start,
+        "readability:ignore-end" => Directive::End,
+        "readability:ignore-line" => Directive::Line,
+        _ => return None,
+    };
+    Some((directive, end))
+}
+
+/// Конец строки, следующей за позицией `pos` (перевод строки остаётся в тексте)
+fn next_line_end(text: &str, pos: usize) -> usize {
+    match text[pos..].find('\n') {
+        Some(nl) => {
+            let next = pos + nl + 1;
+            text[next..].find('\n').map_or(text.len(), |n| next + n)
+        }
+        None => pos,
+    }
+}
+
+/// Удаляет фрагменты, отмеченные директивами. ignore-start … ignore-end исключают всё между
+/// ними; вложенные пары допускаются, фрагмент кончается на парной внешней ignore-end.
+/// ignore-line исключает следующую строку целиком, вместе с директивами в ней. Сами директивы
+/// тоже удаляются. Лишняя ignore-end лишь удаляется с предупреждением, незакрытая ignore-start
+/// исключает текст до конца с предупреждением; номера строк в предупреждениях — с единицы.
+pub fn strip_directives(text: &str) -> StrippedDirectives {
+    // (начало, конец) удаляемых фрагментов и директив внутри них
+    let mut spans: Vec<(usize, usize)> = Vec::new();
+    let mut markers: Vec<(usize, usize)> = Vec::new();
+    let mut warnings = Vec::new();
+    let mut depth = 0usize;
+    let mut region = (0usize, 0usize);
+    let mut line = 1usize;
+    let mut counted = 0usize;
+    let mut pos = 0usize;
+    while let Some(found) = text[pos..].find("<!--") {
+        let at = pos + found;
+        line += text[counted..at].matches('\n').count();
+        counted = at;
+        let Some((directive, end)) = directive_at(text, at) else {
+            pos = at + "<!--".len();
+            continue;
+        };
+        markers.push((at, end));
+        pos = end;
+        match directive {
+            Directive::Start => {
+                if depth == 0 {
+                    region = (at, line);
+                }
+                depth += 1;
+            }
+            Directive::End if depth == 0 => {
+                warnings.push(Warning::StrayIgnoreEnd { line });
+                spans.push((at, end));
+            }
+            Directive::End => {
+                depth -= 1;
+                if depth == 0 {
+                    spans.push((region.0, end));
+                }
+            }
+            Directive::Line if depth > 0 => {}
+            Directive::Line => {
+                pos = next_line_end(text, end);
+                spans.push((at, pos));
+            }
+        }
+    }
+    if depth > 0 {
+        warnings.push(Warning::UnclosedIgnoreStart { line: region.1 });
+        spans.push((region.0, text.len()));
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut removed = Vec::with_capacity(spans.len());
+    let mut offsets = OffsetMap::default();
+    let (mut processed, mut original) = (0usize, 0usize);
+    let mut pos = 0;
+    for (s, e) in spans {
+        let before = text[pos..s].chars().count();
+        out.push_str(&text[pos..s]);
+        out.push(' ');
+        let mut content = String::new();
+        let mut from = s;
+        for &(ms, me) in markers.iter().filter(|&&(ms, _)| ms >= s && ms < e) {
+            content.push_str(&text[from..ms]);
+            content.push(' ');
+            from = me;
+        }
+        content.push_str(&text[from..e]);
+        removed.push(content);
+        // Пробел на месте фрагмента соответствует его началу, следующий символ — концу
+        processed += before + 1;
+        original += before + text[s..e].chars().count();
+        offsets.push(processed, original);
+        pos = e;
+    }
+    out.push_str(&text[pos..]);
+
+    StrippedDirectives {
+        text: out,
+        removed,
+        warnings,
+        offsets,
+    }
+}
@@ -0,0 +1,282 @@
+//! Ошибки и предупреждения библиотеки. Тексты здесь не хранятся: `Display` даёт лишь
+//! нейтральное английское описание, сообщения для пользователя формирует бинарник.
+
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::aggregate::AggregateBy;
+use crate::metrics::{Metric, MetricGap};
+use crate::pipeline::Stage;
+use crate::Lang;
+
+/// Какой файл не удалось прочитать
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileKind {
+    Dict,
+    Text,
+    Contractions,
+    LangData,
+    Synonyms,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// Файл не открылся или не прочитался
+    Io {
+        kind: FileKind,
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// Не удалось прочитать текст из STDIN
+    Stdin(io::Error),
+    DictJson(serde_json::Error),
+    /// Верхний уровень JSON-словаря — не массив
+    DictNotArray,
+    /// При потоковом чтении: файл кончился до закрывающей ] или после неё есть что-то ещё
+    DictUnterminated,
+    DictTrailingData,
+    /// Запись словаря (номер с 1) — не массив
+    DictEntryNotArray { entry: usize },
+    DictEntryTooShort { entry: usize },
+    DictWordNotString { entry: usize },
+    DictCountNotNumber { entry: usize },
+    CsvUnclosedQuote { line: usize },
+    CsvBadLine { line: usize },
+    /// Повтор слова при --dict-duplicates error
+    DuplicateWord { word: String, entry: usize },
+    DictEmpty,
+    DictZeroCounts,
+    ContractionsBadLine { path: PathBuf, line: usize },
+    ContractionsEmptyExpansion { path: PathBuf, line: usize },
+    /// Строка файла --synonyms без табуляции или с пустым списком синонимов
+    SynonymsBadLine { path: PathBuf, line: usize },
+    /// Вход больше допустимого (--max-input-bytes, --max-tokens); обработка прервана
+    InputTooLarge(InputLimit),
+    /// Обработка текста не уложилась в --timeout
+    Timeout(Duration),
+    /// Стадии --pipeline: неизвестное имя, повтор, текстовая стадия после стадии слов
+    PipelineUnknownStage(String),
+    PipelineDuplicateStage(Stage),
+    PipelineStageOrder { text: Stage, token: Stage },
+    /// Файлы --lang-data: строка не разбирается, неизвестный ключ или раздел, значение
+    /// не того вида; в syllables.toml нет vowels
+    LangDataBadLine { path: PathBuf, line: usize },
+    LangDataUnknownKey { path: PathBuf, line: usize, key: String },
+    LangDataBadValue { path: PathBuf, line: usize, key: String },
+    LangDataMissingVowels { path: PathBuf },
+}
+
+/// Превышенное ограничение размера входа
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputLimit {
+    Bytes(u64),
+    Tokens(usize),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { kind, path, .. } => write!(f, "cannot read {kind:?} file {}", path.display()),
+            Error::Stdin(_) => write!(f, "cannot read STDIN"),
+            Error::DictJson(_) => write!(f, "invalid dictionary JSON"),
+            Error::DictNotArray => write!(f, "dictionary is not a JSON array"),
+            Error::DictUnterminated => write!(f, "dictionary JSON array is not closed"),
+            Error::DictTrailingData => write!(f, "trailing data after dictionary JSON array"),
+            Error::DictEntryNotArray { entry } => write!(f, "dictionary entry {entry} is not an array"),
+            Error::DictEntryTooShort { entry } => write!(f, "dictionary entry {entry} is too short"),
+            Error::DictWordNotString { entry } => write!(f, "dictionary entry {entry}: word is not a string"),
+            Error::DictCountNotNumber { entry } => write!(f, "dictionary entry {entry}: count is not a number"),
+            Error::CsvUnclosedQuote { line } => write!(f, "CSV dictionary line {line}: unclosed quote"),
+            Error::CsvBadLine { line } => write!(f, "CSV dictionary line {line}: malformed"),
+            Error::DuplicateWord { word, entry } => write!(f, "duplicate dictionary word {word:?} (entry {entry})"),
+            Error::DictEmpty => write!(f, "dictionary is empty"),
+            Error::DictZeroCounts => write!(f, "all dictionary counts are zero"),
+            Error::ContractionsBadLine { path, line } => {
+                write!(f, "contractions table {} line {line}: malformed", path.display())
+            }
+            Error::ContractionsEmptyExpansion { path, line } => {
+                write!(f, "contractions table {} line {line}: empty expansion", path.display())
+            }
+            Error::SynonymsBadLine { path, line } => {
+                write!(f, "synonyms file {} line {line}: malformed", path.display())
+            }
+            Error::InputTooLarge(InputLimit::Bytes(n)) => write!(f, "input too large: more than {n} bytes"),
+            Error::InputTooLarge(InputLimit::Tokens(n)) => write!(f, "input too large: more than {n} words"),
+            Error::Timeout(limit) => write!(f, "timed out after {}s", limit.as_secs_f64()),
+            Error::PipelineUnknownStage(name) => write!(f, "unknown pipeline stage {name:?}"),
+            Error::PipelineDuplicateStage(stage) => write!(f, "duplicate pipeline stage {}", stage.name()),
+            Error::PipelineStageOrder { text, token } => {
+                write!(f, "text stage {} after token stage {}", text.name(), token.name())
+            }
+            Error::LangDataBadLine { path, line } => write!(f, "language data {} line {line}: malformed", path.display()),
+            Error::LangDataUnknownKey { path, line, key } => {
+                write!(f, "language data {} line {line}: unknown key {key:?}", path.display())
+            }
+            Error::LangDataBadValue { path, line, key } => {
+                write!(f, "language data {} line {line}: bad value for {key:?}", path.display())
+            }
+            Error::LangDataMissingVowels { path } => write!(f, "language data {}: no vowels", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } | Error::Stdin(source) => Some(source),
+            Error::DictJson(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Предупреждение, не мешающее оценке
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Warning {
+    /// Повторы слов в словаре сведены по --dict-duplicates
+    DictDuplicates { count: usize },
+    /// Некорректные записи словаря пропущены (--dict-lenient); номера первых с единицы
+    DictMalformedSkipped { count: usize, first: Vec<usize> },
+    /// Веса вне [0, 1] приведены к границам
+    WeightsClamped { count: usize },
+    /// Текст, по-видимому, на другом языке (код языка из определителя)
+    LanguageMismatch { detected: &'static str, expected: Lang },
+    /// Закрывающая кавычка без открывающей (смещение в байтах)
+    StrayClosingQuote { at: usize },
+    /// Цитата, не закрытая к концу абзаца, не продолжена в следующем
+    UnclosedQuoteParagraph { at: usize },
+    UnclosedQuoteAtEnd { at: usize },
+    /// readability:ignore-end без открывающей ignore-start (номер строки с единицы)
+    StrayIgnoreEnd { line: usize },
+    /// readability:ignore-start не закрыта: исключено всё до конца текста
+    UnclosedIgnoreStart { line: usize },
+    /// Метрика не посчитана и выводится как null
+    MetricUnavailable { metric: Metric, reason: MetricGap },
+    /// При --aggregate-by ни одна часть не набрала --min-tokens слов; оценка — по всем словам
+    NoAggregateUnits { by: AggregateBy, excluded: usize },
+    /// Слова длиннее --max-token-len символов пропущены
+    LongTokensSkipped { count: usize, max: usize },
+    /// Файл --cache-scores не читается: кэш начат заново
+    CacheCorrupt { path: PathBuf },
+    /// Нечитаемые строки журнала --history пропущены
+    HistoryBrokenLines { count: usize },
+}
+
+/// Коды и имена предупреждений. Коды стабильны: новый вид получает следующий номер,
+/// номер удалённого не переиспользуется.
+pub const WARNING_CODES: &[(&str, &str)] = &[
+    ("W001", "DICT_DUPLICATES"),
+    ("W002", "DICT_MALFORMED"),
+    ("W003", "WEIGHTS_CLAMPED"),
+    ("W004", "LANG_MISMATCH"),
+    ("W005", "STRAY_CLOSING_QUOTE"),
+    ("W006", "UNCLOSED_QUOTE_PARAGRAPH"),
+    ("W007", "UNCLOSED_QUOTE_AT_END"),
+    ("W008", "STRAY_IGNORE_END"),
+    ("W009", "UNCLOSED_IGNORE_START"),
+    ("W010", "METRIC_UNAVAILABLE"),
+    ("W011", "NO_AGGREGATE_UNITS"),
+    ("W012", "LONG_TOKENS_SKIPPED"),
+    ("W013", "CACHE_CORRUPT"),
+    ("W014", "HISTORY_BROKEN_LINES"),
+];
+
+impl Warning {
+    fn index(&self) -> usize {
+        match self {
+            Warning::DictDuplicates { .. } => 0,
+            Warning::DictMalformedSkipped { .. } => 1,
+            Warning::WeightsClamped { .. } => 2,
+            Warning::LanguageMismatch { .. } => 3,
+            Warning::StrayClosingQuote { .. } => 4,
+            Warning::UnclosedQuoteParagraph { .. } => 5,
+            Warning::UnclosedQuoteAtEnd { .. } => 6,
+            Warning::StrayIgnoreEnd { .. } => 7,
+            Warning::UnclosedIgnoreStart { .. } => 8,
+            Warning::MetricUnavailable { .. } => 9,
+            Warning::NoAggregateUnits { .. } => 10,
+            Warning::LongTokensSkipped { .. } => 11,
+            Warning::CacheCorrupt { .. } => 12,
+            Warning::HistoryBrokenLines { .. } => 13,
+        }
+    }
+
+    /// Стабильный код: W004
+    pub fn code(&self) -> &'static str {
+        WARNING_CODES[self.index()].0
+    }
+
+    /// Имя вида: LANG_MISMATCH
+    pub fn name(&self) -> &'static str {
+        WARNING_CODES[self.index()].1
+    }
+
+    /// Поля предупреждения для JSON, без текста
+    pub fn context(&self) -> serde_json::Value {
+        match self {
+            Warning::DictDuplicates { count } | Warning::WeightsClamped { count } | Warning::HistoryBrokenLines { count } => {
+                serde_json::json!({ "count": count })
+            }
+            Warning::DictMalformedSkipped { count, first } => serde_json::json!({ "count": count, "first": first }),
+            Warning::LanguageMismatch { detected, expected } => {
+                serde_json::json!({ "detected": detected, "expected": expected.code() })
+            }
+            Warning::StrayClosingQuote { at } | Warning::UnclosedQuoteParagraph { at } | Warning::UnclosedQuoteAtEnd { at } => {
+                serde_json::json!({ "at": at })
+            }
+            Warning::StrayIgnoreEnd { line } | Warning::UnclosedIgnoreStart { line } => serde_json::json!({ "line": line }),
+            Warning::MetricUnavailable { metric, reason } => {
+                serde_json::json!({ "metric": metric.name(), "reason": reason.name() })
+            }
+            Warning::NoAggregateUnits { by, excluded } => serde_json::json!({ "by": by.name(), "excluded": excluded }),
+            Warning::LongTokensSkipped { count, max } => serde_json::json!({ "count": count, "max": max }),
+            Warning::CacheCorrupt { path } => serde_json::json!({ "path": path.display().to_string() }),
+        }
+    }
+
+    /// Обратно по коду и полям `context` (записи --results-out и --cache-scores)
+    pub fn from_json(code: &str, context: &serde_json::Value) -> Option<Warning> {
+        let field = |key: &str| context[key].as_u64().map(|n| n as usize);
+        Some(match code {
+            "W001" => Warning::DictDuplicates { count: field("count")? },
+            "W002" => Warning::DictMalformedSkipped {
+                count: field("count")?,
+                first: context["first"].as_array()?.iter().map(|n| n.as_u64().map(|n| n as usize)).collect::<Option<_>>()?,
+            },
+            "W003" => Warning::WeightsClamped { count: field("count")? },
+            "W004" => Warning::LanguageMismatch {
+                detected: crate::known_language(context["detected"].as_str()?)?,
+                expected: Lang::from_code(context["expected"].as_str()?)?,
+            },
+            "W005" => Warning::StrayClosingQuote { at: field("at")? },
+            "W006" => Warning::UnclosedQuoteParagraph { at: field("at")? },
+            "W007" => Warning::UnclosedQuoteAtEnd { at: field("at")? },
+            "W008" => Warning::StrayIgnoreEnd { line: field("line")? },
+            "W009" => Warning::UnclosedIgnoreStart { line: field("line")? },
+            "W010" => Warning::MetricUnavailable {
+                metric: Metric::from_name(context["metric"].as_str()?)?,
+                reason: MetricGap::from_name(context["reason"].as_str()?)?,
+            },
+            "W011" => Warning::NoAggregateUnits {
+                by: AggregateBy::from_name(context["by"].as_str()?)?,
+                excluded: field("excluded")?,
+            },
+            "W012" => Warning::LongTokensSkipped { count: field("count")?, max: field("max")? },
+            "W013" => Warning::CacheCorrupt { path: PathBuf::from(context["path"].as_str()?) },
+            "W014" => Warning::HistoryBrokenLines { count: field("count")? },
+            _ => return None,
+        })
+    }
+}
+
+/// Код предупреждения по записи `W004` или `W004_LANG_MISMATCH` (без учёта регистра)
+pub fn parse_warning_code(s: &str) -> Option<&'static str> {
+    WARNING_CODES
+        .iter()
+        .find(|(code, name)| s.eq_ignore_ascii_case(code) || s.eq_ignore_ascii_case(&format!("{code}_{name}")))
+        .map(|(code, _)| *code)
+}
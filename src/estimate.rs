@@ -0,0 +1,163 @@
+//! Приближённая оценка по случайной выборке предложений (--estimate) для очень больших входов.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::dict::FrequencyDict;
+use crate::rng::SplitMix64;
+use crate::{metrics, prepare_text, token_weight, Error, FileKind, InputLimit, Result, ScoreOptions};
+
+/// Размер выборки и зерно генератора
+#[derive(Clone, Copy, Debug)]
+pub struct EstimateOptions {
+    pub sentences: usize,
+    pub seed: u64,
+}
+
+/// Оценка по выборке: отношение суммы весов к числу слов в выбранных предложениях
+#[derive(Clone, Copy, Debug)]
+pub struct Estimate {
+    pub score: f64,
+    /// Стандартная ошибка оценки-отношения по разбросу между предложениями,
+    /// с поправкой на конечность совокупности (при выборке из всех предложений — 0);
+    /// None — выбрано одно предложение, разброс не определён
+    pub std_error: Option<f64>,
+    /// Слов в выбранных предложениях
+    pub tokens: usize,
+    pub sampled: usize,
+    /// Всего предложений во входе
+    pub total: usize,
+    pub seed: u64,
+}
+
+impl Estimate {
+    pub fn to_json(self) -> serde_json::Value {
+        serde_json::json!({
+            "std_error": self.std_error,
+            "sampled_sentences": self.sampled,
+            "total_sentences": self.total,
+            "seed": self.seed,
+        })
+    }
+}
+
+/// Сколько байт читать за раз
+const CHUNK_BYTES: usize = 64 * 1024;
+
+/// Резервуарная выборка (алгоритм R) по потоку предложений
+struct Reservoir {
+    sample: Vec<String>,
+    seen: usize,
+    rng: SplitMix64,
+    capacity: usize,
+}
+
+impl Reservoir {
+    fn offer(&mut self, sentence: &str) {
+        self.seen += 1;
+        if self.sample.len() < self.capacity {
+            self.sample.push(sentence.to_string());
+        } else {
+            let j = self.rng.below(self.seen);
+            if j < self.capacity {
+                self.sample[j] = sentence.to_string();
+            }
+        }
+    }
+}
+
+/// Читает вход (файл или STDIN) один раз кусками, режет на предложения по тем же правилам,
+/// что и `metrics::sentence_ends`, и держит в памяти только выборку и незаконченное предложение.
+/// Одинаковые вход, параметры и зерно дают одинаковый результат. None — в выборке нет ни одного слова.
+pub fn estimate(
+    path: Option<&Path>,
+    dict: &FrequencyDict,
+    opts: &ScoreOptions,
+    eopts: EstimateOptions,
+) -> Result<Option<Estimate>> {
+    let io_err = |source| match path {
+        Some(p) => Error::Io {
+            kind: FileKind::Text,
+            path: p.to_path_buf(),
+            source,
+        },
+        None => Error::Stdin(source),
+    };
+    let mut input: Box<dyn Read> = match path {
+        Some(p) => Box::new(File::open(p).map_err(io_err)?),
+        None => Box::new(io::stdin().lock()),
+    };
+    let mut reservoir = Reservoir {
+        sample: Vec::with_capacity(eopts.sentences),
+        seen: 0,
+        rng: SplitMix64::new(eopts.seed),
+        capacity: eopts.sentences,
+    };
+    let mut bytes = Vec::new();
+    let mut pending = String::new();
+    let mut chunk = vec![0u8; CHUNK_BYTES];
+    let mut read_total = 0u64;
+    loop {
+        let n = input.read(&mut chunk).map_err(io_err)?;
+        read_total += n as u64;
+        if let Some(max) = opts.max_input_bytes.filter(|&max| read_total > max) {
+            return Err(Error::InputTooLarge(InputLimit::Bytes(max)));
+        }
+        bytes.extend_from_slice(&chunk[..n]);
+        // Незаконченный в конце куска символ UTF-8 ждёт следующего куска
+        let valid = match std::str::from_utf8(&bytes) {
+            Ok(s) => s.len(),
+            Err(e) if e.error_len().is_none() && n > 0 => e.valid_up_to(),
+            Err(e) => return Err(io_err(io::Error::new(io::ErrorKind::InvalidData, e))),
+        };
+        pending.push_str(std::str::from_utf8(&bytes[..valid]).expect("проверено выше"));
+        bytes.drain(..valid);
+        let at_eof = n == 0;
+        // Конец буфера — ещё не конец предложения: дальше может идти его продолжение
+        let mut start = 0;
+        for end in metrics::sentence_ends(&pending, opts.typographic) {
+            if end == pending.len() && !at_eof {
+                break;
+            }
+            reservoir.offer(&pending[start..end]);
+            start = end;
+        }
+        pending.drain(..start);
+        if at_eof {
+            break;
+        }
+    }
+
+    // Сумма весов и число слов каждого выбранного предложения
+    let mut parts = Vec::with_capacity(reservoir.sample.len());
+    for sentence in &reservoir.sample {
+        let prepared = prepare_text(sentence, Some(dict), opts)?;
+        let sum: f64 = prepared.tokens.iter().map(|t| token_weight(t, dict, opts)).sum();
+        parts.push((sum, prepared.tokens.len()));
+    }
+    let tokens: usize = parts.iter().map(|&(_, n)| n).sum();
+    if tokens == 0 {
+        return Ok(None);
+    }
+    let score = parts.iter().map(|&(s, _)| s).sum::<f64>() / tokens as f64;
+    let m = parts.len() as f64;
+    let std_error = (parts.len() >= 2).then(|| {
+        let mean_tokens = tokens as f64 / m;
+        let residuals: f64 = parts
+            .iter()
+            .map(|&(s, n)| (s - score * n as f64).powi(2))
+            .sum::<f64>()
+            / (m - 1.0);
+        let fpc = 1.0 - m / reservoir.seen as f64;
+        (fpc * residuals / m).sqrt() / mean_tokens
+    });
+    Ok(Some(Estimate {
+        score,
+        std_error,
+        tokens,
+        sampled: parts.len(),
+        total: reservoir.seen,
+        seed: eopts.seed,
+    }))
+}
@@ -0,0 +1,43 @@
+//! Выгрузка оцениваемых слов по одному для признаков машинного обучения (--export-features).
+
+use crate::dict::FrequencyDict;
+use crate::{metrics, token_weight, PreparedText, ScoreOptions};
+
+/// Одно слово в порядке текста
+#[derive(Clone, Copy, Debug)]
+pub struct TokenFeature<'a> {
+    /// Номер слова (с 0) среди оцениваемых
+    pub index: usize,
+    pub word: &'a str,
+    /// Вес, с которым слово вошло в оценку: среднее весов равно оценке (при --aggregate-by —
+    /// оценке по всем словам)
+    pub weight: f64,
+    pub oov: bool,
+    /// Номер предложения (с 0), в котором стоит слово
+    pub sentence: usize,
+}
+
+/// Передаёт в `f` по одному слова, по которым считалась оценка: после всех стадий
+/// нормализации, --top-text-words и --top-text-sentences.
+pub fn for_each_feature<'a, E>(
+    prepared: &'a PreparedText,
+    dict: &FrequencyDict,
+    opts: &ScoreOptions,
+    mut f: impl FnMut(TokenFeature<'a>) -> Result<(), E>,
+) -> Result<(), E> {
+    let mut ends = metrics::sentence_ends(&prepared.text, opts.typographic).peekable();
+    let mut sentence = 0;
+    for (index, t) in prepared.analyzed_tokens().iter().enumerate() {
+        while ends.next_if(|&end| end <= t.start).is_some() {
+            sentence += 1;
+        }
+        f(TokenFeature {
+            index,
+            word: &t.word,
+            weight: token_weight(t, dict, opts),
+            oov: !dict.contains(&t.word),
+            sentence,
+        })?;
+    }
+    Ok(())
+}
@@ -0,0 +1,33 @@
+//! SHA-256 по ходу чтения (--with-provenance): вход и словарь хэшируются тем же проходом,
+//! которым читаются, без повторного чтения файла.
+
+use std::io::{self, Read};
+
+use sha2::{Digest, Sha256};
+
+/// Читатель, передающий прочитанные байты в `hasher`; без хэшера — просто обёртка
+pub struct HashingReader<'a, R> {
+    inner: R,
+    hasher: Option<&'a mut Sha256>,
+}
+
+impl<'a, R: Read> HashingReader<'a, R> {
+    pub fn new(inner: R, hasher: Option<&'a mut Sha256>) -> Self {
+        HashingReader { inner, hasher }
+    }
+}
+
+impl<R: Read> Read for HashingReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if let Some(h) = self.hasher.as_deref_mut() {
+            h.update(&buf[..n]);
+        }
+        Ok(n)
+    }
+}
+
+/// Итог хэшера в шестнадцатеричном виде
+pub fn hex(hasher: Sha256) -> String {
+    hasher.finalize().iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -0,0 +1,180 @@
+//! Журнал оценок (--history): JSON Lines с записью на каждую оценку, и подкоманда `history`.
+
+use std::fs::OpenOptions;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use clap::Args;
+use sha2::{Digest, Sha256};
+
+use readability::{ScoreReport, Warning};
+
+use crate::corpus::{FileOutcome, FileResult};
+use crate::i18n::Msg;
+use crate::warnings;
+
+#[derive(Args, Debug)]
+pub struct HistoryArgs {
+    /// Журнал, записанный с --history
+    #[arg(long = "show")]
+    show: PathBuf,
+
+    /// Сколько последних записей показать
+    #[arg(long = "last", default_value_t = 10)]
+    last: usize,
+}
+
+/// SHA-256 текста в шестнадцатеричном виде
+pub fn text_hash(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Откуда берётся время записей журнала
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Clock {
+    /// Текущее время
+    System,
+    /// Заданное время, секунды от 1970-01-01 UTC (SOURCE_DATE_EPOCH при --reproducible)
+    Fixed(u64),
+    /// Без поля "time" (--reproducible без SOURCE_DATE_EPOCH)
+    Omitted,
+}
+
+impl Clock {
+    fn time(self) -> Option<String> {
+        match self {
+            Clock::System => Some(utc_now()),
+            Clock::Fixed(secs) => Some(utc_time(secs)),
+            Clock::Omitted => None,
+        }
+    }
+}
+
+/// Текущее время UTC в формате RFC 3339
+pub fn utc_now() -> String {
+    utc_time(SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()))
+}
+
+/// Время UTC в формате RFC 3339 с точностью до секунды
+fn utc_time(secs: u64) -> String {
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Дни от 1970-01-01 → григорианская дата (алгоритм civil_from_days Говарда Хиннанта)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3600,
+        rem / 60 % 60,
+        rem % 60
+    )
+}
+
+/// Запись журнала об одной оценке; `input` — путь к тексту или "-" для STDIN
+pub fn record(input: &str, text: &str, report: &ScoreReport, clock: Clock) -> serde_json::Value {
+    let mut rec = serde_json::json!({
+        "path": input,
+        "score": report.score,
+        "tokens": report.tokens,
+        "sha256": text_hash(text),
+    });
+    if let Some(time) = clock.time() {
+        rec["time"] = time.into();
+    }
+    rec
+}
+
+/// Дописывает запись в журнал одной строкой и сбрасывает её на диск. Если последняя строка
+/// оборвана (сбой посреди записи), новая запись начинается с новой строки, а не склеивается с ней.
+pub fn append(path: &Path, record: &serde_json::Value) -> Result<()> {
+    let write = || -> io::Result<()> {
+        let mut f = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+        let mut line = String::new();
+        if f.metadata()?.len() > 0 {
+            let mut last = [0u8; 1];
+            f.seek(SeekFrom::End(-1))?;
+            f.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                line.push('\n');
+            }
+        }
+        line.push_str(&record.to_string());
+        line.push('\n');
+        f.write_all(line.as_bytes())?;
+        f.sync_data()
+    };
+    write().with_context(|| Msg::FileWrite(path).to_string())
+}
+
+/// Записи по всем оценённым в этом запуске файлам корпуса (взятые из --resume уже записаны). Хеш считается по файлу, перечитанному после
+/// оценки: оценка сама текст не хранит, а держать все тексты корпуса ради журнала дорого.
+pub fn append_results(path: &Path, results: &[FileResult], clock: Clock) -> Result<()> {
+    for r in results {
+        let FileOutcome::Scored(report) = &r.outcome else {
+            continue;
+        };
+        if r.reused {
+            continue;
+        }
+        let Ok(text) = std::fs::read_to_string(&r.path) else {
+            continue;
+        };
+        append(path, &record(&r.path.display().to_string(), &text, report, clock))?;
+    }
+    Ok(())
+}
+
+/// Печатает последние записи журнала таблицей; изменение оценки считается относительно
+/// предыдущей записи того же файла. Нечитаемые строки пропускаются с предупреждением.
+pub fn run(args: &HistoryArgs) -> Result<()> {
+    let text = std::fs::read_to_string(&args.show)
+        .with_context(|| Msg::HistoryOpen(&args.show).to_string())?;
+    let mut broken = 0usize;
+    let mut rows: Vec<(serde_json::Value, Option<f64>)> = Vec::new();
+    let mut last_by_path: std::collections::HashMap<String, f64> = Default::default();
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        let Ok(rec) = serde_json::from_str::<serde_json::Value>(line) else {
+            broken += 1;
+            continue;
+        };
+        let path = rec["path"].as_str().unwrap_or("").to_string();
+        let delta = rec["score"]
+            .as_f64()
+            .and_then(|s| last_by_path.insert(path, s).map(|prev| s - prev));
+        rows.push((rec, delta));
+    }
+    if broken > 0 {
+        warnings::emit(&[Warning::HistoryBrokenLines { count: broken }], Some(&args.show));
+    }
+
+    let mut out = io::stdout().lock();
+    writeln!(
+        out,
+        "{:<20}  {:>9}  {:>10}  {:>7}  {:<12}  path",
+        "time", "score", "delta", "tokens", "sha256"
+    )?;
+    for (rec, delta) in &rows[rows.len().saturating_sub(args.last)..] {
+        let score = rec["score"].as_f64().map_or_else(|| "-".to_string(), |s| format!("{s:.6}"));
+        let delta = delta.map_or_else(|| "-".to_string(), |d| format!("{d:+.6}"));
+        let hash: String = rec["sha256"].as_str().unwrap_or("-").chars().take(12).collect();
+        writeln!(
+            out,
+            "{:<20}  {score:>9}  {delta:>10}  {:>7}  {hash:<12}  {}",
+            rec["time"].as_str().unwrap_or("-"),
+            rec["tokens"].as_u64().map_or_else(|| "-".to_string(), |t| t.to_string()),
+            rec["path"].as_str().unwrap_or("-"),
+        )?;
+    }
+    Ok(())
+}
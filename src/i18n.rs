@@ -0,0 +1,1020 @@
+//! Сообщения для пользователя на языке интерфейса: английский по умолчанию и русский.
+//! Язык выбирается через --lang-ui, иначе по переменной окружения LANG.
+
+use std::error::Error as StdError;
+use std::ffi::OsString;
+use std::fmt;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+use readability::aggregate::AggregateBy;
+use readability::metrics::MetricGap;
+use readability::pipeline::Stage;
+use readability::{Error, FileKind, InputLimit, Lang, Warning, WARNING_CODES};
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UiLang {
+    En,
+    Ru,
+}
+
+static UI_LANG: OnceLock<UiLang> = OnceLock::new();
+
+/// Язык интерфейса, выбранный при запуске (до вызова [`init`] — английский)
+pub fn ui() -> UiLang {
+    UI_LANG.get().copied().unwrap_or(UiLang::En)
+}
+
+/// Выбирает язык до разбора аргументов, чтобы и справка clap выводилась на нём:
+/// `--lang-ui ru` / `--lang-ui=ru`, затем LANG (ru_RU.UTF-8 → ru), иначе английский.
+/// Распознанный --lang-ui убирается из аргументов: иначе clap счёл бы его аргументом корня,
+/// несовместимым с подкомандой. Некорректное значение остаётся — о нём сообщит clap.
+pub fn init(args: Vec<OsString>) -> (UiLang, Vec<OsString>) {
+    let mut from_args = None;
+    let mut rest = Vec::with_capacity(args.len());
+    let mut it = args.into_iter().peekable();
+    while let Some(arg) = it.next() {
+        let s = arg.to_string_lossy();
+        let value = match s.strip_prefix("--lang-ui") {
+            Some("") => it.peek().map(|v| (v.to_string_lossy().into_owned(), true)),
+            Some(v) => v.strip_prefix('=').map(|v| (v.to_string(), false)),
+            None => None,
+        };
+        match value.and_then(|(v, separate)| Some((UiLang::from_str(&v, true).ok()?, separate))) {
+            Some((lang, separate)) => {
+                from_args = Some(lang);
+                if separate {
+                    it.next();
+                }
+            }
+            None => rest.push(arg),
+        }
+    }
+    let lang = from_args.unwrap_or_else(|| match std::env::var("LANG") {
+        Ok(v) if v.starts_with("ru") => UiLang::Ru,
+        _ => UiLang::En,
+    });
+    UI_LANG.get_or_init(|| lang);
+    (lang, rest)
+}
+
+/// Номера через запятую: 3, 17, 42
+fn join_numbers(numbers: &[usize]) -> String {
+    numbers.iter().map(usize::to_string).collect::<Vec<_>>().join(", ")
+}
+
+fn lang_name(code: &str, ui: UiLang) -> &str {
+    match (ui, code) {
+        (UiLang::En, "en") => "English",
+        (UiLang::En, "de") => "German",
+        (UiLang::En, "fr") => "French",
+        (UiLang::En, "es") => "Spanish",
+        (UiLang::En, "ru") => "Russian",
+        (UiLang::Ru, "en") => "английский",
+        (UiLang::Ru, "de") => "немецкий",
+        (UiLang::Ru, "fr") => "французский",
+        (UiLang::Ru, "es") => "испанский",
+        (UiLang::Ru, "ru") => "русский",
+        (_, other) => other,
+    }
+}
+
+/// Ключи каталога с данными для подстановки
+#[derive(Clone, Copy, Debug)]
+pub enum Msg<'a> {
+    ErrorPrefix,
+    NotANumber(&'a str),
+    NotPositive,
+    NotPositiveNumber,
+    WeightOutOfRange(f64),
+    CoverageOutOfRange(f64),
+    InvalidPath(&'a Path),
+    TempFileCreate(&'a Path),
+    FileReplace(&'a Path),
+    FileWrite(&'a Path),
+    DirRead(&'a Path),
+    BadExcludeGlob(&'a str),
+    /// Пояснение уже на языке интерфейса (текст предупреждения о языке)
+    StrictLangMismatch(&'a str),
+    NoWords,
+    /// Слов не осталось, потому что все длиннее --max-token-len
+    NoWordsLongTokens { count: usize, max: usize },
+    /// Слов нет, а текст, по-видимому, на другом языке, чем словарь
+    NoWordsLanguage { detected: &'a str, expected: Lang },
+    AppendScoreSingleInput,
+    AnnotateSingleInput,
+    EstimateSingleInput,
+    ReproducibleNeedsSeed(&'a str),
+    SourceDateEpochInvalid(&'a str),
+    ContractionsFileUnused,
+    VerbosePipeline(&'a str),
+    VerboseThresholds { bounds: &'a str, sentences: usize },
+    AutoThresholdsUnavailable,
+    DeniedWarnings(usize),
+    UnknownWarningCode(&'a str),
+    VerboseClauses { over: usize, max_clauses: usize },
+    TemplatePlainOnly,
+    TemplateUnknownField { name: &'a str, valid: &'a str },
+    TemplateSpecOnText(&'a str),
+    TemplateBadSpec(&'a str),
+    TemplateBadEscape(&'a str),
+    TemplateUnclosed,
+    EstimatedFrom(usize),
+    SelfRelativeCorpusOnly,
+    ProfileSingleInput,
+    FeaturesSingleInput,
+    WatchSingleInput,
+    ResultsOutCorpusOnly,
+    ResumeMismatch(&'a Path),
+    CacheCorpusOnly,
+    CacheFingerprint(&'a Path),
+    CacheStats { hits: usize, misses: usize },
+    HistoryOpen(&'a Path),
+    DaemonBadJson(&'a str),
+    DaemonNoText,
+    DaemonBadTopTextWords,
+    DaemonStdin,
+    DaemonReloadBusy,
+    DaemonUnknownCmd(&'a str),
+    DictVerbose { entries: usize, duplicates: usize, distinct: usize },
+    DictCoverage { entries: usize, coverage: f64 },
+    DictQuantized { bits: u8, max_error: f64 },
+    QuantizeBits(&'a str),
+    BlendBadComponent(&'a str),
+    BlendSum(f64),
+    OovWeightsBadPart(&'a str),
+    VerboseTokens(usize),
+    VerboseContractions(usize),
+    VerboseQuotes { chars: usize, tokens: usize },
+    VerboseIgnored(usize),
+    VerboseDehyphenated(usize),
+    VerboseDeduped { lines: usize, tokens: usize },
+    VerboseAggregate { units: usize, excluded: usize, token_score: f64 },
+    SkippedInsufficient(usize),
+    Skipped(&'a str),
+    Failed(&'a str),
+    PruneOverwrite(&'a Path),
+    WordListOpen(&'a Path),
+    DictWrite(&'a Path),
+    PruneReport { entries_in: usize, entries_out: usize, bytes: usize },
+    DiffThreshold { flag: &'static str, actual: &'a str, limit: &'a str },
+    WordsStdin,
+}
+
+impl Msg<'_> {
+    pub fn render(&self, ui: UiLang) -> String {
+        use UiLang::{En, Ru};
+        match (*self, ui) {
+            (Msg::ErrorPrefix, En) => "Error".into(),
+            (Msg::ErrorPrefix, Ru) => "Ошибка".into(),
+            (Msg::NotANumber(s), En) => format!("not a number: {s}"),
+            (Msg::NotANumber(s), Ru) => format!("не число: {s}"),
+            (Msg::NotPositive, En) => "must be a positive integer".into(),
+            (Msg::NotPositive, Ru) => "ожидалось целое больше нуля".into(),
+            (Msg::NotPositiveNumber, En) => "must be a positive number".into(),
+            (Msg::NotPositiveNumber, Ru) => "ожидалось число больше нуля".into(),
+            (Msg::WeightOutOfRange(v), En) => format!("weight must be in [0, 1], got {v}"),
+            (Msg::WeightOutOfRange(v), Ru) => format!("вес должен быть в диапазоне [0, 1], получено {v}"),
+            (Msg::CoverageOutOfRange(v), En) => format!("coverage must be in (0, 1], got {v}"),
+            (Msg::CoverageOutOfRange(v), Ru) => format!("доля должна быть в диапазоне (0, 1], получено {v}"),
+            (Msg::InvalidPath(p), En) => format!("Invalid file path: {}", p.display()),
+            (Msg::InvalidPath(p), Ru) => format!("Некорректный путь к файлу: {}", p.display()),
+            (Msg::TempFileCreate(p), En) => format!("Failed to create temporary file: {}", p.display()),
+            (Msg::TempFileCreate(p), Ru) => format!("Не удалось создать временный файл: {}", p.display()),
+            (Msg::FileReplace(p), En) => format!("Failed to replace file: {}", p.display()),
+            (Msg::FileReplace(p), Ru) => format!("Не удалось заменить файл: {}", p.display()),
+            (Msg::FileWrite(p), En) => format!("Failed to write file: {}", p.display()),
+            (Msg::FileWrite(p), Ru) => format!("Не удалось записать файл: {}", p.display()),
+            (Msg::DirRead(p), En) => format!("Failed to read directory: {}", p.display()),
+            (Msg::DirRead(p), Ru) => format!("Не удалось прочитать каталог: {}", p.display()),
+            (Msg::BadExcludeGlob(g), En) => format!("Invalid --exclude pattern: {g}"),
+            (Msg::BadExcludeGlob(g), Ru) => format!("Некорректный шаблон --exclude: {g}"),
+            (Msg::StrictLangMismatch(detail), En) => {
+                format!("Text language does not match the dictionary language: {detail}")
+            }
+            (Msg::StrictLangMismatch(detail), Ru) => {
+                format!("Язык текста не совпадает с языком словаря: {detail}")
+            }
+            (Msg::NoWords, En) => "No words found to score".into(),
+            (Msg::NoWords, Ru) => "Не найдено ни одного слова для оценки".into(),
+            (Msg::NoWordsLanguage { detected, expected }, En) => format!(
+                "No words found to score: the text appears to be {}, but the dictionary is {} (--lang)",
+                lang_name(detected, ui),
+                lang_name(expected.code(), ui)
+            ),
+            (Msg::NoWordsLanguage { detected, expected }, Ru) => format!(
+                "Не найдено ни одного слова для оценки: текст, по-видимому, на языке «{}», а словарь — «{}» (--lang)",
+                lang_name(detected, ui),
+                lang_name(expected.code(), ui)
+            ),
+            (Msg::NoWordsLongTokens { count, max }, En) => {
+                format!("No words found to score: {count} words longer than {max} characters skipped (--max-token-len)")
+            }
+            (Msg::NoWordsLongTokens { count, max }, Ru) => {
+                format!("Не найдено ни одного слова для оценки: пропущено слов длиннее {max} символов: {count} (--max-token-len)")
+            }
+            (Msg::AppendScoreSingleInput, En) => "--append-score supports a single input text only".into(),
+            (Msg::AppendScoreSingleInput, Ru) => {
+                "--append-score поддерживается только для одного входного текста".into()
+            }
+            (Msg::AnnotateSingleInput, En) => "--annotate supports a single input text only".into(),
+            (Msg::AnnotateSingleInput, Ru) => "--annotate поддерживается только для одного входного текста".into(),
+            (Msg::TemplatePlainOnly, En) => "--template applies to plain output only".into(),
+            (Msg::TemplatePlainOnly, Ru) => "--template применяется только к plain-выводу".into(),
+            (Msg::TemplateUnknownField { name, valid }, En) => {
+                format!("unknown template field {{{name}}}; valid fields: {valid}")
+            }
+            (Msg::TemplateUnknownField { name, valid }, Ru) => {
+                format!("неизвестное поле шаблона {{{name}}}; допустимые поля: {valid}")
+            }
+            (Msg::TemplateSpecOnText(name), En) => format!("field {{{name}}} is text and takes no format"),
+            (Msg::TemplateSpecOnText(name), Ru) => format!("поле {{{name}}} текстовое, формат к нему не применяется"),
+            (Msg::TemplateBadSpec(spec), En) => format!("invalid format {spec:?}: expected .N, % or .N%"),
+            (Msg::TemplateBadSpec(spec), Ru) => format!("некорректный формат {spec:?}: ожидается .N, % или .N%"),
+            (Msg::TemplateBadEscape(escape), En) => format!("unknown escape `{escape}`: expected \\t, \\n or \\\\"),
+            (Msg::TemplateBadEscape(escape), Ru) => {
+                format!("неизвестная escape-последовательность `{escape}`: ожидается \\t, \\n или \\\\")
+            }
+            (Msg::TemplateUnclosed, En) => "unmatched brace in template: write {{ and }} for literal braces".into(),
+            (Msg::TemplateUnclosed, Ru) => {
+                "непарная фигурная скобка в шаблоне: литеральные скобки записываются как {{ и }}".into()
+            }
+            (Msg::VerboseClauses { over, max_clauses }, En) => {
+                format!("Sentences with more than {max_clauses} clauses: {over} (range, clauses, depth)")
+            }
+            (Msg::VerboseClauses { over, max_clauses }, Ru) => {
+                format!("Предложений больше чем с {max_clauses} клаузами: {over} (диапазон, клаузы, глубина)")
+            }
+            (Msg::ContractionsFileUnused, En) => {
+                "--contractions-file needs --expand-contractions or the expand-contractions stage in --pipeline".into()
+            }
+            (Msg::ContractionsFileUnused, Ru) => {
+                "--contractions-file требует --expand-contractions или стадии expand-contractions в --pipeline".into()
+            }
+            (Msg::VerbosePipeline(stages), En) => format!("Pipeline: {stages}"),
+            (Msg::VerbosePipeline(stages), Ru) => format!("Цепочка нормализации: {stages}"),
+            (Msg::VerboseThresholds { bounds, sentences }, En) => {
+                format!("Label thresholds (quintiles of {sentences} reference sentences): {bounds}")
+            }
+            (Msg::VerboseThresholds { bounds, sentences }, Ru) => {
+                format!("Пороги меток (квинтили {sentences} эталонных предложений): {bounds}")
+            }
+            (Msg::DeniedWarnings(n), En) => format!("{n} warnings with --deny-warnings"),
+            (Msg::DeniedWarnings(n), Ru) => format!("предупреждений при --deny-warnings: {n}"),
+            (Msg::UnknownWarningCode(s), En) => {
+                format!("unknown warning code {s:?}: expected W001..W{:03} or a full name such as W004_LANG_MISMATCH", WARNING_CODES.len())
+            }
+            (Msg::UnknownWarningCode(s), Ru) => {
+                format!("неизвестный код предупреждения {s:?}: ожидается W001..W{:03} или полное имя, например W004_LANG_MISMATCH", WARNING_CODES.len())
+            }
+            (Msg::AutoThresholdsUnavailable, En) => {
+                "--auto-thresholds: none of the reference sentences could be scored with this pipeline".into()
+            }
+            (Msg::AutoThresholdsUnavailable, Ru) => {
+                "--auto-thresholds: ни одно эталонное предложение не удалось оценить с этой цепочкой нормализации".into()
+            }
+            (Msg::ReproducibleNeedsSeed(flag), En) => format!("--reproducible with {flag} needs an explicit --seed"),
+            (Msg::ReproducibleNeedsSeed(flag), Ru) => format!("--reproducible вместе с {flag} требует явного --seed"),
+            (Msg::SourceDateEpochInvalid(v), En) => {
+                format!("SOURCE_DATE_EPOCH must be a number of seconds since 1970-01-01 UTC, got `{v}`")
+            }
+            (Msg::SourceDateEpochInvalid(v), Ru) => {
+                format!("SOURCE_DATE_EPOCH должен быть числом секунд от 1970-01-01 UTC, а не `{v}`")
+            }
+            (Msg::EstimateSingleInput, En) => "--estimate supports a single input text only".into(),
+            (Msg::EstimateSingleInput, Ru) => "--estimate поддерживается только для одного входного текста".into(),
+            (Msg::EstimatedFrom(1), En) => "estimated from 1 sentence".into(),
+            (Msg::EstimatedFrom(n), En) => format!("estimated from {n} sentences"),
+            (Msg::EstimatedFrom(n), Ru) if n % 10 == 1 && n % 100 != 11 => format!("оценка по {n} предложению"),
+            (Msg::EstimatedFrom(n), Ru) => format!("оценка по {n} предложениям"),
+            (Msg::SelfRelativeCorpusOnly, En) => {
+                "--self-relative needs several input files or a directory: the dictionary is built from them".into()
+            }
+            (Msg::SelfRelativeCorpusOnly, Ru) => {
+                "--self-relative требует нескольких входных файлов или каталога: словарь строится по ним".into()
+            }
+            (Msg::FeaturesSingleInput, En) => "--export-features supports a single input text only".into(),
+            (Msg::FeaturesSingleInput, Ru) => {
+                "--export-features поддерживается только для одного входного текста".into()
+            }
+            (Msg::ProfileSingleInput, En) => "--profile-out supports a single input text only".into(),
+            (Msg::ProfileSingleInput, Ru) => {
+                "--profile-out поддерживается только для одного входного текста".into()
+            }
+            (Msg::ResultsOutCorpusOnly, En) => {
+                "--results-out works in corpus mode only (several --text inputs or a directory)".into()
+            }
+            (Msg::ResultsOutCorpusOnly, Ru) => {
+                "--results-out работает только в режиме корпуса (несколько --text или каталог)".into()
+            }
+            (Msg::ResumeMismatch(p), En) => format!(
+                "{} was written with another dictionary or other settings; run without --resume to score everything again",
+                p.display()
+            ),
+            (Msg::ResumeMismatch(p), Ru) => format!(
+                "{} записан с другим словарём или другими настройками; запустите без --resume, чтобы оценить всё заново",
+                p.display()
+            ),
+            (Msg::CacheCorpusOnly, En) => {
+                "--cache-scores works in corpus mode only (several --text inputs or a directory)".into()
+            }
+            (Msg::CacheCorpusOnly, Ru) => {
+                "--cache-scores работает только в режиме корпуса (несколько --text или каталог)".into()
+            }
+            (Msg::CacheFingerprint(p), En) => format!("Failed to read file for the cache key: {}", p.display()),
+            (Msg::CacheFingerprint(p), Ru) => format!("Не удалось прочитать файл для ключа кэша: {}", p.display()),
+            (Msg::CacheStats { hits, misses }, En) => format!("cache: {hits} hits, {misses} misses"),
+            (Msg::CacheStats { hits, misses }, Ru) => format!("кэш: попаданий {hits}, промахов {misses}"),
+            (Msg::WatchSingleInput, En) => "--watch requires a single --text file".into(),
+            (Msg::WatchSingleInput, Ru) => "--watch требует ровно один файл в --text".into(),
+            (Msg::HistoryOpen(p), En) => format!("Failed to read history: {}", p.display()),
+            (Msg::HistoryOpen(p), Ru) => format!("Не удалось прочитать журнал: {}", p.display()),
+            (Msg::DaemonBadJson(e), En) => format!("Invalid request JSON: {e}"),
+            (Msg::DaemonBadJson(e), Ru) => format!("Некорректный JSON запроса: {e}"),
+            (Msg::DaemonNoText, En) => "Field \"text\" is missing or not a string".into(),
+            (Msg::DaemonNoText, Ru) => "Поле \"text\" отсутствует или не строка".into(),
+            (Msg::DaemonBadTopTextWords, En) => "Field \"top_text_words\" must be a non-negative integer".into(),
+            (Msg::DaemonBadTopTextWords, Ru) => {
+                "Поле \"top_text_words\" должно быть неотрицательным целым".into()
+            }
+            (Msg::DaemonReloadBusy, En) => "A dictionary reload is already in progress".into(),
+            (Msg::DaemonReloadBusy, Ru) => "Словарь уже перезагружается".into(),
+            (Msg::DaemonUnknownCmd(cmd), En) => format!("Unknown command {cmd}: expected \"reload\" or \"status\""),
+            (Msg::DaemonUnknownCmd(cmd), Ru) => {
+                format!("Неизвестная команда {cmd}: ожидалась \"reload\" или \"status\"")
+            }
+            (Msg::DaemonStdin, En) => "Failed to read request from STDIN".into(),
+            (Msg::DaemonStdin, Ru) => "Не удалось прочитать запрос из STDIN".into(),
+            (Msg::DictVerbose { entries, duplicates, distinct }, En) => {
+                format!("Dictionary: {entries} entries, duplicate words: {duplicates}, distinct words: {distinct}")
+            }
+            (Msg::DictVerbose { entries, duplicates, distinct }, Ru) => {
+                format!("Словарь: {entries} записей, повторов слов: {duplicates}, различных слов: {distinct}")
+            }
+            (Msg::DictCoverage { entries, coverage }, En) => {
+                format!("Dictionary coverage: {entries} entries loaded, {:.2}% of the total count", coverage * 100.0)
+            }
+            (Msg::DictCoverage { entries, coverage }, Ru) => {
+                format!("Покрытие словаря: загружено {entries} записей, {:.2}% суммы частот", coverage * 100.0)
+            }
+            (Msg::DictQuantized { bits, max_error }, En) => {
+                format!("Dictionary weights quantized to {bits} bits: score error at most {max_error:.1e}")
+            }
+            (Msg::DictQuantized { bits, max_error }, Ru) => {
+                format!("Веса словаря квантованы до {bits} бит: ошибка оценки не больше {max_error:.1e}")
+            }
+            (Msg::QuantizeBits(s), En) => format!("unsupported width {s:?}: only 16 bits are supported"),
+            (Msg::QuantizeBits(s), Ru) => format!("неподдерживаемая разрядность {s:?}: поддерживается только 16 бит"),
+            (Msg::BlendBadComponent(part), En) => format!("expected path:share, got {part:?}"),
+            (Msg::BlendBadComponent(part), Ru) => format!("ожидалось путь:доля, получено {part:?}"),
+            (Msg::OovWeightsBadPart(part), En) => {
+                format!("Invalid --oov-weights part {part:?}: expected name=W, typo=W or rare=W")
+            }
+            (Msg::OovWeightsBadPart(part), Ru) => {
+                format!("Некорректная часть --oov-weights {part:?}: ожидается name=W, typo=W или rare=W")
+            }
+            (Msg::BlendSum(sum), En) => format!("blend shares must sum to 1.0, got {sum}"),
+            (Msg::BlendSum(sum), Ru) => format!("доли смеси должны в сумме давать 1.0, получено {sum}"),
+            (Msg::VerboseTokens(n), En) => format!("Words scored: {n}"),
+            (Msg::VerboseTokens(n), Ru) => format!("Оценено слов: {n}"),
+            (Msg::VerboseContractions(n), En) => format!("Contractions expanded: {n}"),
+            (Msg::VerboseContractions(n), Ru) => format!("Раскрыто сокращений: {n}"),
+            (Msg::VerboseIgnored(n), En) => format!("Excluded by readability:ignore directives: {n} words"),
+            (Msg::VerboseIgnored(n), Ru) => format!("Исключено директивами readability:ignore: {n} слов"),
+            (Msg::VerboseDehyphenated(n), En) => format!("Words rejoined across line-end hyphens: {n}"),
+            (Msg::VerboseDehyphenated(n), Ru) => format!("Склеено слов, перенесённых с дефисом: {n}"),
+            (Msg::VerboseQuotes { chars, tokens }, En) => {
+                format!("Excluded as direct speech: {chars} characters, {tokens} words")
+            }
+            (Msg::VerboseQuotes { chars, tokens }, Ru) => {
+                format!("Исключено как прямая речь: {chars} символов, {tokens} слов")
+            }
+            (Msg::VerboseAggregate { units, excluded, token_score }, En) => format!(
+                "Parts scored: {units}, excluded below --min-tokens: {excluded}; mean over all words: {token_score:.6}"
+            ),
+            (Msg::VerboseAggregate { units, excluded, token_score }, Ru) => format!(
+                "Оценено частей: {units}, исключено как короткие (--min-tokens): {excluded}; \
+                 среднее по всем словам: {token_score:.6}"
+            ),
+            (Msg::VerboseDeduped { lines, tokens }, En) => {
+                format!("Excluded as repeated lines: {lines} lines, {tokens} words")
+            }
+            (Msg::VerboseDeduped { lines, tokens }, Ru) => {
+                format!("Исключено повторяющихся строк: {lines}, слов в них: {tokens}")
+            }
+            (Msg::SkippedInsufficient(n), En) => format!("skipped: not enough words to score ({n})"),
+            (Msg::SkippedInsufficient(n), Ru) => format!("пропущен: недостаточно слов для оценки ({n})"),
+            (Msg::Skipped(reason), En) => format!("skipped: {reason}"),
+            (Msg::Skipped(reason), Ru) => format!("пропущен: {reason}"),
+            (Msg::Failed(err), En) => format!("error: {err}"),
+            (Msg::Failed(err), Ru) => format!("ошибка: {err}"),
+            (Msg::PruneOverwrite(p), En) => format!(
+                "The result would overwrite the source dictionary {}; use --force if this is intended",
+                p.display()
+            ),
+            (Msg::PruneOverwrite(p), Ru) => format!(
+                "Результат перезапишет исходный словарь {}; используйте --force, если это намеренно",
+                p.display()
+            ),
+            (Msg::WordListOpen(p), En) => format!("Failed to open word list: {}", p.display()),
+            (Msg::WordListOpen(p), Ru) => format!("Не удалось открыть список слов: {}", p.display()),
+            (Msg::DictWrite(p), En) => format!("Failed to write dictionary: {}", p.display()),
+            (Msg::DictWrite(p), Ru) => format!("Не удалось записать словарь: {}", p.display()),
+            (Msg::DiffThreshold { flag, actual, limit }, En) => {
+                format!("Difference exceeds {flag}: {actual} (limit {limit})")
+            }
+            (Msg::DiffThreshold { flag, actual, limit }, Ru) => {
+                format!("Различие превышает порог {flag}: {actual} (порог {limit})")
+            }
+            (Msg::PruneReport { entries_in, entries_out, bytes }, En) => {
+                format!("Entries in: {entries_in}, out: {entries_out}, file size: {bytes} bytes")
+            }
+            (Msg::PruneReport { entries_in, entries_out, bytes }, Ru) => {
+                format!("Записей на входе: {entries_in}, на выходе: {entries_out}, размер файла: {bytes} байт")
+            }
+            (Msg::WordsStdin, En) => "Failed to read words from STDIN".into(),
+            (Msg::WordsStdin, Ru) => "Не удалось прочитать слова из STDIN".into(),
+        }
+    }
+}
+
+/// Печатается на языке, выбранном при запуске: удобно в `bail!` и `format!`
+impl fmt::Display for Msg<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(ui()))
+    }
+}
+
+/// Текст ошибки библиотеки (без причины — её печатает вызывающий код)
+pub fn error_text(err: &Error, ui: UiLang) -> String {
+    use UiLang::{En, Ru};
+    let file = |kind: FileKind| match (kind, ui) {
+        (FileKind::Dict, En) => "Failed to open dictionary",
+        (FileKind::Dict, Ru) => "Не удалось открыть словарь",
+        (FileKind::Text, En) => "Failed to open input text",
+        (FileKind::Text, Ru) => "Не удалось открыть входной текст",
+        (FileKind::Contractions, En) => "Failed to open contractions table",
+        (FileKind::Contractions, Ru) => "Не удалось открыть таблицу сокращений",
+        (FileKind::LangData, En) => "Failed to open language data file",
+        (FileKind::LangData, Ru) => "Не удалось открыть файл данных языка",
+        (FileKind::Synonyms, En) => "Failed to open synonyms file",
+        (FileKind::Synonyms, Ru) => "Не удалось открыть файл синонимов",
+    };
+    match (err, ui) {
+        (Error::Io { kind, path, .. }, _) => format!("{}: {}", file(*kind), path.display()),
+        (Error::Stdin(_), En) => "Failed to read text from STDIN".into(),
+        (Error::Stdin(_), Ru) => "Не удалось прочитать текст из STDIN".into(),
+        (Error::DictJson(_), En) => "Invalid frequency dictionary JSON".into(),
+        (Error::DictJson(_), Ru) => "Некорректный JSON частотного словаря".into(),
+        (Error::DictNotArray, En) => "Expected a top-level JSON array".into(),
+        (Error::DictNotArray, Ru) => "Ожидался JSON-массив верхнего уровня".into(),
+        (Error::DictUnterminated, En) => "The JSON array ends without a closing ]".into(),
+        (Error::DictUnterminated, Ru) => "JSON-массив обрывается без закрывающей ]".into(),
+        (Error::DictTrailingData, En) => "Unexpected data after the closing ] of the JSON array".into(),
+        (Error::DictTrailingData, Ru) => "Лишние данные после закрывающей ] JSON-массива".into(),
+        (Error::DictEntryNotArray { entry }, En) => {
+            format!("Dictionary entry {entry} is not an array of two values")
+        }
+        (Error::DictEntryNotArray { entry }, Ru) => {
+            format!("Элемент словаря {entry} не является массивом из двух значений")
+        }
+        (Error::DictEntryTooShort { entry }, En) => format!("Dictionary entry {entry} has length < 2"),
+        (Error::DictEntryTooShort { entry }, Ru) => format!("Элемент массива словаря {entry} имеет длину < 2"),
+        (Error::DictWordNotString { entry }, En) => {
+            format!("Dictionary entry {entry}: first element is not a string")
+        }
+        (Error::DictWordNotString { entry }, Ru) => format!("Элемент словаря {entry}: первый элемент не строка"),
+        (Error::DictCountNotNumber { entry }, En) => {
+            format!("Dictionary entry {entry}: second element is not a number")
+        }
+        (Error::DictCountNotNumber { entry }, Ru) => format!("Элемент словаря {entry}: второй элемент не число"),
+        (Error::CsvUnclosedQuote { line }, En) => format!("CSV dictionary, line {line}: unclosed quote"),
+        (Error::CsvUnclosedQuote { line }, Ru) => format!("CSV-словарь, строка {line}: незакрытая кавычка"),
+        (Error::CsvBadLine { line }, En) => format!("CSV dictionary, line {line}: expected `word,count`"),
+        (Error::CsvBadLine { line }, Ru) => format!("CSV-словарь, строка {line}: ожидалось `word,count`"),
+        (Error::DuplicateWord { word, entry }, En) => {
+            format!("Word \"{word}\" occurs in the dictionary again (entry {entry}); see --dict-duplicates")
+        }
+        (Error::DuplicateWord { word, entry }, Ru) => {
+            format!("Слово «{word}» встречается в словаре повторно (запись {entry}); см. --dict-duplicates")
+        }
+        (Error::DictEmpty, En) => "Dictionary is empty".into(),
+        (Error::DictEmpty, Ru) => "Словарь пуст".into(),
+        (Error::DictZeroCounts, En) => {
+            "All dictionary entries have zero frequency: nothing to normalize weights by".into()
+        }
+        (Error::DictZeroCounts, Ru) => {
+            "Все записи словаря имеют нулевую частоту: нормировать веса не на что".into()
+        }
+        (Error::ContractionsBadLine { path, line }, En) => format!(
+            "Contractions table {}, line {line}: expected `form<TAB>expansion`",
+            path.display()
+        ),
+        (Error::ContractionsBadLine { path, line }, Ru) => format!(
+            "Таблица сокращений {}, строка {line}: ожидалось `форма<TAB>раскрытие`",
+            path.display()
+        ),
+        (Error::ContractionsEmptyExpansion { path, line }, En) => {
+            format!("Contractions table {}, line {line}: empty expansion", path.display())
+        }
+        (Error::ContractionsEmptyExpansion { path, line }, Ru) => {
+            format!("Таблица сокращений {}, строка {line}: пустое раскрытие", path.display())
+        }
+        (Error::SynonymsBadLine { path, line }, En) => format!(
+            "Synonyms file {}, line {line}: expected `word<TAB>synonym, synonym`",
+            path.display()
+        ),
+        (Error::SynonymsBadLine { path, line }, Ru) => format!(
+            "Файл синонимов {}, строка {line}: ожидалось `слово<TAB>синоним, синоним`",
+            path.display()
+        ),
+        (Error::InputTooLarge(InputLimit::Bytes(n)), En) => {
+            format!("Input too large: more than {n} bytes (--max-input-bytes)")
+        }
+        (Error::InputTooLarge(InputLimit::Bytes(n)), Ru) => {
+            format!("Слишком большой вход: больше {n} байт (--max-input-bytes)")
+        }
+        (Error::InputTooLarge(InputLimit::Tokens(n)), En) => {
+            format!("Input too large: more than {n} words (--max-tokens)")
+        }
+        (Error::InputTooLarge(InputLimit::Tokens(n)), Ru) => {
+            format!("Слишком большой вход: больше {n} слов (--max-tokens)")
+        }
+        (Error::Timeout(limit), En) => {
+            format!("Timed out: processing took longer than {}s (--timeout)", limit.as_secs_f64())
+        }
+        (Error::Timeout(limit), Ru) => {
+            format!("Превышено время: обработка дольше {} с (--timeout)", limit.as_secs_f64())
+        }
+        (Error::PipelineUnknownStage(name), En) => {
+            format!("Unknown pipeline stage {name:?}; valid stages: {}", Stage::all_names())
+        }
+        (Error::PipelineUnknownStage(name), Ru) => {
+            format!("Неизвестная стадия цепочки {name:?}; допустимые стадии: {}", Stage::all_names())
+        }
+        (Error::PipelineDuplicateStage(stage), En) => format!("Pipeline stage {} is listed twice", stage.name()),
+        (Error::PipelineDuplicateStage(stage), Ru) => format!("Стадия {} указана в цепочке дважды", stage.name()),
+        (Error::PipelineStageOrder { text, token }, En) => format!(
+            "Text stage {} must come before word stage {}: text stages run before tokenization",
+            text.name(),
+            token.name()
+        ),
+        (Error::PipelineStageOrder { text, token }, Ru) => format!(
+            "Текстовая стадия {} должна идти раньше стадии слов {}: текстовые стадии выполняются до токенизации",
+            text.name(),
+            token.name()
+        ),
+        (Error::LangDataBadLine { path, line }, En) => {
+            format!("Language data file {}, line {line}: cannot parse the line", path.display())
+        }
+        (Error::LangDataBadLine { path, line }, Ru) => {
+            format!("Файл данных языка {}, строка {line}: строка не разбирается", path.display())
+        }
+        (Error::LangDataUnknownKey { path, line, key }, En) => {
+            format!("Language data file {}, line {line}: unknown key or section `{key}`", path.display())
+        }
+        (Error::LangDataUnknownKey { path, line, key }, Ru) => {
+            format!("Файл данных языка {}, строка {line}: неизвестный ключ или раздел `{key}`", path.display())
+        }
+        (Error::LangDataBadValue { path, line, key }, En) => format!(
+            "Language data file {}, line {line}: bad value for `{key}` (vowels is a string, digraphs, \
+             silent_endings and syllabic_endings are string arrays, [exceptions] entries are numbers)",
+            path.display()
+        ),
+        (Error::LangDataBadValue { path, line, key }, Ru) => format!(
+            "Файл данных языка {}, строка {line}: неверное значение `{key}` (vowels — строка, digraphs, \
+             silent_endings и syllabic_endings — массивы строк, записи [exceptions] — числа)",
+            path.display()
+        ),
+        (Error::LangDataMissingVowels { path }, En) => format!("Language data file {}: `vowels` is missing", path.display()),
+        (Error::LangDataMissingVowels { path }, Ru) => format!("Файл данных языка {}: не задан `vowels`", path.display()),
+    }
+}
+
+pub fn warning_text(warning: &Warning, ui: UiLang) -> String {
+    use UiLang::{En, Ru};
+    match (warning, ui) {
+        (Warning::DictDuplicates { count }, En) => format!("{count} duplicate dictionary entries were merged"),
+        (Warning::DictDuplicates { count }, Ru) => format!("сведено повторов слов в словаре: {count}"),
+        (Warning::DictMalformedSkipped { count, first }, En) => {
+            format!("skipped {count} malformed dictionary entries (first: {})", join_numbers(first))
+        }
+        (Warning::DictMalformedSkipped { count, first }, Ru) => {
+            format!("пропущено некорректных записей словаря: {count} (первые: {})", join_numbers(first))
+        }
+        (Warning::WeightsClamped { count }, En) => format!("{count} dictionary weights outside [0, 1] were clamped"),
+        (Warning::WeightsClamped { count }, Ru) => {
+            format!("{count} весов словаря вне диапазона [0, 1] приведены к границам")
+        }
+        (Warning::LanguageMismatch { detected, expected }, En) => format!(
+            "the input text appears to be {}, but the dictionary is {}; the score may be meaningless",
+            lang_name(detected, ui),
+            lang_name(expected.code(), ui)
+        ),
+        (Warning::LanguageMismatch { detected, expected }, Ru) => format!(
+            "входной текст, по-видимому, на языке «{}», а словарь — «{}»; оценка может быть бессмысленной",
+            lang_name(detected, ui),
+            lang_name(expected.code(), ui)
+        ),
+        (Warning::StrayClosingQuote { at }, En) => {
+            format!("closing quote without an opening one (byte {at}); further quotes are not excluded")
+        }
+        (Warning::StrayClosingQuote { at }, Ru) => {
+            format!("закрывающая кавычка без открывающей (байт {at}); дальнейшие цитаты не исключаются")
+        }
+        (Warning::UnclosedQuoteParagraph { at }, En) => format!(
+            "unclosed quote (byte {at}) is not continued in the next paragraph; further quotes are not excluded"
+        ),
+        (Warning::UnclosedQuoteParagraph { at }, Ru) => format!(
+            "незакрытая кавычка (байт {at}) не продолжена в следующем абзаце; дальнейшие цитаты не исключаются"
+        ),
+        (Warning::UnclosedQuoteAtEnd { at }, En) => {
+            format!("unclosed quote (byte {at}) up to the end of the text; it is not excluded")
+        }
+        (Warning::UnclosedQuoteAtEnd { at }, Ru) => {
+            format!("незакрытая кавычка (байт {at}) до конца текста; она не исключается")
+        }
+        (Warning::StrayIgnoreEnd { line }, En) => {
+            format!("readability:ignore-end without a matching ignore-start (line {line}); it is ignored")
+        }
+        (Warning::StrayIgnoreEnd { line }, Ru) => {
+            format!("readability:ignore-end без парной ignore-start (строка {line}); директива пропущена")
+        }
+        (Warning::UnclosedIgnoreStart { line }, En) => {
+            format!("readability:ignore-start (line {line}) is never closed; the rest of the text is excluded")
+        }
+        (Warning::UnclosedIgnoreStart { line }, Ru) => {
+            format!("readability:ignore-start (строка {line}) не закрыта; исключён весь текст до конца")
+        }
+        (Warning::MetricUnavailable { metric, reason }, En) => {
+            let reason = match reason {
+                MetricGap::NoSentences => "no sentences found",
+                MetricGap::NoSyllableRules => "there are no syllable rules for the language (see --lang-data)",
+            };
+            format!("metric {} is not computed: {reason}", metric.name())
+        }
+        (Warning::MetricUnavailable { metric, reason }, Ru) => {
+            let reason = match reason {
+                MetricGap::NoSentences => "не найдено ни одного предложения",
+                MetricGap::NoSyllableRules => "для языка нет правил подсчёта слогов (см. --lang-data)",
+            };
+            format!("метрика {} не посчитана: {reason}", metric.name())
+        }
+        (Warning::NoAggregateUnits { by, excluded }, En) => format!(
+            "no {} reaches --min-tokens ({excluded} excluded); the score is the mean over all words",
+            by.name()
+        ),
+        (Warning::NoAggregateUnits { by, excluded }, Ru) => {
+            let unit = match by {
+                AggregateBy::Document => "части",
+                AggregateBy::Paragraph => "абзаца",
+                AggregateBy::Sentence => "предложения",
+            };
+            format!("нет ни одного {unit} не короче --min-tokens (исключено {excluded}); оценка — среднее по всем словам")
+        }
+        (Warning::LongTokensSkipped { count, max }, En) => {
+            format!("{count} words longer than {max} characters skipped (--max-token-len)")
+        }
+        (Warning::LongTokensSkipped { count, max }, Ru) => {
+            format!("пропущено слов длиннее {max} символов: {count} (--max-token-len)")
+        }
+        (Warning::CacheCorrupt { path }, En) => {
+            format!("score cache {} is unreadable; all files will be scored again", path.display())
+        }
+        (Warning::CacheCorrupt { path }, Ru) => {
+            format!("кэш оценок {} не читается; все файлы будут оценены заново", path.display())
+        }
+        (Warning::HistoryBrokenLines { count }, En) => format!("skipped unreadable history lines: {count}"),
+        (Warning::HistoryBrokenLines { count }, Ru) => format!("пропущено нечитаемых строк журнала: {count}"),
+    }
+}
+
+/// Одно звено цепочки причин: ошибки библиотеки берутся из каталога, прочие — как есть
+fn cause_text(err: &(dyn StdError + 'static)) -> String {
+    match err.downcast_ref::<Error>() {
+        Some(e) => error_text(e, ui()),
+        None => err.to_string(),
+    }
+}
+
+/// «Нет слов для оценки» по предупреждениям подготовленного текста: если текст, по-видимому,
+/// на другом языке или слова были, но все пропущены по --max-token-len, об этом говорится
+/// в самом сообщении
+pub fn no_words(warnings: &[Warning]) -> String {
+    let language = warnings.iter().find_map(|w| match w {
+        Warning::LanguageMismatch { detected, expected } => Some(Msg::NoWordsLanguage { detected, expected: *expected }),
+        _ => None,
+    });
+    let long = warnings.iter().find_map(|w| match w {
+        Warning::LongTokensSkipped { count, max } => Some(Msg::NoWordsLongTokens { count: *count, max: *max }),
+        _ => None,
+    });
+    language.or(long).unwrap_or(Msg::NoWords).to_string()
+}
+
+/// Ошибка библиотеки вместе с причинами: `сообщение: причина`
+pub fn describe(err: &Error) -> String {
+    let mut parts = vec![error_text(err, ui())];
+    let mut source = err.source();
+    while let Some(e) = source {
+        parts.push(cause_text(e));
+        source = e.source();
+    }
+    parts.join(": ")
+}
+
+/// Итоговое сообщение об ошибке для STDERR
+pub fn describe_anyhow(err: &anyhow::Error) -> String {
+    err.chain().map(cause_text).collect::<Vec<_>>().join(": ")
+}
+
+/// Перевод справки clap. Русский текст — doc-комментарии аргументов, поэтому каталог
+/// хранит только английский; путь — имена подкоманд через пробел ("" — корень).
+fn help_en(path: &str, id: &str) -> Option<&'static str> {
+    Some(match (path, id) {
+        ("", "dict_path") => "JSON dictionary like [[\"the\", 199660765], ...]; defaults to \
+             word_frequencies.json for en and word_frequencies.<lang>.json for other languages",
+        ("", "lang_data") => "Directory with language data: `<lang>/stopwords.txt` (one word per line, for the \
+             stopwords stage of --pipeline) and `<lang>/syllables.toml` (vowels, digraphs, silent and syllabic \
+             endings, an [exceptions] table; for flesch and fog). A missing file falls back to the built-in \
+             English data for English and to nothing for other languages",
+        ("", "lang") => "Text language: selects the word pattern, lowercasing rules and the default dictionary",
+        ("", "format") => "Output format: plain — just the number, json — an object with the score and metadata",
+        ("", "text_paths") => "Path to a text file or directory to score; may be repeated. \
+             Reads the text from STDIN if omitted. Several paths or a directory enable corpus mode",
+        ("", "no_ignore") => "Do not honor .gitignore and .ignore files when walking directories",
+        ("", "exclude") => "Exclude paths matching a glob when walking directories (e.g. '*.min.js.map'); may be repeated",
+        ("", "jobs") => "Number of threads for corpus scoring (0 — one per core)",
+        ("", "sort") => "Order corpus results by score (ties broken by path)",
+        ("", "top") => "Keep only the first N files after sorting",
+        ("", "bottom") => "Keep only the last N files after sorting",
+        ("", "dedupe_lines") => "In corpus mode, exclude lines repeated across many files \
+             (navigation, footers) from scoring; ignored for a single input",
+        ("", "dedupe_threshold") => "Fraction of files above which a line counts as boilerplate for --dedupe-lines",
+        ("", "summary") => "Corpus summary after per-file results (auto — only for more than one input, \
+             always — even for a single input)",
+        ("", "top_text_words") => "Score only the first N words of the input text (in text order)",
+        ("", "top_text_sentences") => "Score only the words of the first N sentences of the input text",
+        ("", "quantize_weights") => "Keep dictionary weights in memory as BITS-bit fixed point (16 only for now): \
+             about half the memory in daemon mode, the score changes by at most 8·10⁻⁶",
+        ("", "top_dict_entries") | ("dict weight", "top_dict_entries") | ("word", "top_dict_entries") => {
+            "Use only the first K dictionary entries (speed/experiments)"
+        }
+        ("", "dict_duplicates") | ("dict weight", "dict_duplicates") | ("word", "dict_duplicates") => {
+            "What to do with a word listed in the dictionary more than once: \
+             error, first, last, sum (add counts) or max"
+        }
+        ("", "weight") | ("dict weight", "weight") | ("word", "weight") => "How word frequency maps to weight: linear — count / max_count, \
+             log — ln(1 + count) / ln(1 + max_count), rank — (N - rank + 1) / N",
+        ("", "expand_contractions") => "Expand contractions before dictionary lookup (won't → will not, I'm → I am)",
+        ("", "contractions_file") => {
+            "TSV file `form<TAB>expansion` extending and overriding the built-in contractions table"
+        }
+        ("", "keep_acronyms") => "Recognize acronyms (NASA, HTML) and dotted abbreviations (U.S., Ph.D., e.g.) as words",
+        ("", "acronym_weight") => "Weight of an acronym missing from the dictionary (instead of 0 for unknown words)",
+        ("", "skip_quotes") => "Exclude direct speech from scoring: fragments in double quotes (\"…\", “…”, «…»)",
+        ("", "no_directives") => "Do not process `<!-- readability:ignore-start -->` … \
+             `<!-- readability:ignore-end -->` and `<!-- readability:ignore-line -->` (excludes the next line) \
+             directives: score the whole text",
+        ("", "no_sanitize") => "Do not clean the text before tokenization: keep the BOM, zero-width characters, \
+             soft hyphens and non-breaking spaces as is",
+        ("", "min_tokens") => "Minimum number of scored words: below it no score is printed \
+             (exit code 3, in JSON \"score\": null and \"reason\": \"insufficient_tokens\")",
+        ("", "max_tokens") => "Do not score a text longer than N words: tokenization stops as soon as there are more \
+             (unlimited by default, 2 000 000 in daemon mode)",
+        ("", "max_input_bytes") => "Do not read more than N bytes of input: a file's size is checked before reading, \
+             STDIN is checked while reading (unlimited by default, 16 MiB per request in daemon mode)",
+        ("", "max_token_len") => "Skip words longer than N characters (warning W012): such \"words\" are usually base64, \
+             hashes or glued-together junk that no dictionary contains",
+        ("", "timeout") => "Abort preparing and scoring a text that takes longer than SECONDS seconds (fractions allowed). \
+             In a corpus the file gets \"reason\": \"timeout\" (CSV status timeout) and the rest are still scored; in daemon mode the limit applies \
+             to each request",
+        ("", "classify_oov") => "Split words missing from the dictionary into classes — name, probable typo, rare word — \
+             with a weight per class (--oov-weights); the class is shown by --explain, JSON adds \"oov_classes\"",
+        ("", "oov_weights") => "Class weights for --classify-oov: `name=0.8,typo=0.3,rare=0.0` \
+             (classes left out keep these defaults)",
+        ("", "aggregate_by") => "Score the document by parts: first each paragraph (separated by a blank line) \
+             or sentence, then combine the part scores with --aggregate. Parts shorter than --min-tokens \
+             are left out; JSON adds an \"aggregate\" object with every part's score and byte range",
+        ("", "aggregate") => "How to combine part scores of --aggregate-by: mean, median or min (the hardest part)",
+        ("", "bootstrap") => "Bootstrap interval of the score: N resamples of words with replacement (default 1000); \
+             plain output prints `score p2.5 p97.5`",
+        ("", "estimate") => "Approximate score for huge inputs: the input is read once, --sample-sentences random \
+             sentences are kept and the score is printed with its standard error: `0.431200 ±0.004100 (...)`",
+        ("", "sample_sentences") => "Sentence sample size for --estimate; the same --seed reproduces the result",
+        ("", "template") => "Custom plain result line instead of the default one, per input: \
+             `{path}\\t{score:.3}\\t{oov_rate:.1%}`. Fields: path, index (input number, from zero), score, tokens, \
+             oov_tokens, oov_rate, contractions_expanded, quoted_chars_excluded, quoted_tokens_excluded, ci_lo, ci_hi \
+             (--bootstrap), oov_name, oov_typo, oov_rare (--classify-oov); format `.N` sets decimals, `%` prints a \
+             percentage. `{{`, `}}`, `\\t`, `\\n`, `\\\\` are literals",
+        ("", "overlap") => "Overlap of the text's vocabulary with the first K dictionary words: how many distinct \
+             words of the text are among them, how many are in the dictionary further down and how many are out of \
+             vocabulary, plus the words ranked K+1..K+--overlap-delta. May be repeated; plain output prints lines \
+             after the score",
+        ("", "overlap_delta") => "How many ranks past K to scan for --overlap pre-teaching candidates",
+        ("", "auto_thresholds") => "Difficulty label from thresholds derived from the dictionary itself: quintiles \
+             of the scores of built-in reference sentences under the same dictionary and --weight. Plain output \
+             prints a `label<TAB>name` line after the score, JSON gains \"label\" and the bounds in \"thresholds\"; \
+             --verbose prints the bounds",
+        ("", "max_clauses") => "Threshold for --metric clause-density: how many clauses per sentence are still fine. \
+             Sentences above it are counted in JSON (the \"clauses\" object) and listed with --verbose",
+        ("", "no_dehyphenate") => "Do not rejoin words wrapped across lines with a hyphen (`read-` / `ability`). \
+             By default a wrap is joined solid when the solid word is in the dictionary or the second part starts \
+             lowercase, and kept hyphenated when that form is in the dictionary (`twenty-first`)",
+        ("", "pipeline") => "Comma-separated normalization stages, run exactly in this order: directives, sanitize, \
+             dehyphenate, skip-quotes (before tokenization), lowercase, expand-contractions, stopwords (on words; stopwords \
+             only here). Without \
+             --pipeline the stages come from --no-directives, --no-sanitize, --no-dehyphenate, --skip-quotes and \
+             --expand-contractions: directives,sanitize,dehyphenate,skip-quotes,lowercase,expand-contractions minus \
+             the disabled ones",
+        ("", "binary_extensions") => "Comma-separated binary file extensions: in corpus mode such files are skipped \
+             (`skipped: binary`), as are files with a NUL byte in their first 8 KB",
+        ("", "force_text") => "Score every corpus file as text without checking whether it is binary",
+        ("", "seed") => "Random seed for reproducibility (defaults to the current time)",
+        ("", "dict_blend") => "Blend of dictionaries instead of --dict: `general.json:0.7,domain.json:0.3`. \
+             Each dictionary is normalized on its own; a word's weight is the sum of share × weight \
+             (a missing word contributes 0); shares must sum to 1",
+        ("", "self_relative") => "Corpus mode without an external dictionary: the dictionary is built from word \
+             frequencies of all input files (with the same tokenization settings), then each file is scored against it",
+        ("", "self_relative_exclude_self") => "With --self-relative, score each file against the corpus dictionary \
+             without its own words, so that a large file does not make its own vocabulary look common",
+        ("", "save_dict") => "Save the --self-relative dictionary (corpus frequencies) to a file: .csv — CSV, otherwise JSON",
+        ("dict weight", "dict_blend") => "Dictionary blend as in --dict-blend for scoring; \
+             also prints each component's weight for the word",
+        ("", "dict_lenient") => "Skip malformed dictionary entries (with a warning listing the first few) \
+             instead of failing; at least one valid entry is required",
+        ("", "explain_spans") => "Add byte ranges of every occurrence in the input text to each word \
+             of the --explain breakdown in JSON: \"spans\": [[start, end], ...]",
+        ("", "explain") => "Print a per-word breakdown: text frequency, weight and tags, hardest words first",
+        ("", "reproducible") => "Fully deterministic output for hash-based drift checks: listings are ordered, \
+             --jobs results come in input order, --history times come from SOURCE_DATE_EPOCH (without it there is \
+             no \"time\" field), and --bootstrap and --estimate need an explicit --seed",
+        ("", "timings") => "Print stage durations to STDERR (in JSON — a \"timings\" object in the report)",
+        ("", "verbose") => "Print processing details to STDERR",
+        ("", "strict_lang") => "Fail if the text language differs from the dictionary language (instead of warning)",
+        ("", "append_score") => "Print the input text unchanged with a score trailer line appended",
+        ("", "trailer_style") => "Trailer syntax: html — <!-- ... -->, hash — # ..., none-prefix — no wrapping",
+        ("", "output_path") => "Where to write the text with the trailer or the --annotate marks (STDOUT by default)",
+        ("", "annotate") => "Print the input text as is, with the weight after every hard word: `rare[0.0002]`, \
+             `[OOV]` for words missing from the dictionary. Replaces the score; suitable for diffs and review comments",
+        ("", "annotate_threshold") => "Mark words with weight below W (words missing from the dictionary are always marked)",
+        ("", "annotate_precision") => "Digits after the decimal point in --annotate marks",
+        ("", "in_place") => "Replace the input file with the text plus trailer (via a temporary file and rename)",
+        ("", "tertile_report") => "Scores of the first, middle and last third of the scored words and the largest \
+             difference between them. When the word count does not divide by 3 the first thirds get one extra word \
+             each (10 words: 4, 3, 3). The split needs the word count, so it works on the fully tokenized text \
+             (hence not with --estimate); the scores themselves take one pass with three running sums. Plain output prints a `tertiles` line after the score, CSV and corpus mode add \
+             front, middle, back columns, JSON gains a \"tertiles\" object",
+        ("", "suggest") => "Suggest more frequent synonyms for words weighing less than --suggest-threshold: a synonym \
+             is offered when it is in the dictionary and weighs at least twice as much as the word. Plain \
+             output prints `suggest` lines after the score, --explain gains a synonyms column, JSON gains a \
+             \"suggestions\" array; words without a more frequent synonym are omitted",
+        ("", "synonyms") => "Synonyms file for --suggest: TSV `word<TAB>synonym, synonym`. Words and synonyms go through \
+             the same normalization stages as the text, so inflected forms may be used in the file",
+        ("", "suggest_threshold") => "Weight threshold for --suggest: suggestions are looked up for words below it",
+        ("", "suggest_limit") => "How many words with suggestions to print, hardest first",
+        ("", "smooth") => "Smooth the profile with a centered moving average over K windows: the CSV gains a smoothed \
+             column. At the text edges the average uses the neighbours that exist",
+        ("", "flag_anomalies") => "Flag windows whose score differs from the smoothed one by more than Z standard \
+             deviations of the residuals: window number, start, scores and the first words of the window. Plain \
+             output prints `anomaly` lines after the score, JSON gains an \"anomalies\" array",
+        ("", "locale") => "Fold the case of dictionary keys and text words by these rules: default — general Unicode \
+             rules (Straße matches STRASSE, İ → i), tr — Turkish (I → ı, İ → i). Without the flag words are only \
+             lowercased and dictionary keys are used as is",
+        ("", "dict_coverage_target") => "Instead of --top-dict-entries: take the most frequent dictionary entries until \
+             their counts reach fraction F (0 < F ≤ 1) of the total count. The dictionary is read twice: first for the \
+             total, then up to the coverage reached; an unsorted one is held in memory whole and sorted by count. \
+             --verbose prints how many entries were kept and the coverage reached",
+        ("", "with_provenance") => "Add a \"provenance\" object to the JSON report: the input path (or stdin) and the \
+             SHA-256 of its bytes, the SHA-256 of the dictionary files and the entry count after filters, the program \
+             version and the configuration fingerprint (as in --cache-scores keys, but without the dictionary \
+             contents). The input is hashed in the same pass that reads it. In corpus mode every JSON/JSONL record carries \
+             its own \"provenance\" with the hash of that file (records reused by --resume keep theirs); plain and CSV \
+             output are unchanged",
+        ("", "deny_warnings") => "Exit with code 1 if the run produced any warning (except those exempted by --allow)",
+        ("", "allow") => "Do not report warnings with this code (W004 or W004_LANG_MISMATCH) and do not count them \
+             for --deny-warnings; may be repeated or comma-separated",
+        ("", "check_config") => "Only check the invocation: flag combinations, auxiliary files and the start of the \
+             dictionary, without reading any input text. The effective configuration (flag values and where they came \
+             from) is printed as JSON; exit code 0 means OK, 2 means an error",
+        ("", "daemon") => "Daemon mode: load the dictionary once, then read JSON requests from STDIN \
+             one per line ({\"id\": 1, \"text\": \"...\"}) and write responses to STDOUT. {\"cmd\": \"reload\"} \
+             rereads the dictionary in the background and swaps it in without interrupting scoring, \
+             {\"cmd\": \"status\"} reports the dictionary path, word count and load time",
+        ("", "metrics") => "Metric to report; may be repeated (freq — dictionary-frequency score, \
+             flesch — Flesch Reading Ease, fog — Gunning Fog index, clause-density — mean clauses per sentence, \
+             see --max-clauses). With several metrics plain output \
+             prints `name<TAB>value` per metric in the requested order",
+        ("", "profile_out") => "Stream a per-window difficulty profile to a CSV file: \
+             `window_index,start_token,start_char,score,oov_rate`; normal output is unchanged. \
+             start_char is a character offset into the input text, accounting for removed characters and quotes",
+        ("", "export_features") => "Write the scored words one per row in text order: index, word, weight, \
+             out-of-vocabulary flag, sentence index. A `.bin` file is a dense little-endian f32 array (without the \
+             words) described by `<file>.json` next to it; anything else is CSV \
+             `token_index,token,weight,oov,sentence_index`",
+        ("", "no_token_text") => "Leave the words out of the --export-features CSV",
+        ("", "window") => "Profile window size, in words",
+        ("", "window_step") => "Profile window step, in words (defaults to the window size: no overlap)",
+        ("", "watch") => "Rescore the --text file on every change and print the score with its change \
+             since the previous update: `0.431000 (+0.004000)`",
+        ("", "no_typographic_normalization") => "Split sentences without typographic rules: the … character \
+             does not end a sentence, and closing quotes after terminal punctuation and dialogue attribution \
+             after direct speech are not taken into account",
+        ("", "results_out") => "In corpus mode, stream results to a JSON Lines file as they complete",
+        ("", "resume") => "Resume an interrupted run: files already in --results-out and unchanged since \
+             (size and modification time) are not scored again; refused if the dictionary or settings changed",
+        ("", "cache_scores") => "In corpus mode, take scores of unchanged files from a cache keyed by a hash \
+             of the content and parameters (version, dictionary, scoring settings) and add new scores to it",
+        ("", "no_cache_read") => "Do not take scores from the cache, only write them",
+        ("", "no_cache_write") => "Do not add new scores to the cache, only read it",
+        ("", "history") => "Append a record (time, path, score, token count, SHA-256 of the text) to a \
+             JSON Lines file on every scoring run",
+        (_, "lang_ui") => "Language of messages and help: en or ru (defaults to LANG, otherwise en)",
+        ("word-freq", "text_path") => "Path to a text file; reads the text from STDIN if omitted",
+        ("word-freq", "lang") => "Text language (same word pattern and case rules as for scoring)",
+        ("word-freq", "format") => {
+            "json — array [[\"word\", count], ...] loadable via --dict; tsv — lines word<TAB>count"
+        }
+        ("word-freq", "limit") => "Print only the first K words",
+        ("word-freq", "min_count") => "Skip words seen fewer than N times",
+        ("history", "show") => "History file written with --history",
+        ("history", "last") => "How many of the latest records to show",
+        ("dict prune", "dict_path") => "Source dictionary (JSON or .csv)",
+        ("dict prune", "out_path") => "Where to save the result",
+        ("dict prune", "top") => "Keep only the K most frequent entries (after the other filters)",
+        ("dict prune", "min_count") => "Drop entries with frequency below N",
+        ("dict prune", "drop_words") => "File with words to drop, one per line",
+        ("dict prune", "format") => {
+            "Result format; inferred from the --out extension by default (.csv — csv, otherwise json)"
+        }
+        ("dict prune", "force") => "Allow overwriting the source dictionary",
+        ("dict weight", "dict_path") => "Dictionary (JSON or .csv)",
+        ("dict diff", "old_path") => "Old dictionary (JSON or .csv)",
+        ("dict diff", "new_path") => "New dictionary (JSON or .csv)",
+        ("dict diff", "rank_delta") => "A shared word counts as moved if its rank changes by more than N positions",
+        ("dict diff", "limit") => "How many words to list in each section (only in old, only in new, moved)",
+        ("dict diff", "max_removed") => "Exit with code 1 if more than N words are only in the old dictionary",
+        ("dict diff", "max_added") => "Exit with code 1 if more than N words are only in the new dictionary",
+        ("dict diff", "max_moved") => "Exit with code 1 if more than N words moved",
+        ("dict diff", "min_correlation") => "Exit with code 1 if the rank correlation of shared words is below R",
+        ("word", "words") => "Words or phrases; read from STDIN one query per line if omitted",
+        ("word", "dict_path") => "Dictionary (JSON or .csv); defaults to the language dictionary, as for scoring",
+        ("word", "lang") => "Language: word pattern, case rules and default dictionary",
+        ("word", "pipeline") => "Normalization stages, as --pipeline for scoring",
+        ("word", "no_sanitize") => "Do not clean the query before tokenization, as --no-sanitize for scoring",
+        ("word", "expand_contractions") => "Expand contractions, as --expand-contractions for scoring",
+        ("word", "contractions_file") => "Additional contractions table, as for scoring",
+        ("word", "keep_acronyms") => "Recognize acronyms and dotted abbreviations, as --keep-acronyms for scoring",
+        ("word", "suggestions") => "How many nearest dictionary words to suggest for an out-of-vocabulary word",
+        ("word", "format") => "plain — lines `query<TAB>word<TAB>weight<TAB>rank<TAB>count`, json — an array of objects",
+        ("dict weight", "words") => "Words to look up (as is); read from STDIN one per line if omitted",
+        _ => return None,
+    })
+}
+
+fn about_en(path: &str) -> Option<&'static str> {
+    Some(match path {
+        "" => "CLI: estimates how understandable a text is using a word-frequency dictionary from English Wikipedia",
+        "word-freq" => "Word-frequency table of the input text (in a format usable with --dict)",
+        "history" => "Show the latest --history records as a table with score changes",
+        "word" => "Weight, rank and frequency of single words or short phrases after the same normalization \
+             as scoring; nearest dictionary words for out-of-vocabulary ones",
+        "dict" => "Operations on frequency dictionaries",
+        "dict prune" => "Trim a dictionary and save it with the original frequencies",
+        "dict weight" => "Show the final weight, rank and frequency of words after loading the dictionary",
+        "dict diff" => "Compare two dictionaries: words only in one of them, rank shifts, rank correlation",
+        _ => return None,
+    })
+}
+
+/// Подставляет тексты справки на языке интерфейса во всю иерархию подкоманд.
+pub fn localize_command(cmd: clap::Command, ui: UiLang) -> clap::Command {
+    if ui == UiLang::Ru {
+        return cmd;
+    }
+    localize_at(cmd, "")
+}
+
+fn localize_at(mut cmd: clap::Command, path: &str) -> clap::Command {
+    if let Some(about) = about_en(path) {
+        cmd = cmd.about(about);
+    }
+    let ids: Vec<String> = cmd.get_arguments().map(|a| a.get_id().to_string()).collect();
+    for id in ids {
+        if let Some(help) = help_en(path, &id) {
+            // Описания значений (doc-комментарии вариантов) остались бы русскими:
+            // английский текст справки перечисляет значения сам
+            cmd = cmd.mut_arg(id, |a| {
+                let described = a.get_possible_values().iter().any(|v| v.get_help().is_some());
+                a.help(help).hide_possible_values(described)
+            });
+        }
+    }
+    let subcommands: Vec<String> = cmd.get_subcommands().map(|s| s.get_name().to_string()).collect();
+    for name in subcommands {
+        let sub_path = if path.is_empty() { name.clone() } else { format!("{path} {name}") };
+        cmd = cmd.mut_subcommand(name, |s| localize_at(s, &sub_path));
+    }
+    cmd
+}
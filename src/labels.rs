@@ -0,0 +1,120 @@
+//! Метки сложности по порогам из самого словаря (--auto-thresholds): пороги — квинтили оценок
+//! встроенного набора эталонных предложений, посчитанных с тем же словарём и весами, что и текст.
+//! Так «сложный» значит одно и то же при --weight linear, log и rank.
+
+use crate::dict::FrequencyDict;
+use crate::{compute_readability, prepare_text, Lang, ScoreOptions};
+
+const EN_REFERENCE: &str = include_str!("../data/en/reference.txt");
+const DE_REFERENCE: &str = include_str!("../data/de/reference.txt");
+const RU_REFERENCE: &str = include_str!("../data/ru/reference.txt");
+
+/// Метка оценки: чем выше оценка (чаще слова), тем проще текст
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Label {
+    VeryDifficult,
+    Difficult,
+    Medium,
+    Easy,
+    VeryEasy,
+}
+
+impl Label {
+    /// Метки по возрастанию оценки
+    pub const ALL: [Label; 5] = [Label::VeryDifficult, Label::Difficult, Label::Medium, Label::Easy, Label::VeryEasy];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Label::VeryDifficult => "very_difficult",
+            Label::Difficult => "difficult",
+            Label::Medium => "medium",
+            Label::Easy => "easy",
+            Label::VeryEasy => "very_easy",
+        }
+    }
+}
+
+/// Границы между соседними метками по возрастанию: оценка ниже `bounds[0]` — very_difficult,
+/// не ниже `bounds[3]` — very_easy
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Thresholds {
+    pub bounds: [f64; 4],
+    /// Сколько эталонных предложений получили оценку
+    pub sentences: usize,
+}
+
+impl Thresholds {
+    /// Квинтили оценок эталонных предложений `opts.lang`. Предложения проходят те же стадии
+    /// нормализации и веса слов вне словаря, что и текст; --top-text-words, --aggregate-by и
+    /// остальное, что относится к тексту целиком, не применяется. Детерминировано для словаря
+    /// и параметров. None — ни одно предложение не удалось оценить.
+    pub fn derive(dict: &FrequencyDict, opts: &ScoreOptions) -> Option<Thresholds> {
+        let sentence_opts = ScoreOptions {
+            top_text_words: None,
+            top_text_sentences: None,
+            explain: false,
+            explain_spans: false,
+            bootstrap: None,
+            min_tokens: 0,
+            max_input_bytes: None,
+            max_tokens: None,
+            aggregate: None,
+            metrics: Vec::new(),
+            overlap: Vec::new(),
+            thresholds: None,
+            suggest: None,
+            ..opts.clone()
+        };
+        let mut scores: Vec<f64> = reference_sentences(opts.lang)
+            .filter_map(|s| {
+                let prepared = prepare_text(s, Some(dict), &sentence_opts).ok()?;
+                compute_readability(prepared.analyzed_tokens(), dict, &sentence_opts)
+            })
+            .collect();
+        if scores.is_empty() {
+            return None;
+        }
+        scores.sort_by(f64::total_cmp);
+        let bounds = [0.2, 0.4, 0.6, 0.8].map(|q| quantile(&scores, q));
+        Some(Thresholds { bounds, sentences: scores.len() })
+    }
+
+    pub fn label(&self, score: f64) -> Label {
+        Label::ALL[self.bounds.iter().take_while(|&&b| score >= b).count()]
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "source": "auto",
+            "bounds": self.bounds,
+            "labels": Label::ALL.map(Label::name),
+            "reference_sentences": self.sentences,
+        })
+    }
+
+    /// Обратно из `to_json`
+    pub fn from_json(v: &serde_json::Value) -> Option<Thresholds> {
+        let bounds: Vec<f64> = v["bounds"].as_array()?.iter().map(serde_json::Value::as_f64).collect::<Option<_>>()?;
+        Some(Thresholds {
+            bounds: bounds.try_into().ok()?,
+            sentences: v["reference_sentences"].as_u64()? as usize,
+        })
+    }
+}
+
+/// Эталонные предложения языка: строки файла без пустых и комментариев
+fn reference_sentences(lang: Lang) -> impl Iterator<Item = &'static str> {
+    let text = match lang {
+        Lang::En => EN_REFERENCE,
+        Lang::De => DE_REFERENCE,
+        Lang::Ru => RU_REFERENCE,
+    };
+    text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#'))
+}
+
+/// Квантиль `q` отсортированных значений с линейной интерполяцией между соседними
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let pos = q * (sorted.len() - 1) as f64;
+    let (lo, hi) = (pos.floor() as usize, pos.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f64)
+}
@@ -0,0 +1,261 @@
+//! Данные языка из файлов (--lang-data): служебные слова для стадии stopwords и правила
+//! подсчёта слогов для Flesch и Fog. Каталог содержит `<код языка>/stopwords.txt` и
+//! `<код языка>/syllables.toml`; для английского встроены файлы из `data/en`.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::{Error, FileKind, Lang, Result};
+
+const EN_STOPWORDS: &str = include_str!("../data/en/stopwords.txt");
+const EN_SYLLABLES: &str = include_str!("../data/en/syllables.toml");
+
+/// Правила подсчёта слогов. Слова сравниваются в нижнем регистре, как `Token::word`.
+#[derive(Clone, Debug, Default)]
+pub struct SyllableRules {
+    vowels: HashSet<char>,
+    /// Сочетания, входящие в группу гласных целиком; длинные проверяются первыми
+    digraphs: Vec<Vec<char>>,
+    silent_endings: Vec<Vec<char>>,
+    syllabic_endings: Vec<Vec<char>>,
+    exceptions: HashMap<String, usize>,
+}
+
+impl SyllableRules {
+    fn is_vowel(&self, c: char) -> bool {
+        self.vowels.contains(&c)
+    }
+
+    /// Окончание `ending` в конце `chars`, перед которым стоит согласная
+    fn ends_after_consonant(&self, chars: &[char], ending: &[char]) -> bool {
+        chars.len() > ending.len()
+            && chars.ends_with(ending)
+            && !self.is_vowel(chars[chars.len() - ending.len() - 1])
+    }
+
+    /// Слоги слова: слово из таблицы исключений — как в ней; иначе группы гласных (вместе
+    /// с сочетаниями из digraphs) без немого окончания, не меньше одного
+    pub fn count(&self, word: &str) -> usize {
+        if let Some(&n) = self.exceptions.get(word) {
+            return n;
+        }
+        let chars: Vec<char> = word.chars().filter(|c| c.is_alphabetic()).collect();
+        let mut groups = 0usize;
+        let mut prev_vowel = false;
+        let mut i = 0;
+        while i < chars.len() {
+            let (len, vowel) = match self.digraphs.iter().find(|d| chars[i..].starts_with(d)) {
+                Some(d) => (d.len(), true),
+                None => (1, self.is_vowel(chars[i])),
+            };
+            if vowel && !prev_vowel {
+                groups += 1;
+            }
+            prev_vowel = vowel;
+            i += len;
+        }
+        let silent = self.silent_endings.iter().any(|e| self.ends_after_consonant(&chars, e))
+            && !self.syllabic_endings.iter().any(|e| self.ends_after_consonant(&chars, e));
+        if silent {
+            groups = groups.saturating_sub(1);
+        }
+        groups.max(1)
+    }
+}
+
+/// Служебные слова и правила слогов одного языка
+#[derive(Clone, Debug, Default)]
+pub struct LangData {
+    pub stopwords: HashSet<String>,
+    /// None — правил для языка нет, Flesch и Fog не считаются
+    pub syllables: Option<SyllableRules>,
+}
+
+impl LangData {
+    /// Встроенные данные английского
+    pub fn english() -> &'static LangData {
+        static EN: OnceLock<LangData> = OnceLock::new();
+        EN.get_or_init(|| {
+            let builtin = Path::new("data/en");
+            LangData {
+                stopwords: parse_stopwords(EN_STOPWORDS, &builtin.join("stopwords.txt")).expect("встроенный файл"),
+                syllables: Some(parse_syllables(EN_SYLLABLES, &builtin.join("syllables.toml")).expect("встроенный файл")),
+            }
+        })
+    }
+
+    /// Данные `lang` из каталога `dir`. Отсутствующий файл заменяется встроенным для английского
+    /// и пустым (без служебных слов, без правил слогов) для остальных языков.
+    pub fn load(dir: &Path, lang: Lang) -> Result<LangData> {
+        let [stopwords_path, syllables_path] = data_files(dir, lang);
+        let fallback = (lang == Lang::En).then(LangData::english);
+        let stopwords = match read_optional(&stopwords_path)? {
+            Some(text) => parse_stopwords(&text, &stopwords_path)?,
+            None => fallback.map(|d| d.stopwords.clone()).unwrap_or_default(),
+        };
+        let syllables = match read_optional(&syllables_path)? {
+            Some(text) => Some(parse_syllables(&text, &syllables_path)?),
+            None => fallback.and_then(|d| d.syllables.clone()),
+        };
+        Ok(LangData { stopwords, syllables })
+    }
+}
+
+/// Файлы данных `lang` в каталоге `dir`: служебные слова и правила слогов
+pub fn data_files(dir: &Path, lang: Lang) -> [PathBuf; 2] {
+    let sub = dir.join(lang.code());
+    [sub.join("stopwords.txt"), sub.join("syllables.toml")]
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(source) => Err(Error::Io {
+            kind: FileKind::LangData,
+            path: path.to_path_buf(),
+            source,
+        }),
+    }
+}
+
+/// Одно слово в строке; пустые строки и строки с # пропускаются
+fn parse_stopwords(text: &str, path: &Path) -> Result<HashSet<String>> {
+    let mut words = HashSet::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.contains(char::is_whitespace) {
+            return Err(Error::LangDataBadLine { path: path.to_path_buf(), line: i + 1 });
+        }
+        words.insert(line.to_lowercase());
+    }
+    Ok(words)
+}
+
+/// Значение в подмножестве TOML, которое понимает syllables.toml
+enum Value {
+    Str(String),
+    List(Vec<String>),
+    Int(usize),
+}
+
+/// Строка в двойных кавычках с экранированием `\"` и `\\` в начале `s`; остаток после неё
+fn parse_string(s: &str) -> Option<(String, &str)> {
+    let mut out = String::new();
+    let mut chars = s.strip_prefix('"')?.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((out, &s[i + 2..])),
+            '\\' => match chars.next()?.1 {
+                c @ ('"' | '\\') => out.push(c),
+                _ => return None,
+            },
+            c => out.push(c),
+        }
+    }
+    None
+}
+
+/// Остаток строки после значения: только пробелы и комментарий
+fn is_trailer(rest: &str) -> bool {
+    let rest = rest.trim_start();
+    rest.is_empty() || rest.starts_with('#')
+}
+
+fn parse_value(s: &str) -> Option<Value> {
+    if s.starts_with('"') {
+        let (value, rest) = parse_string(s)?;
+        return is_trailer(rest).then_some(Value::Str(value));
+    }
+    if let Some(mut rest) = s.strip_prefix('[') {
+        let mut items = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix(']') {
+                return is_trailer(after).then_some(Value::List(items));
+            }
+            let (item, after) = parse_string(rest)?;
+            items.push(item);
+            rest = after.trim_start();
+            match rest.strip_prefix(',') {
+                Some(after) => rest = after,
+                None if rest.starts_with(']') => {}
+                None => return None,
+            }
+        }
+    }
+    let end = s.find('#').unwrap_or(s.len());
+    s[..end].trim().parse().ok().map(Value::Int)
+}
+
+fn chars_of(items: Vec<String>) -> Vec<Vec<char>> {
+    let mut seqs: Vec<Vec<char>> = items.iter().map(|s| s.to_lowercase().chars().collect()).collect();
+    seqs.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    seqs
+}
+
+/// Разбор syllables.toml: ключи vowels (строка), digraphs, silent_endings, syllabic_endings
+/// (массивы строк) и таблица [exceptions] вида `слово = число`
+fn parse_syllables(text: &str, path: &Path) -> Result<SyllableRules> {
+    let bad_line = |line| Error::LangDataBadLine { path: path.to_path_buf(), line };
+    let mut rules = SyllableRules::default();
+    let mut vowels_seen = false;
+    let mut in_exceptions = false;
+    for (i, raw) in text.lines().enumerate() {
+        let line_no = i + 1;
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[') {
+            let Some(name) = section.split_once(']').filter(|(_, rest)| is_trailer(rest)).map(|(n, _)| n.trim()) else {
+                return Err(bad_line(line_no));
+            };
+            if name != "exceptions" {
+                return Err(Error::LangDataUnknownKey { path: path.to_path_buf(), line: line_no, key: name.to_string() });
+            }
+            in_exceptions = true;
+            continue;
+        }
+        let (key, rest) = if line.starts_with('"') {
+            parse_string(line).ok_or_else(|| bad_line(line_no))?
+        } else {
+            let end = line.find(|c: char| c == '=' || c.is_whitespace()).unwrap_or(line.len());
+            (line[..end].to_string(), &line[end..])
+        };
+        let Some(value) = rest.trim_start().strip_prefix('=') else {
+            return Err(bad_line(line_no));
+        };
+        if key.is_empty() {
+            return Err(bad_line(line_no));
+        }
+        let bad_value = || Error::LangDataBadValue { path: path.to_path_buf(), line: line_no, key: key.clone() };
+        let value = parse_value(value.trim_start()).ok_or_else(bad_value)?;
+        if in_exceptions {
+            let Value::Int(n) = value else {
+                return Err(bad_value());
+            };
+            rules.exceptions.insert(key.to_lowercase(), n);
+            continue;
+        }
+        match (key.as_str(), value) {
+            ("vowels", Value::Str(s)) => {
+                rules.vowels = s.to_lowercase().chars().collect();
+                vowels_seen = true;
+            }
+            ("digraphs", Value::List(items)) => rules.digraphs = chars_of(items),
+            ("silent_endings", Value::List(items)) => rules.silent_endings = chars_of(items),
+            ("syllabic_endings", Value::List(items)) => rules.syllabic_endings = chars_of(items),
+            ("vowels" | "digraphs" | "silent_endings" | "syllabic_endings", _) => return Err(bad_value()),
+            _ => return Err(Error::LangDataUnknownKey { path: path.to_path_buf(), line: line_no, key }),
+        }
+    }
+    if !vowels_seen {
+        return Err(Error::LangDataMissingVowels { path: path.to_path_buf() });
+    }
+    Ok(rules)
+}
@@ -0,0 +1,1084 @@
+//! Оценка «понятности» текста по частотному словарю: токенизация, загрузка словарей и подсчёт.
+//! Библиотека не печатает сообщений: ошибки и предупреждения возвращаются типами из [`error`].
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+
+pub mod aggregate;
+pub mod annotate;
+pub mod bootstrap;
+pub mod casefold;
+pub mod clauses;
+pub mod contractions;
+pub mod deadline;
+pub mod dehyphenate;
+pub mod dict;
+pub mod directives;
+pub mod error;
+pub mod estimate;
+pub mod features;
+pub mod hashing;
+pub mod labels;
+pub mod lang_data;
+pub mod metrics;
+pub mod offsets;
+pub mod overlap;
+pub mod oov;
+pub mod pipeline;
+pub mod quotes;
+pub mod rng;
+pub mod sanitize;
+pub mod suggest;
+pub mod tertiles;
+pub mod timings;
+pub mod windows;
+
+pub use error::{Error, FileKind, InputLimit, Result, Warning, WARNING_CODES};
+use dict::FrequencyDict;
+use hashing::HashingReader;
+use offsets::OffsetMap;
+use pipeline::Stage;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lang {
+    En,
+    De,
+    Ru,
+}
+
+impl Lang {
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::De => "de",
+            Lang::Ru => "ru",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::value_variants().iter().copied().find(|l| l.code() == code)
+    }
+
+    /// Словарь по умолчанию: для английского сохраняем исторический word_frequencies.json
+    pub fn default_dict_path(self) -> PathBuf {
+        match self {
+            Lang::En => PathBuf::from("word_frequencies.json"),
+            other => PathBuf::from(format!("word_frequencies.{}.json", other.code())),
+        }
+    }
+
+    /// Класс букв языка для регулярных выражений
+    pub fn letters(self) -> &'static str {
+        match self {
+            // İ и ı — чтобы турецкие имена (İstanbul) оставались одним словом для --locale tr
+            Lang::En => "A-Za-zİı",
+            // Латиница с умлаутами и ß (включая заглавную ẞ), İ и ı — как в английском
+            Lang::De => "A-Za-zÄÖÜäöüßẞİı",
+            Lang::Ru => "А-Яа-яЁё",
+        }
+    }
+
+    pub fn word_pattern(self) -> String {
+        let l = self.letters();
+        match self {
+            // Слова: последовательности латинских букв; апострофы внутри слов допускаем (can't, I'm)
+            Lang::En => format!("[{l}]+(?:'[{l}]+)?"),
+            Lang::De => format!("[{l}]+"),
+            // Кириллица; дефис внутри слова допускаем (кто-то, из-за)
+            Lang::Ru => format!("[{l}]+(?:-[{l}]+)*"),
+        }
+    }
+
+    pub fn token_regex(self) -> &'static Regex {
+        static EN: OnceLock<Regex> = OnceLock::new();
+        static DE: OnceLock<Regex> = OnceLock::new();
+        static RU: OnceLock<Regex> = OnceLock::new();
+        let cell = match self {
+            Lang::En => &EN,
+            Lang::De => &DE,
+            Lang::Ru => &RU,
+        };
+        cell.get_or_init(|| Regex::new(&self.word_pattern()).unwrap())
+    }
+
+    /// Шаблон слова, перед которым стоит альтернатива для сокращений с точками (U.S., Ph.D., e.g.):
+    /// два и более сегмента из одной-двух букв, каждый с точкой
+    pub fn acronym_token_regex(self) -> &'static Regex {
+        static EN: OnceLock<Regex> = OnceLock::new();
+        static DE: OnceLock<Regex> = OnceLock::new();
+        static RU: OnceLock<Regex> = OnceLock::new();
+        let cell = match self {
+            Lang::En => &EN,
+            Lang::De => &DE,
+            Lang::Ru => &RU,
+        };
+        cell.get_or_init(|| {
+            let l = self.letters();
+            Regex::new(&format!(r"(?:[{l}]{{1,2}}\.){{2,}}|{}", self.word_pattern())).unwrap()
+        })
+    }
+
+    pub fn lowercase(self, word: &str) -> String {
+        match self {
+            Lang::En => word.to_ascii_lowercase(),
+            // Для не-ASCII алфавитов нужен полный Unicode lowercase (Ä → ä, Ж → ж)
+            Lang::De | Lang::Ru => word.to_lowercase(),
+        }
+    }
+}
+
+/// Проверка языка смотрит только на начало текста, чтобы не замедлять работу на больших файлах
+const LANG_DETECT_SAMPLE_BYTES: usize = 4096;
+/// На коротких текстах эвристика ненадёжна — проверку пропускаем
+const LANG_DETECT_MIN_WORDS: usize = 20;
+
+/// Частые служебные слова для различения языков на латинице
+const STOPWORDS_BY_LANG: &[(&str, &[&str])] = &[
+    ("en", &["the", "and", "of", "to", "is", "in", "that", "it", "was", "for", "with", "on", "are", "this"]),
+    ("de", &["der", "die", "das", "und", "ist", "nicht", "ein", "eine", "zu", "mit", "sich", "auf", "den", "ich"]),
+    ("fr", &["le", "la", "les", "et", "est", "une", "des", "du", "que", "pas", "pour", "dans", "qui", "au"]),
+    ("es", &["el", "los", "las", "y", "es", "una", "del", "que", "por", "con", "para", "se", "no", "lo"]),
+];
+
+/// Грубое определение языка по соотношению кириллицы/латиницы и служебным словам.
+/// Возвращает код языка или None, если текст слишком короткий или язык не распознан.
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let mut end = text.len().min(LANG_DETECT_SAMPLE_BYTES);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let sample = &text[..end];
+
+    let words: Vec<String> = sample
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+    if words.len() < LANG_DETECT_MIN_WORDS {
+        return None;
+    }
+
+    let cyrillic = sample.chars().filter(|c| ('\u{0400}'..='\u{04FF}').contains(c)).count();
+    let latin = sample.chars().filter(|c| c.is_ascii_alphabetic()).count();
+    if cyrillic > latin {
+        return Some("ru");
+    }
+
+    let mut best: Option<(&'static str, usize)> = None;
+    for (code, stopwords) in STOPWORDS_BY_LANG {
+        let hits = words.iter().filter(|w| stopwords.contains(&w.as_str())).count();
+        if hits > best.map_or(0, |(_, h)| h) {
+            best = Some((code, hits));
+        }
+    }
+    // Пара случайных совпадений ещё ни о чём не говорит
+    best.filter(|(_, hits)| *hits >= 3).map(|(code, _)| code)
+}
+
+/// Код языка из тех, что различает `detect_language`
+pub(crate) fn known_language(code: &str) -> Option<&'static str> {
+    std::iter::once("ru").chain(STOPWORDS_BY_LANG.iter().map(|(c, _)| *c)).find(|c| *c == code)
+}
+
+/// Предупреждение, если язык текста не похож на язык словаря.
+pub fn language_mismatch(text: &str, lang: Lang) -> Option<Warning> {
+    let detected = detect_language(text)?;
+    if detected == lang.code() {
+        return None;
+    }
+    Some(Warning::LanguageMismatch {
+        detected,
+        expected: lang,
+    })
+}
+
+/// Слово текста после токенизации
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    /// Нормализованная форма для поиска в словаре (в нижнем регистре)
+    pub word: String,
+    /// Аббревиатура или сокращение с точками (только с --keep-acronyms)
+    pub acronym: bool,
+    /// Похоже на имя собственное (только с --classify-oov, см. `oov::mark_proper_names`)
+    pub proper: bool,
+    /// Байтовый диапазон слова в токенизированном тексте; у слов из раскрытого
+    /// сокращения — диапазон самого сокращения
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Token {
+    pub fn word(word: String, start: usize, end: usize) -> Self {
+        Token {
+            word,
+            acronym: false,
+            proper: false,
+            start,
+            end,
+        }
+    }
+}
+
+/// Строка разбора --explain для одного различного слова текста
+#[derive(Clone, Debug)]
+pub struct ExplainEntry {
+    pub word: String,
+    pub count: usize,
+    pub weight: f64,
+    pub in_dict: bool,
+    pub acronym: bool,
+    /// Класс слова вне словаря (только с --classify-oov)
+    pub oov_class: Option<oov::OovClass>,
+    /// Байтовые диапазоны всех вхождений во входном тексте; заполняются только с --explain-spans
+    pub spans: Vec<(usize, usize)>,
+    /// Более частые синонимы с весами (только с --suggest)
+    pub suggestions: Vec<(String, f64)>,
+}
+
+impl ExplainEntry {
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut json = serde_json::json!({
+            "word": self.word,
+            "count": self.count,
+            "weight": self.weight,
+            "in_dict": self.in_dict,
+            "acronym": self.acronym,
+        });
+        if let Some(class) = self.oov_class {
+            json["oov_class"] = class.name().into();
+        }
+        if !self.spans.is_empty() {
+            json["spans"] = serde_json::json!(self.spans);
+        }
+        if !self.suggestions.is_empty() {
+            json["suggestions"] = self
+                .suggestions
+                .iter()
+                .map(|(word, weight)| serde_json::json!({"word": word, "weight": weight}))
+                .collect();
+        }
+        json
+    }
+
+    /// Обратно из `to_json`
+    pub fn from_json(v: &serde_json::Value) -> Option<ExplainEntry> {
+        let oov_class = match v.get("oov_class") {
+            Some(name) => Some(oov::OovClass::from_name(name.as_str()?)?),
+            None => None,
+        };
+        let spans = match v.get("spans") {
+            Some(spans) => spans
+                .as_array()?
+                .iter()
+                .map(|s| Some((s[0].as_u64()? as usize, s[1].as_u64()? as usize)))
+                .collect::<Option<_>>()?,
+            None => Vec::new(),
+        };
+        let suggestions = match v.get("suggestions") {
+            Some(s) => suggest::weighted_words_from_json(s)?,
+            None => Vec::new(),
+        };
+        Some(ExplainEntry {
+            word: v["word"].as_str()?.to_string(),
+            count: v["count"].as_u64()? as usize,
+            weight: v["weight"].as_f64()?,
+            in_dict: v["in_dict"].as_bool()?,
+            acronym: v["acronym"].as_bool()?,
+            oov_class,
+            spans,
+            suggestions,
+        })
+    }
+
+    pub fn tags(&self) -> Vec<&'static str> {
+        let mut tags = Vec::new();
+        if !self.in_dict {
+            tags.push("oov");
+        }
+        if let Some(class) = self.oov_class {
+            tags.push(class.name());
+        }
+        if self.acronym {
+            tags.push("acronym");
+        }
+        tags
+    }
+}
+
+/// Параметры оценки, общие для всех режимов (один текст, корпус, демон)
+#[derive(Clone, Debug)]
+pub struct ScoreOptions {
+    pub lang: Lang,
+    pub top_text_words: Option<usize>,
+    /// Оценивать только слова первых N предложений (--top-text-sentences)
+    pub top_text_sentences: Option<usize>,
+    /// Таблица для стадии expand-contractions
+    pub contractions: Option<Arc<contractions::Contractions>>,
+    /// Служебные слова и правила слогов из --lang-data; None — встроенные (есть только для английского)
+    pub lang_data: Option<Arc<lang_data::LangData>>,
+    pub keep_acronyms: bool,
+    pub acronym_weight: f64,
+    /// Стадии нормализации и их порядок (--pipeline или флаги --no-sanitize, --skip-quotes,
+    /// --expand-contractions)
+    pub pipeline: pipeline::TokenPipeline,
+    /// Учитывать типографику при разбиении на предложения: многоточие …, кавычки после знака
+    /// конца и прямую речь (отключается --no-typographic-normalization)
+    pub typographic: bool,
+    pub explain: bool,
+    /// Добавлять к разбору --explain байтовые диапазоны вхождений слов (--explain-spans)
+    pub explain_spans: bool,
+    pub bootstrap: Option<bootstrap::BootstrapOptions>,
+    pub min_tokens: usize,
+    /// Ограничения размера входа: байт при чтении и слов при токенизации; None — без ограничения
+    pub max_input_bytes: Option<u64>,
+    pub max_tokens: Option<usize>,
+    /// Слова длиннее стольких символов пропускаются с предупреждением (--max-token-len)
+    pub max_token_len: usize,
+    /// Срок на подготовку и оценку одного текста (--timeout); None — без ограничения
+    pub timeout: Option<Duration>,
+    /// Оценка по абзацам или предложениям (--aggregate-by); None — по всем словам сразу
+    pub aggregate: Option<aggregate::AggregateOptions>,
+    /// Веса слов вне словаря по классам (--classify-oov, --oov-weights); None — все они весят 0
+    pub oov_weights: Option<oov::OovWeights>,
+    /// Метрики для отчёта (--metric), в порядке запроса; пусто — только частотная оценка
+    pub metrics: Vec<metrics::Metric>,
+    /// Порог клауз в предложении для clause-density: предложения сверх него считаются отдельно
+    pub max_clauses: usize,
+    /// Пересечение с первыми K словами словаря (--overlap) для каждого K; словарь нужен с рангами
+    pub overlap: Vec<usize>,
+    /// Сколько рангов за K просматривать в поисках слов-кандидатов (--overlap-delta)
+    pub overlap_delta: usize,
+    /// Пороги меток сложности (--auto-thresholds); None — оценка без метки
+    pub thresholds: Option<labels::Thresholds>,
+    /// Оценивать отдельно первую, среднюю и последнюю треть слов (--tertile-report)
+    pub tertiles: bool,
+    /// Правила свёртки регистра на стадии lowercase (--locale); те же, что у ключей словаря.
+    /// None — только нижний регистр (`Lang::lowercase`)
+    pub locale: Option<casefold::Locale>,
+    /// Подсказки синонимов для редких слов (--suggest)
+    pub suggest: Option<suggest::SuggestOptions>,
+}
+
+/// Значения по умолчанию — как у командной строки без флагов (английский язык)
+impl Default for ScoreOptions {
+    fn default() -> Self {
+        ScoreOptions {
+            lang: Lang::En,
+            top_text_words: None,
+            top_text_sentences: None,
+            contractions: None,
+            lang_data: None,
+            keep_acronyms: false,
+            acronym_weight: 1.0,
+            pipeline: pipeline::TokenPipeline::default(),
+            typographic: true,
+            explain: false,
+            explain_spans: false,
+            bootstrap: None,
+            min_tokens: 0,
+            max_input_bytes: None,
+            max_tokens: None,
+            max_token_len: 100,
+            timeout: None,
+            aggregate: None,
+            oov_weights: None,
+            metrics: Vec::new(),
+            max_clauses: 3,
+            overlap: Vec::new(),
+            overlap_delta: 500,
+            thresholds: None,
+            tertiles: false,
+            locale: None,
+            suggest: None,
+        }
+    }
+}
+
+impl ScoreOptions {
+    /// Слишком мало слов для осмысленной оценки (--min-tokens); ровно N слов — достаточно
+    pub fn insufficient(&self, tokens: usize) -> bool {
+        tokens < self.min_tokens
+    }
+
+    /// Данные языка: загруженные из --lang-data, иначе встроенные для английского
+    pub fn language_data(&self) -> Option<&lang_data::LangData> {
+        match &self.lang_data {
+            Some(data) => Some(data),
+            None => (self.lang == Lang::En).then(lang_data::LangData::english),
+        }
+    }
+}
+
+/// Отчёт без оценки для текста, в котором меньше --min-tokens слов
+pub fn insufficient_json(tokens: usize, lang: Lang) -> serde_json::Value {
+    serde_json::json!({
+        "score": null,
+        "tokens": tokens,
+        "lang": lang.code(),
+        "reason": "insufficient_tokens",
+    })
+}
+
+/// Результат оценки одного текста
+#[derive(Clone, Debug)]
+pub struct ScoreReport {
+    pub score: f64,
+    /// Сколько слов реально участвовало в оценке (после --top-text-words и --top-text-sentences)
+    pub tokens: usize,
+    /// Сколько из них вне словаря
+    pub oov_tokens: usize,
+    /// Сколько сокращений раскрыто (--expand-contractions)
+    pub contractions_expanded: usize,
+    /// Сколько символов и слов исключено как прямая речь (--skip-quotes)
+    pub quoted_chars_excluded: usize,
+    pub quoted_tokens_excluded: usize,
+    /// Сколько слов исключено директивами readability:ignore
+    pub ignored_tokens_excluded: usize,
+    /// Сколько перенесённых через строку слов склеено
+    pub dehyphenated: usize,
+    /// Разбор по словам; заполняется только с --explain
+    pub explain: Vec<ExplainEntry>,
+    pub bootstrap: Option<bootstrap::BootstrapResult>,
+    /// Оценки частей текста при --aggregate-by; `score` тогда — их агрегат
+    pub aggregate: Option<Box<aggregate::AggregateResult>>,
+    /// Слова вне словаря по классам (с --classify-oov)
+    pub oov_classes: Option<oov::OovCounts>,
+    /// Клаузы и глубина вставок по предложениям (с --metric clause-density)
+    pub clauses: Option<clauses::ClauseReport>,
+    /// Пересечение с первыми K словами словаря, по одному на --overlap
+    pub overlap: Vec<overlap::Overlap>,
+    /// Пороги, по которым выводится метка оценки (--auto-thresholds)
+    pub thresholds: Option<labels::Thresholds>,
+    /// Оценки третей текста (с --tertile-report)
+    pub tertiles: Option<tertiles::Tertiles>,
+    /// Более частые синонимы редких слов (с --suggest)
+    pub suggestions: Vec<suggest::Suggestion>,
+    /// Значения метрик --metric в порядке запроса; None — метрику посчитать не удалось
+    pub metrics: Vec<(metrics::Metric, Option<f64>)>,
+    /// Предупреждения, возникшие при обработке текста
+    pub warnings: Vec<Warning>,
+    /// Длительность этапов; чтение текста заполняет вызывающий код
+    pub timings: timings::TextTimings,
+}
+
+impl ScoreReport {
+    /// Метка оценки по порогам --auto-thresholds
+    pub fn label(&self) -> Option<labels::Label> {
+        self.thresholds.map(|t| t.label(self.score))
+    }
+
+    /// JSON-отчёт без предупреждений: их текст (на языке интерфейса) добавляет вызывающий код
+    pub fn to_json(&self, lang: Lang) -> serde_json::Value {
+        let mut json = serde_json::json!({
+            "score": self.score,
+            "tokens": self.tokens,
+            "lang": lang.code(),
+        });
+        if let Some(t) = &self.thresholds {
+            json["label"] = t.label(self.score).name().into();
+            json["thresholds"] = t.to_json();
+        }
+        if !self.explain.is_empty() {
+            json["explain"] = self.explain.iter().map(ExplainEntry::to_json).collect();
+        }
+        if let Some(b) = self.bootstrap {
+            json["bootstrap"] = b.to_json();
+        }
+        if let Some(a) = &self.aggregate {
+            json["aggregate"] = a.to_json();
+        }
+        if let Some(c) = self.oov_classes {
+            json["oov_classes"] = c.to_json();
+        }
+        if let Some(c) = &self.clauses {
+            json["clauses"] = c.to_json();
+        }
+        if let Some(t) = &self.tertiles {
+            json["tertiles"] = t.to_json();
+        }
+        if !self.suggestions.is_empty() {
+            json["suggestions"] = self.suggestions.iter().map(suggest::Suggestion::to_json).collect();
+        }
+        if !self.overlap.is_empty() {
+            json["overlap"] = self.overlap.iter().map(overlap::Overlap::to_json).collect();
+        }
+        if !self.metrics.is_empty() {
+            let metrics: serde_json::Map<String, serde_json::Value> = self
+                .metrics
+                .iter()
+                .map(|(m, v)| (m.name().to_string(), serde_json::json!(v)))
+                .collect();
+            json["metrics"] = serde_json::Value::Object(metrics);
+        }
+        json
+    }
+
+    /// Полная запись отчёта для --cache-scores и --results-out: `to_json` вместе со счётчиками,
+    /// предупреждениями (кодом и полями) и метриками в порядке запроса. `from_record` восстанавливает
+    /// по ней всё, кроме длительностей этапов, так что вывод из записи не отличается от свежего.
+    pub fn to_record(&self, lang: Lang) -> serde_json::Value {
+        let mut json = self.to_json(lang);
+        json["oov_tokens"] = self.oov_tokens.into();
+        json["contractions_expanded"] = self.contractions_expanded.into();
+        json["quoted_chars_excluded"] = self.quoted_chars_excluded.into();
+        json["quoted_tokens_excluded"] = self.quoted_tokens_excluded.into();
+        json["ignored_tokens_excluded"] = self.ignored_tokens_excluded.into();
+        json["dehyphenated"] = self.dehyphenated.into();
+        json["metrics"] = self.metrics.iter().map(|(m, v)| serde_json::json!([m.name(), v])).collect();
+        json["warnings"] = self
+            .warnings
+            .iter()
+            .map(|w| serde_json::json!({ "code": w.code(), "context": w.context() }))
+            .collect();
+        json
+    }
+
+    /// Обратно из `to_record`; None — запись неполная или другого формата
+    pub fn from_record(v: &serde_json::Value) -> Option<ScoreReport> {
+        fn list<T>(v: &serde_json::Value, parse: impl Fn(&serde_json::Value) -> Option<T>) -> Option<Vec<T>> {
+            match v {
+                serde_json::Value::Null => Some(Vec::new()),
+                v => v.as_array()?.iter().map(parse).collect(),
+            }
+        }
+        fn optional<T>(v: &serde_json::Value, parse: impl Fn(&serde_json::Value) -> Option<T>) -> Option<Option<T>> {
+            match v {
+                serde_json::Value::Null => Some(None),
+                v => parse(v).map(Some),
+            }
+        }
+        let count = |key: &str| v[key].as_u64().map(|n| n as usize);
+        let metrics = list(&v["metrics"], |m| Some((metrics::Metric::from_name(m[0].as_str()?)?, m[1].as_f64())))?;
+        let warnings = list(&v["warnings"], |w| Warning::from_json(w["code"].as_str()?, &w["context"]))?;
+        Some(ScoreReport {
+            score: v["score"].as_f64()?,
+            tokens: count("tokens")?,
+            oov_tokens: count("oov_tokens")?,
+            contractions_expanded: count("contractions_expanded")?,
+            quoted_chars_excluded: count("quoted_chars_excluded")?,
+            quoted_tokens_excluded: count("quoted_tokens_excluded")?,
+            ignored_tokens_excluded: count("ignored_tokens_excluded")?,
+            dehyphenated: count("dehyphenated")?,
+            explain: list(&v["explain"], ExplainEntry::from_json)?,
+            bootstrap: optional(&v["bootstrap"], bootstrap::BootstrapResult::from_json)?,
+            aggregate: optional(&v["aggregate"], aggregate::AggregateResult::from_json)?.map(Box::new),
+            oov_classes: optional(&v["oov_classes"], oov::OovCounts::from_json)?,
+            clauses: optional(&v["clauses"], clauses::ClauseReport::from_json)?,
+            overlap: list(&v["overlap"], overlap::Overlap::from_json)?,
+            thresholds: optional(&v["thresholds"], labels::Thresholds::from_json)?,
+            tertiles: optional(&v["tertiles"], tertiles::Tertiles::from_json)?,
+            suggestions: list(&v["suggestions"], suggest::Suggestion::from_json)?,
+            metrics,
+            warnings,
+            timings: Default::default(),
+        })
+    }
+
+    /// Разбор --explain в plain-режиме: `слово<TAB>вхождений<TAB>вес<TAB>пометки`, с --suggest —
+    /// и `<TAB>синоним (вес), ...` у слов с подсказками
+    pub fn write_explain(&self, out: &mut impl Write) -> io::Result<()> {
+        for e in &self.explain {
+            write!(out, "{}\t{}\t{:.6}\t{}", e.word, e.count, e.weight, e.tags().join(","))?;
+            if !e.suggestions.is_empty() {
+                let alternatives: Vec<String> = e.suggestions.iter().map(|(w, x)| format!("{w} ({x:.6})")).collect();
+                write!(out, "\t{}", alternatives.join(", "))?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn read_input_text(path: Option<&Path>) -> Result<String> {
+    read_input_limited(path, None)
+}
+
+/// Читает текст, но не больше `max_bytes` байт: размер файла проверяется по метаданным до чтения,
+/// а у потоков (STDIN, каналы) чтение прекращается на первом лишнем байте.
+pub fn read_input_limited(path: Option<&Path>, max_bytes: Option<u64>) -> Result<String> {
+    read_input(path, max_bytes, None)
+}
+
+/// Как `read_input_limited`, но вместе с SHA-256 прочитанных байт, посчитанным тем же проходом
+pub fn read_input_hashed(path: Option<&Path>, max_bytes: Option<u64>) -> Result<(String, String)> {
+    let mut hasher = Sha256::new();
+    let text = read_input(path, max_bytes, Some(&mut hasher))?;
+    Ok((text, hashing::hex(hasher)))
+}
+
+fn read_input(path: Option<&Path>, max_bytes: Option<u64>, hasher: Option<&mut Sha256>) -> Result<String> {
+    let too_large = |max| Error::InputTooLarge(InputLimit::Bytes(max));
+    let read = match path {
+        Some(p) => {
+            let io_err = |source| Error::Io {
+                kind: FileKind::Text,
+                path: p.to_path_buf(),
+                source,
+            };
+            let f = File::open(p).map_err(io_err)?;
+            if let Some(max) = max_bytes {
+                if f.metadata().map_err(io_err)?.len() > max {
+                    return Err(too_large(max));
+                }
+            }
+            read_limited(HashingReader::new(f, hasher), max_bytes).map_err(io_err)?
+        }
+        None => read_limited(HashingReader::new(io::stdin().lock(), hasher), max_bytes).map_err(Error::Stdin)?,
+    };
+    read.ok_or_else(|| too_large(max_bytes.unwrap_or_default()))
+}
+
+/// Весь поток как UTF-8, если в нём не больше `max_bytes` байт; иначе None, прочитав max_bytes + 1
+pub fn read_limited(reader: impl Read, max_bytes: Option<u64>) -> io::Result<Option<String>> {
+    let mut buf = Vec::new();
+    match max_bytes {
+        Some(max) => {
+            reader.take(max.saturating_add(1)).read_to_end(&mut buf)?;
+            if buf.len() as u64 > max {
+                return Ok(None);
+            }
+        }
+        None => {
+            let mut reader = reader;
+            reader.read_to_end(&mut buf)?;
+        }
+    }
+    String::from_utf8(buf)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn tokenize(text: &str, lang: Lang, keep_acronyms: bool) -> Vec<Token> {
+    tokens(text, lang, keep_acronyms).collect()
+}
+
+/// Слова текста по одному, без накопления: вызывающий код может остановиться в любой момент
+pub fn tokens(text: &str, lang: Lang, keep_acronyms: bool) -> Box<dyn Iterator<Item = Token> + '_> {
+    cased_tokens(text, lang, keep_acronyms, true)
+}
+
+/// Как `tokens`, но с `lowercase == false` слова остаются в том регистре, в каком написаны
+/// (стадию lowercase тогда применяет `prepare_text`)
+fn cased_tokens(text: &str, lang: Lang, keep_acronyms: bool, lowercase: bool) -> Box<dyn Iterator<Item = Token> + '_> {
+    let normalize = move |raw: &str| if lowercase { lang.lowercase(raw) } else { raw.to_string() };
+    if !keep_acronyms {
+        return Box::new(
+            lang.token_regex()
+                .find_iter(text)
+                .map(move |m| Token::word(normalize(m.as_str()), m.start(), m.end())),
+        );
+    }
+    Box::new(lang.acronym_token_regex().find_iter(text).map(move |m| {
+        let raw = m.as_str();
+        let letters = raw.chars().filter(|c| c.is_alphabetic()).count();
+        let acronym = raw.ends_with('.')
+            || ((2..=6).contains(&letters) && raw.chars().all(char::is_uppercase));
+        Token {
+            word: normalize(raw),
+            acronym,
+            proper: false,
+            start: m.start(),
+            end: m.end(),
+        }
+    }))
+}
+
+pub fn tokenize_words(text: &str, lang: Lang) -> Vec<String> {
+    // Шаблон слова зависит от языка; всё приводим к нижнему регистру
+    lang.token_regex()
+        .find_iter(text)
+        .map(|m| lang.lowercase(m.as_str()))
+        .collect()
+}
+
+/// Слова текста вместе с их байтовыми диапазонами `(начало, конец, слово)`. Диапазон относится
+/// к исходной записи слова в `text`, даже если в нижнем регистре оно другой длины.
+pub fn tokenize_with_spans(text: &str, lang: Lang) -> Vec<(usize, usize, String)> {
+    lang.token_regex()
+        .find_iter(text)
+        .map(|m| (m.start(), m.end(), lang.lowercase(m.as_str())))
+        .collect()
+}
+
+/// Вес слова: из словаря; для неизвестных аббревиатур — --acronym-weight, для прочих неизвестных —
+/// вес их класса при --classify-oov, иначе 0.
+pub fn token_weight(token: &Token, dict: &FrequencyDict, opts: &ScoreOptions) -> f64 {
+    match dict.weight(&token.word) {
+        Some(w) => w,
+        None if token.acronym => opts.acronym_weight,
+        None => opts
+            .oov_weights
+            .map_or(0.0, |w| w.weight(oov::classify(token, dict, opts.lang))),
+    }
+}
+
+/// Класс слова вне словаря, если классы включены (--classify-oov); аббревиатуры не классифицируются
+fn oov_class(token: &Token, dict: &FrequencyDict, opts: &ScoreOptions) -> Option<oov::OovClass> {
+    let classify = opts.oov_weights.is_some() && !token.acronym && !dict.contains(&token.word);
+    classify.then(|| oov::classify(token, dict, opts.lang))
+}
+
+pub fn compute_readability(
+    tokens: &[Token],
+    dict: &FrequencyDict,
+    opts: &ScoreOptions,
+) -> Option<f64> {
+    let iter = tokens.iter();
+    let iter = if let Some(n) = opts.top_text_words {
+        Box::new(iter.take(n)) as Box<dyn Iterator<Item = &Token>>
+    } else {
+        Box::new(iter) as Box<dyn Iterator<Item = &Token>>
+    };
+
+    let mut sum = 0.0f64;
+    let mut cnt = 0usize;
+
+    for t in iter {
+        let wgt = token_weight(t, dict, opts);
+        debug_assert!((0.0..=1.0).contains(&wgt), "вес вне [0, 1]: {wgt} для {:?}", t.word);
+        sum += wgt;
+        cnt += 1;
+    }
+
+    if cnt == 0 { None } else { Some(sum / (cnt as f64)) }
+}
+
+/// Разбор по различным словам оцениваемой части текста, от самых трудных (малый вес) к простым.
+pub fn explain_tokens(tokens: &[Token], dict: &FrequencyDict, opts: &ScoreOptions) -> Vec<ExplainEntry> {
+    let mut by_word: HashMap<&str, ExplainEntry> = HashMap::new();
+    for t in tokens {
+        by_word
+            .entry(t.word.as_str())
+            .and_modify(|e| e.count += 1)
+            .or_insert_with(|| ExplainEntry {
+                word: t.word.clone(),
+                count: 1,
+                weight: token_weight(t, dict, opts),
+                in_dict: dict.contains(&t.word),
+                acronym: t.acronym,
+                oov_class: oov_class(t, dict, opts),
+                spans: Vec::new(),
+                suggestions: Vec::new(),
+            });
+    }
+    let mut entries: Vec<ExplainEntry> = by_word.into_values().collect();
+    entries.sort_by(|a, b| a.weight.total_cmp(&b.weight).then_with(|| a.word.cmp(&b.word)));
+    entries
+}
+
+/// Текст после предобработки вместе со словами: общая основа оценки и профиля по окнам
+#[derive(Clone, Debug)]
+pub struct PreparedText<'a> {
+    /// Входной текст до какой-либо обработки
+    pub input: &'a str,
+    /// Токенизированный текст (очищенный, при --skip-quotes — без цитат); к нему относятся `Token::start`
+    pub text: Cow<'a, str>,
+    /// Этапы обработки, менявшие смещения, в порядке применения
+    pub offsets: Vec<OffsetMap>,
+    pub tokens: Vec<Token>,
+    /// Сколько первых слов участвуют в оценке (после --top-text-words и --top-text-sentences)
+    pub analyzed: usize,
+    pub contractions_expanded: usize,
+    pub quoted_chars_excluded: usize,
+    pub quoted_tokens_excluded: usize,
+    pub ignored_tokens_excluded: usize,
+    pub dehyphenated: usize,
+    pub warnings: Vec<Warning>,
+    pub tokenize_time: Duration,
+    /// Срок --timeout, отсчитанный от начала подготовки; оценка проверяет тот же срок
+    pub deadline: Option<deadline::Deadline>,
+}
+
+impl PreparedText<'_> {
+    /// Слова, участвующие в оценке
+    pub fn analyzed_tokens(&self) -> &[Token] {
+        &self.tokens[..self.analyzed]
+    }
+
+    /// Смещение в символах обработанного текста → смещение во входном тексте
+    pub fn original_char(&self, processed: usize) -> usize {
+        self.offsets.iter().rev().fold(processed, |pos, map| map.original(pos))
+    }
+
+    /// Байтовые диапазоны слов во входном тексте. Конец переводится по последнему символу слова,
+    /// чтобы убранные сразу за словом символы не попадали в диапазон.
+    pub fn input_spans(&self, tokens: &[Token]) -> Vec<(usize, usize)> {
+        // Неразрывный пробел меняет длину в байтах, не меняя смещений в символах:
+        // совпадают диапазоны, только если текст не менялся вовсе
+        if self.text == self.input {
+            return tokens.iter().map(|t| (t.start, t.end)).collect();
+        }
+        // Начала символов в байтах, с концом текста в конце: символ → байт по индексу,
+        // байт → символ двоичным поиском
+        let char_starts = |s: &str| -> Vec<usize> {
+            s.char_indices().map(|(i, _)| i).chain(std::iter::once(s.len())).collect()
+        };
+        let processed = char_starts(&self.text);
+        let input = char_starts(self.input);
+        let to_char = |byte: usize| processed.partition_point(|&b| b < byte);
+        tokens
+            .iter()
+            .map(|t| {
+                let start = self.original_char(to_char(t.start));
+                let end = self.original_char(to_char(t.end) - 1) + 1;
+                (input[start], input[end])
+            })
+            .collect()
+    }
+}
+
+/// Предобработка и токенизация текста по стадиям `opts.pipeline` в их порядке. Токенизация прерывается
+/// с `Error::InputTooLarge`, как только слов становится больше `opts.max_tokens`, и с `Error::Timeout`,
+/// как только истекает `opts.timeout`. Слова длиннее `opts.max_token_len` символов пропускаются.
+pub fn prepare_text<'a>(input: &'a str, dict: Option<&FrequencyDict>, opts: &ScoreOptions) -> Result<PreparedText<'a>> {
+    let text = input;
+    let started = Instant::now();
+    let deadline = deadline::Deadline::start(opts.timeout);
+    let mut warnings = Vec::new();
+    let mut quoted_chars_excluded = 0;
+    let mut quoted_tokens_excluded = 0;
+    let mut ignored_tokens_excluded = 0;
+    let mut dehyphenated = 0;
+    let mut offsets = Vec::new();
+    let mut text: Cow<'a, str> = Cow::Borrowed(text);
+    let stages = opts.pipeline.stages();
+    let text_stages = stages.iter().take_while(|s| s.is_text()).count();
+    for stage in &stages[..text_stages] {
+        text = match stage {
+            Stage::Directives => {
+                let stripped = directives::strip_directives(&text);
+                for span in &stripped.removed {
+                    ignored_tokens_excluded += tokenize(span, opts.lang, opts.keep_acronyms).len();
+                }
+                warnings.extend(stripped.warnings);
+                offsets.push(stripped.offsets);
+                Cow::Owned(stripped.text)
+            }
+            Stage::Sanitize => {
+                let (clean, map) = sanitize::sanitize(&text);
+                let clean = match clean {
+                    Cow::Owned(s) => Some(s),
+                    Cow::Borrowed(_) => None,
+                };
+                offsets.push(map);
+                clean.map_or(text, Cow::Owned)
+            }
+            Stage::Dehyphenate => {
+                let (joined, map, count) = dehyphenate::dehyphenate(&text, opts.lang, dict);
+                let joined = match joined {
+                    Cow::Owned(s) => Some(s),
+                    Cow::Borrowed(_) => None,
+                };
+                dehyphenated += count;
+                offsets.push(map);
+                joined.map_or(text, Cow::Owned)
+            }
+            Stage::SkipQuotes => {
+                let stripped = quotes::strip_quotes(&text);
+                for span in &stripped.removed {
+                    quoted_chars_excluded += span.chars().count();
+                    quoted_tokens_excluded += tokenize(span, opts.lang, opts.keep_acronyms).len();
+                }
+                warnings.extend(stripped.warning);
+                offsets.push(stripped.offsets);
+                Cow::Owned(stripped.text)
+            }
+            Stage::Lowercase | Stage::ExpandContractions | Stage::Stopwords => unreachable!("стадии слов идут после текстовых"),
+        };
+        deadline::check(deadline)?;
+    }
+
+    let mut tokens = Vec::new();
+    let mut long_tokens = 0;
+    for (i, t) in cased_tokens(&text, opts.lang, opts.keep_acronyms, false).enumerate() {
+        if (i + 1) % deadline::CHECK_EVERY == 0 {
+            deadline::check(deadline)?;
+        }
+        // Длина в байтах не меньше длины в символах: короткие слова не пересчитываются
+        if t.word.len() > opts.max_token_len && t.word.chars().count() > opts.max_token_len {
+            long_tokens += 1;
+            continue;
+        }
+        if let Some(max) = opts.max_tokens.filter(|&max| tokens.len() == max) {
+            return Err(Error::InputTooLarge(InputLimit::Tokens(max)));
+        }
+        tokens.push(t);
+    }
+    if long_tokens > 0 {
+        warnings.push(Warning::LongTokensSkipped { count: long_tokens, max: opts.max_token_len });
+    }
+    deadline::check(deadline)?;
+    let mut contractions_expanded = 0;
+    for stage in &stages[text_stages..] {
+        match stage {
+            Stage::Lowercase => {
+                for t in &mut tokens {
+                    match opts.locale {
+                        Some(locale) => {
+                            if let Cow::Owned(folded) = casefold::fold(&t.word, locale) {
+                                t.word = folded;
+                            }
+                        }
+                        None => t.word = opts.lang.lowercase(&t.word),
+                    }
+                }
+            }
+            Stage::Stopwords => {
+                if let Some(data) = opts.language_data() {
+                    tokens.retain(|t| !data.stopwords.contains(&t.word));
+                }
+            }
+            Stage::ExpandContractions => {
+                if let Some(table) = &opts.contractions {
+                    let expanded;
+                    (tokens, expanded) = table.expand(tokens);
+                    contractions_expanded += expanded;
+                }
+            }
+            Stage::Directives | Stage::Sanitize | Stage::Dehyphenate | Stage::SkipQuotes => unreachable!("текстовые стадии проверены в TokenPipeline::new"),
+        }
+        deadline::check(deadline)?;
+    }
+    if opts.oov_weights.is_some() {
+        oov::mark_proper_names(&text, &mut tokens, opts.typographic);
+    }
+    let mut analyzed = opts
+        .top_text_words
+        .map_or(tokens.len(), |n| n.min(tokens.len()));
+    if let Some(n) = opts.top_text_sentences {
+        // Слов до конца N-го предложения (или всех, если предложений меньше)
+        let end = n.checked_sub(1).map_or(Some(0), |i| metrics::sentence_ends(&text, opts.typographic).nth(i));
+        if let Some(end) = end {
+            analyzed = analyzed.min(tokens.partition_point(|t| t.start < end));
+        }
+    }
+    Ok(PreparedText {
+        input,
+        text,
+        offsets,
+        tokens,
+        analyzed,
+        contractions_expanded,
+        quoted_chars_excluded,
+        quoted_tokens_excluded,
+        ignored_tokens_excluded,
+        dehyphenated,
+        warnings,
+        tokenize_time: started.elapsed(),
+        deadline,
+    })
+}
+
+/// Оценивает подготовленный текст; None — если в тексте не нашлось ни одного слова.
+/// Срок --timeout проверяется между частями отчёта (`Error::Timeout`).
+pub fn score_prepared(prepared: &PreparedText, dict: &FrequencyDict, opts: &ScoreOptions) -> Result<Option<ScoreReport>> {
+    let started = Instant::now();
+    let check = || deadline::check(prepared.deadline);
+    let tokens = prepared.analyzed_tokens();
+    let Some(score) = compute_readability(tokens, dict, opts) else {
+        return Ok(None);
+    };
+    check()?;
+    let mut explain = if opts.explain {
+        explain_tokens(tokens, dict, opts)
+    } else {
+        Vec::new()
+    };
+    if opts.explain_spans && !explain.is_empty() {
+        let mut by_word: HashMap<&str, Vec<(usize, usize)>> = HashMap::new();
+        for (t, span) in tokens.iter().zip(prepared.input_spans(tokens)) {
+            let spans = by_word.entry(t.word.as_str()).or_default();
+            // Слова из одного раскрытого сокращения делят его диапазон
+            if spans.last() != Some(&span) {
+                spans.push(span);
+            }
+        }
+        for e in &mut explain {
+            e.spans = by_word.remove(e.word.as_str()).unwrap_or_default();
+        }
+    }
+    check()?;
+    let suggestions = opts
+        .suggest
+        .as_ref()
+        .map_or_else(Vec::new, |s| suggest::suggest(tokens, dict, opts, s));
+    for e in &mut explain {
+        if let Some(s) = suggestions.iter().find(|s| s.word == e.word) {
+            e.suggestions = s.alternatives.clone();
+        }
+    }
+    check()?;
+    let bootstrap = opts.bootstrap.and_then(|b| {
+        let weights: Vec<f64> = tokens.iter().map(|t| token_weight(t, dict, opts)).collect();
+        bootstrap::bootstrap(&weights, b)
+    });
+    let oov_classes = opts.oov_weights.map(|_| {
+        let mut counts = oov::OovCounts::default();
+        for class in tokens.iter().filter_map(|t| oov_class(t, dict, opts)) {
+            counts.add(class);
+        }
+        counts
+    });
+    check()?;
+    let mut warnings = prepared.warnings.clone();
+    let clauses = opts
+        .metrics
+        .contains(&metrics::Metric::ClauseDensity)
+        .then(|| clauses::analyze(prepared, opts))
+        .flatten();
+    let metrics = metrics::compute(opts, prepared, score, clauses.as_ref(), &mut warnings);
+    check()?;
+    let aggregate = opts
+        .aggregate
+        .and_then(|a| aggregate::aggregate(prepared, dict, opts, a, score))
+        .map(Box::new);
+    check()?;
+    // Если ни одна часть не набрала --min-tokens слов, остаётся оценка по всем словам
+    let score = match &aggregate {
+        Some(a) => a.score.unwrap_or_else(|| {
+            warnings.push(Warning::NoAggregateUnits { by: a.by, excluded: a.excluded() });
+            score
+        }),
+        None => score,
+    };
+    Ok(Some(ScoreReport {
+        score,
+        tokens: prepared.analyzed,
+        oov_tokens: tokens.iter().filter(|t| !dict.contains(&t.word)).count(),
+        contractions_expanded: prepared.contractions_expanded,
+        quoted_chars_excluded: prepared.quoted_chars_excluded,
+        quoted_tokens_excluded: prepared.quoted_tokens_excluded,
+        ignored_tokens_excluded: prepared.ignored_tokens_excluded,
+        dehyphenated: prepared.dehyphenated,
+        explain,
+        bootstrap,
+        aggregate,
+        oov_classes,
+        clauses,
+        overlap: overlap::overlap(tokens, dict, &opts.overlap, opts.overlap_delta),
+        thresholds: opts.thresholds,
+        tertiles: opts.tertiles.then(|| tertiles::tertiles(tokens, dict, opts)),
+        suggestions,
+        metrics,
+        warnings,
+        timings: timings::TextTimings {
+            read: Default::default(),
+            tokenize: prepared.tokenize_time,
+            score: started.elapsed(),
+        },
+    }))
+}
+
+/// Токенизирует и оценивает текст; None — если в тексте не нашлось ни одного слова.
+pub fn score_text(text: &str, dict: &FrequencyDict, opts: &ScoreOptions) -> Result<Option<ScoreReport>> {
+    score_prepared(&prepare_text(text, Some(dict), opts)?, dict, opts)
+}
@@ -1,145 +1,2013 @@
-use std::collections::HashMap;
 use std::fs::File;
-use std::io::{self, Read};
-use std::path::PathBuf;
+use std::io::{self, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
 
 use anyhow::{bail, Context, Result};
-use clap::Parser;
+use clap::parser::ValueSource;
+use clap::{ArgAction, ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use regex::Regex;
 
+use readability::annotate::{self, AnnotateOptions};
+use readability::aggregate::{Aggregate, AggregateBy, AggregateOptions};
+use readability::casefold::Locale;
+use readability::dict::{self, DictLoadInfo, DictOptions, DuplicatePolicy, FrequencyDict, Weighting};
+use readability::estimate::{self, EstimateOptions};
+use readability::features;
+use readability::labels::Thresholds;
+use readability::lang_data::{self, LangData};
+use readability::metrics::Metric;
+use readability::oov::OovWeights;
+use readability::pipeline::{Stage, TokenPipeline};
+use readability::suggest::{SuggestOptions, Synonyms};
+use readability::windows::{self, WindowOptions};
+use readability::{
+    bootstrap, contractions, insufficient_json, language_mismatch, prepare_text, read_input_limited, rng,
+    read_input_hashed, score_prepared, InputLimit, Lang, PreparedText, ScoreOptions, ScoreReport, Warning,
+};
+
+mod cache;
+mod checkpoint;
+mod corpus;
+mod daemon_dict;
+mod dict_cmd;
+mod history;
+mod i18n;
+mod self_relative;
+mod template;
+mod warnings;
+mod watch;
+mod word_cmd;
+mod word_freq;
+
+use daemon_dict::{ActiveDict, DaemonDict, DictSource};
+use i18n::Msg;
+use template::{parse_template, Template};
+
 /// CLI: вычисление "понятности" текста по частотному словарю из английской Википедии.
 #[derive(Parser, Debug)]
-#[command(name = "readability", version, about)]
+#[command(name = "readability", version, about, args_conflicts_with_subcommands = true)]
 struct Args {
-    /// Путь к JSON-словарю вида [["the", 199660765], ...]
-    #[arg(long = "dict", default_value = "word_frequencies.json")]
-    dict_path: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Путь к JSON-словарю вида [["the", 199660765], ...];
+    /// по умолчанию word_frequencies.json для en и word_frequencies.<lang>.json для остальных языков
+    #[arg(long = "dict")]
+    dict_path: Option<PathBuf>,
+
+    /// Смесь словарей вместо --dict: `general.json:0.7,domain.json:0.3`. Каждый словарь
+    /// нормируется отдельно, вес слова — сумма долей × весов (нет слова — вклад 0); доли в сумме дают 1
+    #[arg(long = "dict-blend", value_parser = dict_cmd::parse_blend, conflicts_with = "dict_path")]
+    dict_blend: Option<dict_cmd::DictBlend>,
+
+    /// Режим корпуса без внешнего словаря: словарь строится из частот слов всех входных файлов
+    /// (с теми же настройками токенизации), затем по нему оценивается каждый файл
+    #[arg(
+        long = "self-relative",
+        requires = "text_paths",
+        conflicts_with_all = ["dict_path", "dict_blend", "dedupe_lines", "cache_scores", "resume", "watch"]
+    )]
+    self_relative: bool,
+
+    /// С --self-relative оценивать каждый файл по словарю корпуса без его собственных слов,
+    /// чтобы большой файл не делал свою лексику «частой»
+    #[arg(long = "self-relative-exclude-self", requires = "self_relative")]
+    self_relative_exclude_self: bool,
+
+    /// Сохранить словарь --self-relative (частоты корпуса) в файл: .csv — CSV, иначе JSON
+    #[arg(long = "save-dict", requires = "self_relative")]
+    save_dict: Option<PathBuf>,
+
+    /// Язык текста: определяет шаблон слов, правила приведения к нижнему регистру и словарь по умолчанию
+    #[arg(long = "lang", value_enum, default_value_t = Lang::En)]
+    lang: Lang,
+
+    /// Сворачивать регистр ключей словаря и слов текста по правилам: default — общие правила Unicode
+    /// (Straße и STRASSE совпадают, İ → i), tr — турецкие (I → ı, İ → i). Без флага слова только
+    /// переводятся в нижний регистр, а ключи словаря берутся как есть
+    #[arg(long = "locale", value_enum)]
+    locale: Option<Locale>,
+
+    /// Каталог данных языков: `<язык>/stopwords.txt` (слово в строке, для стадии stopwords в --pipeline)
+    /// и `<язык>/syllables.toml` (гласные, сочетания, немые и слоговые окончания, таблица [exceptions];
+    /// для flesch и fog). Отсутствующий файл заменяется встроенными данными для английского,
+    /// для остальных языков — ничем
+    #[arg(long = "lang-data")]
+    lang_data: Option<PathBuf>,
+
+    /// Формат вывода: plain — только число, json — объект с оценкой и метаданными
+    #[arg(long = "format", value_enum, default_value_t = OutputFormat::Plain)]
+    format: OutputFormat,
 
-    /// Путь к текстовому файлу для оценки; если не указан — читаем текст из STDIN
+    /// Путь к текстовому файлу или каталогу для оценки; можно указать несколько раз.
+    /// Если не указан — читаем текст из STDIN. Несколько путей или каталог включают режим корпуса
     #[arg(long = "text")]
-    text_path: Option<PathBuf>,
+    text_paths: Vec<PathBuf>,
+
+    /// Не учитывать .gitignore и .ignore при обходе каталогов
+    #[arg(long = "no-ignore")]
+    no_ignore: bool,
+
+    /// Исключить пути по глобу при обходе каталогов (например, '*.min.js.map'); можно указать несколько раз
+    #[arg(long = "exclude")]
+    exclude: Vec<String>,
+
+    /// Расширения двоичных файлов через запятую: в режиме корпуса такие файлы пропускаются
+    /// (`skipped: binary`), как и файлы с нулевым байтом в первых 8 КБ
+    #[arg(long = "binary-extensions", value_delimiter = ',', default_value = DEFAULT_BINARY_EXTENSIONS)]
+    binary_extensions: Vec<String>,
+
+    /// Оценивать все файлы корпуса как текст, не проверяя, двоичные ли они
+    #[arg(long = "force-text")]
+    force_text: bool,
+
+    /// Число потоков для оценки корпуса (0 — по числу ядер)
+    #[arg(long = "jobs", default_value_t = 1)]
+    jobs: usize,
+
+    /// Упорядочить результаты корпуса по оценке (при равенстве — по пути)
+    #[arg(long = "sort", value_enum)]
+    sort: Option<corpus::SortOrder>,
+
+    /// Оставить только первые N файлов после сортировки
+    #[arg(long = "top", requires = "sort", conflicts_with = "bottom")]
+    top: Option<usize>,
+
+    /// Оставить только последние N файлов после сортировки
+    #[arg(long = "bottom", requires = "sort")]
+    bottom: Option<usize>,
+
+    /// В режиме корпуса исключить из оценки строки, повторяющиеся во многих файлах
+    /// (навигация, подвалы); для одного входа не действует
+    #[arg(long = "dedupe-lines")]
+    dedupe_lines: bool,
+
+    /// Доля файлов, больше которой строка считается шаблоном для --dedupe-lines
+    #[arg(long = "dedupe-threshold", default_value_t = 0.5, requires = "dedupe_lines", value_parser = parse_unit_weight)]
+    dedupe_threshold: f64,
+
+    /// Итоговая статистика по корпусу после построчных результатов;
+    /// для одного входа печатается только с --summary=always
+    #[arg(long = "summary", value_enum, num_args = 0..=1, require_equals = true, default_missing_value = "auto")]
+    summary: Option<SummaryMode>,
 
     /// Анализировать только первые N слов входного текста (по порядку в тексте)
     #[arg(long = "top-text-words")]
     top_text_words: Option<usize>,
 
+    /// Анализировать только слова первых N предложений входного текста
+    #[arg(long = "top-text-sentences", conflicts_with = "top_text_words")]
+    top_text_sentences: Option<usize>,
+
     /// Использовать только первые K записей словаря (ускорение/эксперименты)
     #[arg(long = "top-dict-entries")]
     top_dict_entries: Option<usize>,
+
+    /// Вместо --top-dict-entries: брать самые частые записи словаря, пока их частоты не наберут
+    /// долю F (0 < F ≤ 1) суммы всех частот. Словарь читается дважды: сначала ради суммы, затем
+    /// до набранной доли; неупорядоченный держится в памяти целиком и сортируется по частоте.
+    /// Сколько записей оставлено и какая доля набрана, печатает --verbose
+    #[arg(long = "dict-coverage-target", value_parser = parse_coverage, conflicts_with = "top_dict_entries")]
+    dict_coverage_target: Option<f64>,
+
+    /// Хранить веса словаря в памяти в фиксированной точке из BITS бит (пока только 16):
+    /// около вдвое меньше памяти в режиме демона, оценка отличается не больше чем на 8·10⁻⁶
+    #[arg(long = "quantize-weights", value_name = "BITS", value_parser = parse_quantize_bits)]
+    quantize_weights: Option<u8>,
+
+    /// Что делать со словом, встретившимся в словаре несколько раз
+    #[arg(long = "dict-duplicates", value_enum, default_value_t = DuplicatePolicy::Sum)]
+    dict_duplicates: DuplicatePolicy,
+
+    /// Пропускать некорректные записи словаря (с предупреждением и номерами первых из них),
+    /// а не завершаться ошибкой; нужна хотя бы одна корректная запись
+    #[arg(long = "dict-lenient")]
+    dict_lenient: bool,
+
+    /// Функция перевода частоты слова в вес: linear, log или rank
+    #[arg(long = "weight", value_enum, default_value_t = Weighting::Linear)]
+    weight: Weighting,
+
+    /// Раскрывать сокращения перед поиском в словаре (won't → will not, I'm → I am)
+    #[arg(long = "expand-contractions")]
+    expand_contractions: bool,
+
+    /// TSV-файл `форма<TAB>раскрытие`, дополняющий и переопределяющий встроенную таблицу сокращений
+    #[arg(long = "contractions-file")]
+    contractions_file: Option<PathBuf>,
+
+    /// Распознавать аббревиатуры (NASA, HTML) и сокращения с точками (U.S., Ph.D., e.g.) как отдельные слова
+    #[arg(long = "keep-acronyms")]
+    keep_acronyms: bool,
+
+    /// Вес аббревиатуры, отсутствующей в словаре (вместо 0 для неизвестных слов)
+    #[arg(long = "acronym-weight", default_value_t = 1.0, requires = "keep_acronyms", value_parser = parse_unit_weight)]
+    acronym_weight: f64,
+
+    /// Исключить из оценки прямую речь: фрагменты в двойных кавычках ("…", “…”, «…»)
+    #[arg(long = "skip-quotes")]
+    skip_quotes: bool,
+
+    /// Не очищать текст перед токенизацией: оставить BOM, символы нулевой ширины,
+    /// мягкие переносы и неразрывные пробелы как есть
+    #[arg(long = "no-sanitize")]
+    no_sanitize: bool,
+
+    /// Не обрабатывать директивы `<!-- readability:ignore-start -->` … `<!-- readability:ignore-end -->`
+    /// и `<!-- readability:ignore-line -->` (исключение следующей строки): оценивать текст целиком
+    #[arg(long = "no-directives")]
+    no_directives: bool,
+
+    /// Не склеивать слова, перенесённые через строку с дефисом (`read-` / `ability`). По умолчанию
+    /// перенос склеивается, если слитное слово есть в словаре или вторая часть начинается со строчной
+    /// буквы, и остаётся через дефис, если так слово есть в словаре (`twenty-first`)
+    #[arg(long = "no-dehyphenate")]
+    no_dehyphenate: bool,
+
+    /// Стадии нормализации через запятую, ровно в этом порядке: directives, sanitize, dehyphenate,
+    /// skip-quotes (до токенизации), lowercase, expand-contractions, stopwords (над словами; stopwords —
+    /// только здесь). Без --pipeline цепочку задают флаги --no-directives, --no-sanitize, --no-dehyphenate,
+    /// --skip-quotes и --expand-contractions: directives,sanitize,dehyphenate,skip-quotes,lowercase,
+    /// expand-contractions без выключенных
+    #[arg(
+        long = "pipeline",
+        value_parser = parse_pipeline,
+        conflicts_with_all = ["no_directives", "no_sanitize", "no_dehyphenate", "skip_quotes", "expand_contractions"]
+    )]
+    pipeline: Option<TokenPipeline>,
+
+    /// Разбивать на предложения без учёта типографики: многоточие … не считается концом
+    /// предложения, кавычки после знака конца и слова автора после прямой речи не учитываются
+    #[arg(long = "no-typographic-normalization")]
+    no_typographic_normalization: bool,
+
+    /// Минимальное число оцениваемых слов: при меньшем оценка не печатается
+    /// (код выхода 3, в JSON — "score": null и "reason": "insufficient_tokens")
+    #[arg(long = "min-tokens", default_value_t = 0)]
+    min_tokens: usize,
+
+    /// Не оценивать текст длиннее N слов: токенизация прерывается, как только их становится больше
+    /// (по умолчанию без ограничения, в режиме демона — 2 000 000)
+    #[arg(long = "max-tokens", value_parser = parse_positive)]
+    max_tokens: Option<usize>,
+
+    /// Не читать вход больше N байт: у файлов размер проверяется до чтения, у STDIN — по ходу
+    /// (по умолчанию без ограничения, в режиме демона — 16 МиБ на запрос)
+    #[arg(long = "max-input-bytes", value_parser = parse_positive)]
+    max_input_bytes: Option<usize>,
+
+    /// Пропускать слова длиннее N символов (с предупреждением W012): такие «слова» — обычно
+    /// base64, хэши или склеенный мусор, и в словаре их всё равно нет
+    #[arg(long = "max-token-len", default_value_t = 100, value_parser = parse_positive)]
+    max_token_len: usize,
+
+    /// Прерывать подготовку и оценку текста, занявшую больше SECONDS секунд (дробные допустимы).
+    /// В корпусе у такого файла "reason": "timeout" (в CSV — статус timeout), остальные оцениваются
+    /// дальше; в режиме демона срок действует на каждый запрос
+    #[arg(long, value_name = "SECONDS", value_parser = parse_positive_f64)]
+    timeout: Option<f64>,
+
+    /// Делить слова вне словаря на классы — имя, вероятная опечатка, редкое слово — и давать
+    /// каждому классу свой вес (--oov-weights); класс виден в --explain, в JSON — "oov_classes"
+    #[arg(long = "classify-oov")]
+    classify_oov: bool,
+
+    /// Веса классов --classify-oov: `name=0.8,typo=0.3,rare=0.0` (не указанные — по умолчанию, как здесь)
+    #[arg(long = "oov-weights", value_parser = parse_oov_weights, requires = "classify_oov")]
+    oov_weights: Option<OovWeights>,
+
+    /// Оценивать документ по частям: сначала каждый абзац (части разделены пустой строкой)
+    /// или предложение, затем оценки частей объединяются функцией --aggregate. Части короче
+    /// --min-tokens не учитываются; в JSON — объект "aggregate" с оценками и диапазонами частей
+    #[arg(long = "aggregate-by", value_enum)]
+    aggregate_by: Option<AggregateBy>,
+
+    /// Как объединять оценки частей --aggregate-by: среднее, медиана или минимум (самая трудная часть)
+    #[arg(long = "aggregate", value_enum, default_value_t = Aggregate::Mean, requires = "aggregate_by")]
+    aggregate: Aggregate,
+
+    /// Интервал оценки бутстрепом: N повторных выборок слов с возвращением (по умолчанию 1000);
+    /// в plain-режиме печатается `оценка p2.5 p97.5`
+    #[arg(long = "bootstrap", num_args = 0..=1, require_equals = true, default_missing_value = "1000")]
+    bootstrap: Option<usize>,
+
+    /// Приближённая оценка больших входов: вход читается один раз, из него случайно выбираются
+    /// --sample-sentences предложений, оценка печатается со стандартной ошибкой: `0.431200 ±0.004100 (...)`
+    #[arg(
+        long = "estimate",
+        conflicts_with_all = [
+            "daemon", "watch", "explain", "bootstrap", "aggregate_by", "metrics", "profile_out",
+            "annotate", "append_score", "top_text_words", "top_text_sentences", "history"
+        ]
+    )]
+    estimate: bool,
+
+    /// Размер выборки предложений для --estimate; при том же --seed результат повторяется
+    #[arg(long = "sample-sentences", default_value_t = 1000, value_parser = parse_positive, requires = "estimate")]
+    sample_sentences: usize,
+
+    /// Своя plain-строка результата вместо обычной, для каждого входа: `{path}\t{score:.3}\t{oov_rate:.1%}`.
+    /// Поля — path, index (номер входа, с нуля), score, tokens, oov_tokens, oov_rate, contractions_expanded,
+    /// quoted_chars_excluded, quoted_tokens_excluded, ci_lo, ci_hi (--bootstrap), oov_name, oov_typo, oov_rare
+    /// (--classify-oov); формат `.N` — знаков после запятой, `%` — в процентах. `{{`, `}}`, `\t`, `\n`, `\\` — литералы
+    #[arg(
+        long = "template",
+        value_parser = parse_template,
+        conflicts_with_all = ["daemon", "watch", "metrics", "annotate", "append_score", "estimate"]
+    )]
+    template: Option<Template>,
+
+    /// Зерно генератора случайных чисел для воспроизводимости (по умолчанию — от текущего времени)
+    #[arg(long = "seed")]
+    seed: Option<u64>,
+
+    /// Вывести разбор по словам: частота в тексте, вес и пометки, от самых трудных к простым
+    #[arg(long = "explain")]
+    explain: bool,
+
+    /// Добавить к каждому слову разбора --explain в JSON байтовые диапазоны всех его вхождений
+    /// во входном тексте: "spans": [[начало, конец], ...]
+    #[arg(long = "explain-spans", requires = "explain")]
+    explain_spans: bool,
+
+    /// Полностью детерминированный вывод для сверки по хешу: списки упорядочены, результаты
+    /// --jobs идут в порядке входа, время в --history берётся из SOURCE_DATE_EPOCH (без него поля
+    /// "time" нет), а --bootstrap и --estimate требуют явного --seed
+    #[arg(long = "reproducible", conflicts_with_all = ["timings", "watch"])]
+    reproducible: bool,
+
+    /// Печатать в STDERR длительность этапов (в JSON — объект "timings" в отчёте)
+    #[arg(long = "timings")]
+    timings: bool,
+
+    /// Печатать в STDERR подробности обработки
+    #[arg(long = "verbose")]
+    verbose: bool,
+
+    /// Завершаться с ошибкой, если язык текста не совпадает с языком словаря (вместо предупреждения)
+    #[arg(long = "strict-lang")]
+    strict_lang: bool,
+
+    /// Вывести исходный текст без изменений, дописав в конец строку-трейлер с оценкой
+    #[arg(long = "append-score", conflicts_with = "daemon", group = "rewrite")]
+    append_score: bool,
+
+    /// Синтаксис трейлера: html — <!-- ... -->, hash — # ..., none-prefix — без обрамления
+    #[arg(long = "trailer-style", value_enum, default_value_t = TrailerStyle::Html, requires = "append_score")]
+    trailer_style: TrailerStyle,
+
+    /// Куда писать текст с трейлером или пометками --annotate (по умолчанию STDOUT)
+    #[arg(long = "output", requires = "rewrite")]
+    output_path: Option<PathBuf>,
+
+    /// Заменить входной файл текстом с трейлером (через временный файл и переименование)
+    #[arg(long = "in-place", requires_all = ["append_score", "text_paths"], conflicts_with = "output_path")]
+    in_place: bool,
+
+    /// Вывести входной текст как есть, но с весом после каждого трудного слова: `редкое[0.0002]`,
+    /// `[OOV]` — слова нет в словаре. Вместо оценки; пригодно для diff и комментариев к ревью
+    #[arg(long = "annotate", conflicts_with = "daemon", group = "rewrite")]
+    annotate: bool,
+
+    /// Помечать слова с весом меньше W (слова вне словаря помечаются всегда)
+    #[arg(long = "annotate-threshold", default_value_t = 0.01, value_parser = parse_unit_weight, requires = "annotate")]
+    annotate_threshold: f64,
+
+    /// Знаков после запятой в пометках --annotate
+    #[arg(long = "annotate-precision", default_value_t = 4, requires = "annotate")]
+    annotate_precision: usize,
+
+    /// Режим демона: словарь загружается один раз, затем из STDIN читаются JSON-запросы
+    /// по одному на строку ({"id": 1, "text": "..."}), ответы пишутся в STDOUT.
+    /// {"cmd": "reload"} перечитывает словарь в фоне и подменяет его, не прерывая оценку,
+    /// {"cmd": "status"} — путь словаря, число слов и время загрузки
+    #[arg(long = "daemon", conflicts_with = "text_paths")]
+    daemon: bool,
+
+    /// Только проверить запуск: сочетания флагов, вспомогательные файлы и начало словаря, без чтения
+    /// входных текстов. Действующая конфигурация (значения флагов и откуда они взялись) печатается
+    /// в JSON; код выхода 0 — всё в порядке, 2 — ошибка
+    #[arg(long = "check-config")]
+    check_config: bool,
+
+    /// Завершаться с кодом 1, если за запуск было хоть одно предупреждение (кроме разрешённых --allow)
+    #[arg(long = "deny-warnings")]
+    deny_warnings: bool,
+
+    /// Не выводить предупреждения с этим кодом (W004 или W004_LANG_MISMATCH) и не учитывать их
+    /// в --deny-warnings; можно указать несколько раз или через запятую
+    #[arg(long = "allow", value_name = "CODE", value_delimiter = ',', value_parser = parse_warning_code)]
+    allow: Vec<&'static str>,
+
+    /// Метрика для вывода; можно указать несколько раз. При нескольких метриках plain-вывод —
+    /// строки `имя<TAB>значение` в порядке запроса, в JSON — объект "metrics" по именам
+    #[arg(long = "metric", value_enum)]
+    metrics: Vec<Metric>,
+
+    /// Пересечение словаря текста с первыми K словами частотного словаря: сколько различных слов
+    /// текста входят в них, сколько есть в словаре дальше и сколько вне словаря, и слова с рангами
+    /// K+1..K+--overlap-delta. Можно указать несколько раз; в plain-режиме — строки после оценки
+    #[arg(long = "overlap", value_parser = parse_positive, conflicts_with = "dict_blend")]
+    overlap: Vec<usize>,
+
+    /// Сколько рангов за K смотреть для --overlap в поисках кандидатов на предварительное изучение
+    #[arg(long = "overlap-delta", default_value_t = 500)]
+    overlap_delta: usize,
+
+    /// Метка сложности по порогам из самого словаря: квинтили оценок встроенных эталонных
+    /// предложений с тем же словарём и --weight. В plain-режиме — строка `label<TAB>метка` после оценки,
+    /// в JSON — "label" и пороги в "thresholds"; пороги печатает --verbose
+    #[arg(long = "auto-thresholds")]
+    auto_thresholds: bool,
+
+    /// Оценки первой, средней и последней трети оцениваемых слов и наибольшая разница между ними.
+    /// Остаток от деления числа слов на 3 достаётся первым третям (10 слов — 4, 3, 3). Границы третей
+    /// зависят от числа слов, поэтому считаются по целиком токенизированному тексту (и не с --estimate);
+    /// сами оценки — за один проход с тремя частичными суммами. В plain-режиме —
+    /// строка `tertiles` после оценки, в CSV и в режиме корпуса — колонки front, middle, back,
+    /// в JSON — объект "tertiles"
+    #[arg(long = "tertile-report", conflicts_with = "estimate")]
+    tertile_report: bool,
+
+    /// Подсказывать более частые синонимы для слов с весом ниже --suggest-threshold: синоним
+    /// предлагается, если он есть в словаре и весит хотя бы вдвое больше слова. В plain-режиме —
+    /// строки `suggest` после оценки, в разборе --explain — колонка синонимов, в JSON — массив
+    /// "suggestions"; слова без более частого синонима не выводятся
+    #[arg(long = "suggest", requires = "synonyms", conflicts_with = "estimate")]
+    suggest: bool,
+
+    /// Файл синонимов для --suggest: TSV `слово<TAB>синоним, синоним`. Слова и синонимы проходят
+    /// те же стадии нормализации, что и текст, так что в файле можно писать словоформы
+    #[arg(long = "synonyms", requires = "suggest")]
+    synonyms: Option<PathBuf>,
+
+    /// Порог веса для --suggest: подсказки ищутся для слов легче порога
+    #[arg(long = "suggest-threshold", default_value_t = 0.01, value_parser = parse_unit_weight, requires = "suggest")]
+    suggest_threshold: f64,
+
+    /// Сколько слов с подсказками выводить, от самых трудных
+    #[arg(long = "suggest-limit", default_value_t = 10, value_parser = parse_positive, requires = "suggest")]
+    suggest_limit: usize,
+
+    /// Добавить в JSON-отчёт объект "provenance": путь входа (или stdin) и SHA-256 его байт,
+    /// SHA-256 файлов словаря и число слов после фильтров, версию программы и отпечаток настроек
+    /// (тот же, что в ключах --cache-scores, но без содержимого словаря). Хэш входа считается тем же
+    /// проходом, которым он читается. В режиме корпуса свой "provenance" с хэшем файла есть у каждой
+    /// записи JSON/JSONL (записи, взятые --resume, сохраняют прежний); plain-режим и CSV не меняются
+    #[arg(long = "with-provenance", conflicts_with_all = ["daemon", "watch", "estimate"])]
+    with_provenance: bool,
+
+    /// Порог для --metric clause-density: сколько клауз в предложении ещё допустимо. Предложения
+    /// сверх порога считаются в JSON (объект "clauses") и перечисляются с --verbose
+    #[arg(long = "max-clauses", default_value_t = 3, value_parser = parse_positive)]
+    max_clauses: usize,
+
+    /// Записать в CSV построчно профиль трудности по окнам слов:
+    /// `window_index,start_token,start_char,score,oov_rate`; обычный вывод не меняется.
+    /// start_char — смещение в символах входного текста, с учётом убранных символов и цитат
+    #[arg(long = "profile-out", conflicts_with = "daemon")]
+    profile_out: Option<PathBuf>,
+
+    /// Записать оцениваемые слова по одному, в порядке текста: номер, слово, вес, признак «вне словаря»,
+    /// номер предложения. Файл `.bin` — плотный массив little-endian f32 (без слов) и описание колонок
+    /// в `<файл>.json` рядом; иначе CSV `token_index,token,weight,oov,sentence_index`
+    #[arg(long = "export-features", conflicts_with_all = ["daemon", "watch", "estimate"])]
+    export_features: Option<PathBuf>,
+
+    /// Не писать слова в CSV --export-features
+    #[arg(long = "no-token-text", requires = "export_features")]
+    no_token_text: bool,
+
+    /// Размер окна профиля, в словах
+    #[arg(long = "window", default_value_t = 100, requires = "profile_out", value_parser = parse_positive)]
+    window: usize,
+
+    /// Шаг окна профиля, в словах (по умолчанию равен размеру окна — окна не перекрываются)
+    #[arg(long = "window-step", requires = "profile_out", value_parser = parse_positive)]
+    window_step: Option<usize>,
+
+    /// Сгладить профиль центрированным скользящим средним по K окнам: в CSV добавляется колонка
+    /// smoothed. У краёв текста среднее берётся по имеющимся соседям
+    #[arg(long = "smooth", requires = "profile_out", value_parser = parse_positive)]
+    smooth: Option<usize>,
+
+    /// Отметить окна, чья оценка отличается от сглаженной больше чем на Z стандартных отклонений
+    /// остатков: номер окна, начало, оценки и первые слова окна. В plain-режиме — строки `anomaly`
+    /// после оценки, в JSON — массив "anomalies"
+    #[arg(long = "flag-anomalies", requires = "smooth", value_parser = parse_positive_f64)]
+    flag_anomalies: Option<f64>,
+
+    /// Переоценивать файл из --text при каждом его изменении и печатать оценку
+    /// с изменением относительно предыдущей: `0.431000 (+0.004000)`
+    #[arg(long = "watch", requires = "text_paths", conflicts_with_all = ["daemon", "append_score", "profile_out"])]
+    watch: bool,
+
+    /// Дописывать в файл JSON Lines запись (время, путь, оценка, число слов, SHA-256 текста)
+    /// при каждой оценке
+    #[arg(long = "history", conflicts_with = "daemon")]
+    history: Option<PathBuf>,
+
+    /// В режиме корпуса дописывать результаты в файл JSON Lines по мере готовности
+    #[arg(long = "results-out", conflicts_with_all = ["daemon", "watch"])]
+    results_out: Option<PathBuf>,
+
+    /// Продолжить прерванную оценку: файлы, уже записанные в --results-out и с тех пор
+    /// не изменившиеся (размер и время изменения), не оцениваются заново; если словарь
+    /// или настройки с тех пор изменились, продолжение отклоняется
+    #[arg(long = "resume", requires = "results_out")]
+    resume: bool,
+
+    /// В режиме корпуса брать оценки неизменившихся файлов из кэша по хэшу содержимого
+    /// и параметров (версия, словарь, настройки оценки) и дописывать в кэш новые
+    #[arg(long = "cache-scores", conflicts_with_all = ["daemon", "watch", "explain", "bootstrap"])]
+    cache_scores: Option<PathBuf>,
+
+    /// Не брать оценки из кэша, только записывать их
+    #[arg(long = "no-cache-read", requires = "cache_scores")]
+    no_cache_read: bool,
+
+    /// Не дописывать в кэш новые оценки, только читать его
+    #[arg(long = "no-cache-write", requires = "cache_scores")]
+    no_cache_write: bool,
+
+    /// Язык сообщений и справки: en или ru (по умолчанию — по переменной LANG, иначе en)
+    // Значение разбирает i18n::init до clap; аргумент объявлен ради справки и проверки значения
+    #[allow(dead_code)]
+    #[arg(long = "lang-ui", value_enum, global = true)]
+    lang_ui: Option<i18n::UiLang>,
 }
 
-fn load_frequency_dict(path: &PathBuf, top_k: Option<usize>) -> Result<HashMap<String, f64>> {
-    let mut f = File::open(path)
-        .with_context(|| format!("Не удалось открыть словарь: {}", path.display()))?;
-    let mut s = String::new();
-    f.read_to_string(&mut s)?;
-    let json: serde_json::Value = serde_json::from_str(&s)
-        .with_context(|| "Некорректный JSON частотного словаря")?;
-
-    let arr = json.as_array().context("Ожидался JSON-массив верхнего уровня")?;
-
-    // Разбираем пары ["word", count]
-    let mut items: Vec<(String, u64)> = Vec::with_capacity(arr.len());
-    for v in arr {
-        if let Some(a) = v.as_array() {
-            if a.len() >= 2 {
-                let word = a[0]
-                    .as_str()
-                    .ok_or_else(|| anyhow::anyhow!("Первый элемент не строка"))?
-                    .to_string();
-                let count = a[1]
-                    .as_u64()
-                    .ok_or_else(|| anyhow::anyhow!("Второй элемент не число"))?;
-                items.push((word, count));
-            } else {
-                bail!("Элемент массива словаря имеет длину < 2");
+/// Код предупреждения для --allow
+fn parse_warning_code(s: &str) -> Result<&'static str, String> {
+    readability::error::parse_warning_code(s).ok_or_else(|| Msg::UnknownWarningCode(s).to_string())
+}
+
+/// Разбор веса из командной строки: число в [0, 1], как и веса словаря
+fn parse_unit_weight(s: &str) -> Result<f64, String> {
+    let v: f64 = s.parse().map_err(|_| Msg::NotANumber(s).to_string())?;
+    if (0.0..=1.0).contains(&v) {
+        Ok(v)
+    } else {
+        Err(Msg::WeightOutOfRange(v).to_string())
+    }
+}
+
+/// Разбор `класс=вес,...` для --oov-weights; классы name, typo, rare
+fn parse_oov_weights(s: &str) -> Result<OovWeights, String> {
+    let mut weights = OovWeights::default();
+    for part in s.split(',') {
+        let (class, value) = part.split_once('=').ok_or_else(|| Msg::OovWeightsBadPart(part).to_string())?;
+        let slot = match class.trim() {
+            "name" => &mut weights.name,
+            "typo" => &mut weights.typo,
+            "rare" => &mut weights.rare,
+            _ => return Err(Msg::OovWeightsBadPart(part).to_string()),
+        };
+        *slot = parse_unit_weight(value.trim())?;
+    }
+    Ok(weights)
+}
+
+/// Таблица сокращений для стадии expand-contractions: встроенная, дополненная --contractions-file
+fn contractions_table(pipeline: &TokenPipeline, file: Option<&Path>) -> Result<Option<Arc<contractions::Contractions>>> {
+    if !pipeline.contains(Stage::ExpandContractions) {
+        if file.is_some() {
+            bail!("{}", Msg::ContractionsFileUnused);
+        }
+        return Ok(None);
+    }
+    let table = contractions::Contractions::builtin();
+    let table = match file {
+        Some(path) => table.with_file(path)?,
+        None => table,
+    };
+    Ok(Some(Arc::new(table)))
+}
+
+fn parse_pipeline(s: &str) -> Result<TokenPipeline, String> {
+    TokenPipeline::parse(s).map_err(|e| i18n::describe(&e))
+}
+
+/// Доля суммы частот для --dict-coverage-target: число в (0, 1]
+fn parse_coverage(s: &str) -> Result<f64, String> {
+    let v: f64 = s.parse().map_err(|_| Msg::NotANumber(s).to_string())?;
+    if v > 0.0 && v <= 1.0 {
+        Ok(v)
+    } else {
+        Err(Msg::CoverageOutOfRange(v).to_string())
+    }
+}
+
+/// Разрядность --quantize-weights: пока поддерживается только 16
+fn parse_quantize_bits(s: &str) -> Result<u8, String> {
+    match s {
+        "16" => Ok(16),
+        _ => Err(Msg::QuantizeBits(s).to_string()),
+    }
+}
+
+/// Разбор порога из командной строки: число больше нуля
+fn parse_positive_f64(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(v) if v > 0.0 && v.is_finite() => Ok(v),
+        Ok(_) => Err(Msg::NotPositiveNumber.to_string()),
+        Err(_) => Err(Msg::NotANumber(s).to_string()),
+    }
+}
+
+/// Разбор размера из командной строки: целое больше нуля
+fn parse_positive(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(0) => Err(Msg::NotPositive.to_string()),
+        Ok(v) => Ok(v),
+        Err(_) => Err(Msg::NotANumber(s).to_string()),
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Частотная таблица слов входного текста (в формате, пригодном для --dict)
+    WordFreq(word_freq::WordFreqArgs),
+    /// Операции над частотными словарями
+    #[command(subcommand)]
+    Dict(dict_cmd::DictCommand),
+    /// Последние записи журнала --history таблицей, с изменением оценки
+    History(history::HistoryArgs),
+    /// Вес, ранг и частота отдельных слов или коротких фраз после той же нормализации, что при оценке;
+    /// для слов вне словаря — ближайшие слова словаря
+    Word(word_cmd::WordArgs),
+}
+
+/// Отпечаток параметров для ключей кэша оценок и заголовка --results-out: содержимое словарей
+/// вместе с `config_fingerprint`
+fn cache_fingerprint(
+    args: &Args,
+    dict_path: &Path,
+    dict_opts: DictOptions,
+    opts: &ScoreOptions,
+    boilerplate: Option<&corpus::Boilerplate>,
+) -> Result<cache::Fingerprint> {
+    let mut fp = config_fingerprint(args, dict_opts, opts, boilerplate)?;
+    match &args.dict_blend {
+        Some(blend) => {
+            for c in &blend.0 {
+                fp.add_file(&c.path)?;
             }
-        } else {
-            bail!("Элемент словаря не является массивом из двух значений");
         }
+        None => fp.add_file(dict_path)?,
+    }
+    Ok(fp)
+}
+
+/// Отпечаток настроек без содержимого словарей (его --with-provenance выводит отдельно):
+/// доли смеси, таблицы сокращений и синонимов, данные языка, настройки загрузки и оценки,
+/// --strict-lang, строки-шаблоны корпуса
+fn config_fingerprint(
+    args: &Args,
+    dict_opts: DictOptions,
+    opts: &ScoreOptions,
+    boilerplate: Option<&corpus::Boilerplate>,
+) -> Result<cache::Fingerprint> {
+    let mut fp = cache::Fingerprint::new();
+    if let Some(blend) = &args.dict_blend {
+        for c in &blend.0 {
+            fp.add(c.share.to_string());
+        }
+    }
+    fp.add(format!("{dict_opts:?} quantize={:?}", args.quantize_weights));
+    if let Some(path) = args.contractions_file.as_deref().filter(|_| opts.pipeline.contains(Stage::ExpandContractions)) {
+        fp.add_file(path)?;
+    }
+    if let Some(path) = args.synonyms.as_deref().filter(|_| opts.suggest.is_some()) {
+        fp.add_file(path)?;
+    }
+    if let Some(dir) = &args.lang_data {
+        for path in lang_data::data_files(dir, args.lang).iter().filter(|p| p.exists()) {
+            fp.add_file(path)?;
+        }
+    }
+    fp.add_options(opts);
+    fp.add(format!("strict_lang={}", args.strict_lang));
+    if let Some(b) = boilerplate {
+        fp.add(format!("{:?}", b.digest()));
+    }
+    Ok(fp)
+}
+
+/// Предупреждение для отчёта (или, с --strict-lang, ошибка), если текст не похож на язык словаря.
+fn check_text_language(text: &str, lang: Lang, strict: bool) -> Result<Option<Warning>> {
+    let Some(warning) = language_mismatch(text, lang) else {
+        return Ok(None);
+    };
+    if strict {
+        bail!("{}", Msg::StrictLangMismatch(&warning_text(&warning)));
+    }
+    Ok(Some(warning))
+}
+
+/// Текст предупреждения библиотеки на языке интерфейса
+fn warning_text(warning: &Warning) -> String {
+    i18n::warning_text(warning, i18n::ui())
+}
+
+/// JSON-отчёт вместе с предупреждениями на языке интерфейса
+fn report_json(report: &ScoreReport, lang: Lang) -> serde_json::Value {
+    let mut json = report.to_json(lang);
+    if let Some(warnings) = warnings::to_json(&report.warnings) {
+        json["warnings"] = warnings;
+    }
+    json
+}
+
+/// Строки для --verbose
+fn verbose_lines(report: &ScoreReport, opts: &ScoreOptions) -> Vec<String> {
+    let mut lines = vec![Msg::VerboseTokens(report.tokens).to_string()];
+    if opts.contractions.is_some() {
+        lines.push(Msg::VerboseContractions(report.contractions_expanded).to_string());
+    }
+    if opts.pipeline.contains(Stage::Directives) {
+        lines.push(Msg::VerboseIgnored(report.ignored_tokens_excluded).to_string());
+    }
+    if opts.pipeline.contains(Stage::Dehyphenate) {
+        lines.push(Msg::VerboseDehyphenated(report.dehyphenated).to_string());
+    }
+    if opts.pipeline.contains(Stage::SkipQuotes) {
+        lines.push(
+            Msg::VerboseQuotes {
+                chars: report.quoted_chars_excluded,
+                tokens: report.quoted_tokens_excluded,
+            }
+            .to_string(),
+        );
+    }
+    if let Some(c) = &report.clauses {
+        lines.push(Msg::VerboseClauses { over: c.over, max_clauses: c.max_clauses }.to_string());
+        for s in c.offenders() {
+            lines.push(format!("  {}..{}\t{}\t{}", s.start, s.end, s.clauses, s.depth));
+        }
+    }
+    if let Some(a) = &report.aggregate {
+        lines.push(
+            Msg::VerboseAggregate {
+                units: a.units.len(),
+                excluded: a.excluded(),
+                token_score: a.token_score,
+            }
+            .to_string(),
+        );
+    }
+    lines
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    Plain,
+    Json,
+    Jsonl,
+    Csv,
+}
+
+/// Поле CSV: в кавычках, если содержит разделитель, кавычку или перевод строки
+fn csv_field(s: &str) -> std::borrow::Cow<'_, str> {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\"")).into()
+    } else {
+        s.into()
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum SummaryMode {
+    /// Только когда оценено больше одного входа
+    Auto,
+    /// Всегда, даже для одного входа
+    Always,
+}
+
+/// Расширения двоичных файлов по умолчанию для --binary-extensions
+const DEFAULT_BINARY_EXTENSIONS: &str = "png,jpg,jpeg,gif,bmp,ico,webp,tif,tiff,pdf,zip,gz,tgz,bz2,xz,7z,rar,tar,\
+     exe,dll,so,dylib,o,a,class,jar,wasm,pyc,woff,woff2,ttf,otf,eot,mp3,mp4,wav,ogg,flac,avi,mov,mkv,sqlite,db";
+
+/// Ограничения демона по умолчанию (если не заданы --max-input-bytes и --max-tokens):
+/// один запрос — не больше 16 МиБ и двух миллионов слов
+const DAEMON_MAX_INPUT_BYTES: u64 = 16 * 1024 * 1024;
+const DAEMON_MAX_TOKENS: usize = 2_000_000;
+
+/// Код выхода, когда слов меньше --min-tokens: отличается и от ошибки (1), и от ошибки аргументов (2)
+const EXIT_INSUFFICIENT_TOKENS: i32 = 3;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum TrailerStyle {
+    Html,
+    Hash,
+    NonePrefix,
+}
+
+impl TrailerStyle {
+    fn render(self, body: &str) -> String {
+        match self {
+            TrailerStyle::Html => format!("<!-- {body} -->"),
+            TrailerStyle::Hash => format!("# {body}"),
+            TrailerStyle::NonePrefix => body.to_string(),
+        }
+    }
+}
+
+/// Трейлер любого стиля, ранее дописанный этой утилитой
+fn trailer_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"^(?:<!-- |# )?readability: \S+ dict=.* tokens=\d+(?: -->)?$").unwrap()
+    })
+}
+
+/// Отрезает от текста трейлер, оставшийся от предыдущего запуска (если он есть),
+/// чтобы повторный запуск заменял его, а не дописывал ещё один.
+fn strip_score_trailer(text: &str) -> &str {
+    let body = text.trim_end_matches(['\n', '\r']);
+    let (head, last_line) = match body.rfind('\n') {
+        Some(i) => (&body[..=i], &body[i + 1..]),
+        None => ("", body),
+    };
+    if trailer_regex().is_match(last_line) {
+        head
+    } else {
+        text
+    }
+}
+
+/// Пишет файл через временный файл рядом с ним и переименование,
+/// чтобы сбой посреди записи не оставил оригинал обрезанным.
+fn write_file_atomically(path: &Path, content: &str) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .with_context(|| Msg::InvalidPath(path).to_string())?;
+    let tmp_path = path.with_file_name(format!(".{}.readability.tmp", file_name.to_string_lossy()));
+    {
+        let mut f = File::create(&tmp_path)
+            .with_context(|| Msg::TempFileCreate(&tmp_path).to_string())?;
+        f.write_all(content.as_bytes())?;
+        f.sync_all()?;
+    }
+    std::fs::rename(&tmp_path, path)
+        .with_context(|| Msg::FileReplace(path).to_string())?;
+    Ok(())
+}
+
+/// Повторно запрошенная метрика выводится один раз, на месте первого упоминания
+fn dedup_metrics(metrics: &[Metric]) -> Vec<Metric> {
+    let mut out: Vec<Metric> = Vec::with_capacity(metrics.len());
+    for &m in metrics {
+        if !out.contains(&m) {
+            out.push(m);
+        }
+    }
+    out
+}
+
+/// Значение метрики для plain-вывода; не посчитанная метрика — прочерк
+fn metric_value(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{v:.6}"))
+}
+
+/// Пишет профиль по окнам в CSV построчно, не накапливая строки в памяти. С --smooth окна
+/// сначала собираются (сглаживанию нужны соседи), и возвращаются аномалии --flag-anomalies.
+fn write_profile(
+    path: &Path,
+    prepared: &PreparedText,
+    dict: &FrequencyDict,
+    opts: &ScoreOptions,
+    wopts: WindowOptions,
+    smooth: Option<(usize, Option<f64>)>,
+) -> Result<Vec<windows::Anomaly>> {
+    let mut anomalies = Vec::new();
+    let mut write = || -> io::Result<()> {
+        let mut out = io::BufWriter::new(File::create(path)?);
+        let Some((k, z)) = smooth else {
+            writeln!(out, "window_index,start_token,start_char,score,oov_rate")?;
+            windows::for_each_window(prepared, dict, opts, wopts, |w| {
+                writeln!(out, "{},{},{},{},{}", w.index, w.start_token, w.start_char, w.score, w.oov_rate)
+            })?;
+            return out.flush();
+        };
+        let mut rows = Vec::new();
+        windows::for_each_window(prepared, dict, opts, wopts, |w| {
+            rows.push(w);
+            Ok::<_, io::Error>(())
+        })?;
+        let scores: Vec<f64> = rows.iter().map(|w| w.score).collect();
+        let smoothed = windows::smooth(&scores, k);
+        writeln!(out, "window_index,start_token,start_char,score,oov_rate,smoothed")?;
+        for (w, s) in rows.iter().zip(&smoothed) {
+            writeln!(out, "{},{},{},{},{},{s}", w.index, w.start_token, w.start_char, w.score, w.oov_rate)?;
+        }
+        if let Some(z) = z {
+            anomalies = windows::anomalies(prepared, &rows, &smoothed, z);
+        }
+        out.flush()
+    };
+    write().with_context(|| Msg::FileWrite(path).to_string())?;
+    Ok(anomalies)
+}
+
+/// Колонки двоичной выгрузки --export-features, в порядке записи
+const FEATURE_COLUMNS: &[&str] = &["token_index", "weight", "oov", "sentence_index"];
+
+/// Пишет --export-features: `.bin` с описанием в `<файл>.json` или CSV
+fn write_features(
+    path: &Path,
+    prepared: &PreparedText,
+    dict: &FrequencyDict,
+    opts: &ScoreOptions,
+    token_text: bool,
+) -> Result<()> {
+    let binary = path.extension().is_some_and(|e| e == "bin");
+    let mut rows = 0usize;
+    let write = |rows: &mut usize| -> io::Result<()> {
+        let mut out = io::BufWriter::new(File::create(path)?);
+        if !binary {
+            let header = match token_text {
+                true => "token_index,token,weight,oov,sentence_index",
+                false => "token_index,weight,oov,sentence_index",
+            };
+            writeln!(out, "{header}")?;
+        }
+        features::for_each_feature(prepared, dict, opts, |f| {
+            *rows += 1;
+            let oov = u8::from(f.oov);
+            if binary {
+                for v in [f.index as f32, f.weight as f32, f32::from(oov), f.sentence as f32] {
+                    out.write_all(&v.to_le_bytes())?;
+                }
+                Ok(())
+            } else if token_text {
+                writeln!(out, "{},{},{},{oov},{}", f.index, csv_field(f.word), f.weight, f.sentence)
+            } else {
+                writeln!(out, "{},{},{oov},{}", f.index, f.weight, f.sentence)
+            }
+        })?;
+        out.flush()
+    };
+    write(&mut rows).with_context(|| Msg::FileWrite(path).to_string())?;
+    if binary {
+        let mut sidecar = path.as_os_str().to_owned();
+        sidecar.push(".json");
+        let sidecar = PathBuf::from(sidecar);
+        let meta = serde_json::json!({
+            "dtype": "float32",
+            "byte_order": "little",
+            "rows": rows,
+            "columns": FEATURE_COLUMNS,
+        });
+        std::fs::write(&sidecar, format!("{meta}\n")).with_context(|| Msg::FileWrite(&sidecar).to_string())?;
     }
+    Ok(())
+}
 
-    if let Some(k) = top_k {
-        items.truncate(k.min(items.len()));
+/// Результат для текста короче --min-tokens и выход с кодом 3.
+/// В plain-режиме ничего не печатаем: признак — код выхода
+fn exit_insufficient(format: OutputFormat, tokens: usize, lang: Lang, dict_name: &str) -> ! {
+    match format {
+        OutputFormat::Plain => {}
+        OutputFormat::Csv => {
+            println!("score,tokens,lang,reason");
+            println!(",{tokens},{},insufficient_tokens", lang.code());
+        }
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let mut json = insufficient_json(tokens, lang);
+            json["dict"] = serde_json::Value::from(dict_name);
+            println!("{json}");
+        }
     }
+    std::process::exit(EXIT_INSUFFICIENT_TOKENS);
+}
 
-    if items.is_empty() {
-        bail!("Словарь пуст");
+/// Оценка по выборке предложений (--estimate): `оценка ±ошибка (пометка)` или JSON с объектом "estimate"
+fn run_estimate(args: &Args, dict: &FrequencyDict, opts: &ScoreOptions, dict_name: &str) -> Result<()> {
+    let eopts = EstimateOptions {
+        sentences: args.sample_sentences,
+        seed: args.seed.unwrap_or_else(rng::seed_from_time),
+    };
+    let text_path = args.text_paths.first().map(PathBuf::as_path);
+    let est = estimate::estimate(text_path, dict, opts, eopts)?.with_context(|| Msg::NoWords.to_string())?;
+    if opts.insufficient(est.tokens) {
+        exit_insufficient(args.format, est.tokens, args.lang, dict_name);
+    }
+    match args.format {
+        OutputFormat::Plain => println!(
+            "{:.6} ±{} ({})",
+            est.score,
+            est.std_error.map_or_else(|| "-".to_string(), |e| format!("{e:.6}")),
+            Msg::EstimatedFrom(est.sampled)
+        ),
+        OutputFormat::Csv => {
+            println!("score,std_error,sampled_sentences,total_sentences,tokens,lang");
+            println!(
+                "{},{},{},{},{},{}",
+                est.score,
+                est.std_error.map_or_else(String::new, |e| e.to_string()),
+                est.sampled,
+                est.total,
+                est.tokens,
+                args.lang.code()
+            );
+        }
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let json = serde_json::json!({
+                "score": est.score,
+                "tokens": est.tokens,
+                "lang": args.lang.code(),
+                "dict": dict_name,
+                "pipeline": opts.pipeline.names(),
+                "estimate": est.to_json(),
+            });
+            println!("{json}");
+        }
     }
+    Ok(())
+}
 
-    let max_count = items.iter().map(|(_, c)| *c).max().unwrap_or(1);
-    let max_count_f = max_count as f64;
+/// Пишет ответ демона одной строкой и сразу сбрасывает; STDOUT общий с потоками перезагрузки
+fn daemon_respond(resp: &serde_json::Value) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    writeln!(out, "{resp}")?;
+    out.flush()
+}
 
-    let mut map = HashMap::with_capacity(items.len());
-    for (w, c) in items {
-        let weight = (c as f64) / max_count_f; // в [0,1], максимум=1.0
-        map.insert(w, weight);
+/// Команда демона {"cmd": "status"} или {"cmd": "reload"}. Перезагрузка идёт в отдельном потоке:
+/// её ответ (или ошибка, при которой остаётся прежний словарь) приходит позже, с тем же "id",
+/// а запросы тем временем оцениваются по прежнему словарю. Одновременно идёт не больше одной
+/// перезагрузки: на следующую, пока прежняя не закончилась, отвечается ошибкой «уже идёт».
+fn handle_daemon_command(
+    cmd: &serde_json::Value,
+    id: serde_json::Value,
+    dicts: &Arc<DaemonDict>,
+    reload: &mut Option<std::thread::JoinHandle<()>>,
+) -> Option<serde_json::Value> {
+    match cmd.as_str() {
+        Some("status") => {
+            let mut resp = dicts.status_json(&dicts.current());
+            resp["id"] = id;
+            Some(resp)
+        }
+        Some("reload") if !dicts.begin_reload() => {
+            Some(serde_json::json!({ "id": id, "error": Msg::DaemonReloadBusy.to_string() }))
+        }
+        Some("reload") => {
+            // Прежняя перезагрузка уже подменила словарь и разве что дописывает ответ: её ответ
+            // выйдет раньше ответа новой
+            if let Some(previous) = reload.take() {
+                let _ = previous.join();
+            }
+            let dicts = Arc::clone(dicts);
+            *reload = Some(std::thread::spawn(move || {
+                let mut resp = match dicts.reload() {
+                    Ok(active) => {
+                        let mut resp = dicts.status_json(&active);
+                        resp["reloaded"] = true.into();
+                        resp
+                    }
+                    Err(e) => serde_json::json!({ "error": i18n::describe_anyhow(&e) }),
+                };
+                resp["id"] = id;
+                // Клиент мог уже закрыть STDOUT: ответить некому
+                let _ = daemon_respond(&resp);
+            }));
+            None
+        }
+        _ => Some(serde_json::json!({ "id": id, "error": Msg::DaemonUnknownCmd(&cmd.to_string()).to_string() })),
     }
-    Ok(map)
 }
 
-fn read_input_text(path: &Option<PathBuf>) -> Result<String> {
-    let mut buf = String::new();
-    match path {
-        Some(p) => {
-            let mut f = File::open(p)
-                .with_context(|| format!("Не удалось открыть входной текст: {}", p.display()))?;
-            f.read_to_string(&mut buf)?;
+/// Обрабатывает один запрос демона и возвращает JSON-ответ (успешный или с ошибкой);
+/// None — ответ придёт позже (перезагрузка словаря).
+fn handle_daemon_request(
+    line: &str,
+    dicts: &Arc<DaemonDict>,
+    defaults: &ScoreOptions,
+    reload: &mut Option<std::thread::JoinHandle<()>>,
+) -> Option<serde_json::Value> {
+    let req: serde_json::Value = match serde_json::from_str(line) {
+        Ok(v) => v,
+        Err(e) => {
+            return Some(serde_json::json!({ "id": null, "error": Msg::DaemonBadJson(&e.to_string()).to_string() }))
         }
+    };
+    let id = req.get("id").cloned().unwrap_or(serde_json::Value::Null);
+    if let Some(cmd) = req.get("cmd") {
+        return handle_daemon_command(cmd, id, dicts, reload);
+    }
+    Some(score_daemon_request(&req, id, &dicts.current(), defaults))
+}
+
+/// Оценка текста из запроса демона
+fn score_daemon_request(
+    req: &serde_json::Value,
+    id: serde_json::Value,
+    active: &ActiveDict,
+    defaults: &ScoreOptions,
+) -> serde_json::Value {
+    let text = match req.get("text").and_then(|t| t.as_str()) {
+        Some(t) => t,
         None => {
-            io::stdin()
-                .read_to_string(&mut buf)
-                .context("Не удалось прочитать текст из STDIN")?;
+            return serde_json::json!({ "id": id, "error": Msg::DaemonNoText.to_string() })
+        }
+    };
+    let dict = &active.dict;
+    let mut opts = defaults.clone();
+    opts.thresholds = active.thresholds;
+    opts.top_text_words = match req.get("top_text_words") {
+        None | Some(serde_json::Value::Null) => defaults.top_text_words,
+        Some(v) => match v.as_u64() {
+            Some(n) => Some(n as usize),
+            None => {
+                return serde_json::json!({
+                    "id": id,
+                    "error": Msg::DaemonBadTopTextWords.to_string()
+                })
+            }
+        },
+    };
+
+    // Предупреждения подготовки нужны и без оценки: из них видно, что все слова пропущены
+    // или что текст на другом языке
+    let mismatch = language_mismatch(text, opts.lang);
+    let scored = prepare_text(text, Some(dict), &opts).and_then(|mut prepared| {
+        prepared.warnings.splice(0..0, mismatch);
+        score_prepared(&prepared, dict, &opts).map(|report| (report, prepared.warnings))
+    });
+    let (report, warnings) = match scored {
+        Ok(scored) => scored,
+        Err(e @ readability::Error::InputTooLarge(_)) => return too_large_json(id, &e),
+        Err(e @ readability::Error::Timeout(_)) => return timeout_json(id, &e),
+        Err(e) => return serde_json::json!({ "id": id, "error": i18n::describe(&e) }),
+    };
+    let tokens = report.as_ref().map_or(0, |r| r.tokens);
+    let mut resp = match report {
+        _ if opts.insufficient(tokens) => insufficient_json(tokens, opts.lang),
+        Some(report) => report_json(&report, opts.lang),
+        None => return serde_json::json!({ "id": id, "error": i18n::no_words(&warnings) }),
+    };
+    resp["id"] = id;
+    resp
+}
+
+/// Ответ демона на слишком большой запрос; "status" — как HTTP 413 Payload Too Large
+fn too_large_json(id: serde_json::Value, e: &readability::Error) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "error": i18n::describe(e),
+        "reason": "input_too_large",
+        "status": 413,
+    })
+}
+
+/// Ответ демона на запрос, не уложившийся в --timeout; "status" — как HTTP 503 Service Unavailable
+fn timeout_json(id: serde_json::Value, e: &readability::Error) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "error": i18n::describe(e),
+        "reason": "timeout",
+        "status": 503,
+    })
+}
+
+/// Цикл демона: один JSON-запрос на строку STDIN, один JSON-ответ на строку STDOUT.
+/// Ответ сбрасывается сразу, чтобы клиент не ждал заполнения буфера; EOF завершает работу.
+/// Строка длиннее `defaults.max_input_bytes` в память целиком не читается: остаток пропускается,
+/// а в ответ идёт ошибка "input_too_large". На EOF демон дожидается начатых перезагрузок.
+fn run_daemon(dicts: Arc<DaemonDict>, defaults: &ScoreOptions) -> Result<()> {
+    let mut stdin = io::stdin().lock();
+    let mut reload = None;
+    let max = defaults.max_input_bytes.unwrap_or(u64::MAX);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        let read = (&mut stdin)
+            .take(max.saturating_add(1))
+            .read_until(b'\n', &mut buf)
+            .with_context(|| Msg::DaemonStdin.to_string())?;
+        if read == 0 {
+            break;
+        }
+        if buf.last() != Some(&b'\n') && read as u64 > max {
+            skip_line(&mut stdin).with_context(|| Msg::DaemonStdin.to_string())?;
+            let e = readability::Error::InputTooLarge(InputLimit::Bytes(max));
+            daemon_respond(&too_large_json(serde_json::Value::Null, &e))?;
+            continue;
         }
+        let line = String::from_utf8_lossy(&buf);
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(resp) = handle_daemon_request(&line, &dicts, defaults, &mut reload) {
+            daemon_respond(&resp)?;
+        }
+    }
+    if let Some(reload) = reload {
+        let _ = reload.join();
     }
-    Ok(buf)
+    Ok(())
 }
 
-fn tokenize_english_words(text: &str) -> Vec<String> {
-    // Слова: последовательности латинских букв; апострофы внутри слов допускаем (can't, I'm)
-    // Всё в нижнем регистре
-    let re = Regex::new(r"[A-Za-z]+(?:'[A-Za-z]+)?").unwrap();
-    re.find_iter(text)
-        .map(|m| m.as_str().to_ascii_lowercase())
-        .collect()
+/// Пропускает поток до конца текущей строки включительно, не накапливая его
+fn skip_line(input: &mut impl BufRead) -> io::Result<()> {
+    loop {
+        let chunk = input.fill_buf()?;
+        if chunk.is_empty() {
+            return Ok(());
+        }
+        match chunk.iter().position(|&b| b == b'\n') {
+            Some(i) => {
+                input.consume(i + 1);
+                return Ok(());
+            }
+            None => {
+                let n = chunk.len();
+                input.consume(n);
+            }
+        }
+    }
 }
 
-fn compute_readability(
-    tokens: &[String],
-    dict_weights: &HashMap<String, f64>,
-    top_text_words: Option<usize>,
-) -> Option<f64> {
-    let iter = tokens.iter();
-    let iter = if let Some(n) = top_text_words {
-        Box::new(iter.take(n)) as Box<dyn Iterator<Item = &String>>
+fn main() {
+    let raw: Vec<std::ffi::OsString> = std::env::args_os().collect();
+    let raw_len = raw.len();
+    let (ui, argv) = i18n::init(raw);
+    // i18n::init убирает распознанный --lang-ui из аргументов
+    let ui_source = if argv.len() != raw_len {
+        "command_line"
+    } else if std::env::var_os("LANG").is_some() {
+        "env:LANG"
     } else {
-        Box::new(iter) as Box<dyn Iterator<Item = &String>>
+        "default"
     };
+    let matches = i18n::localize_command(Args::command(), ui).get_matches_from(argv);
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if args.check_config {
+        std::process::exit(check_config(&args, &matches, (ui, ui_source)));
+    }
+    if let Err(e) = run(args) {
+        eprintln!("{}: {}", Msg::ErrorPrefix, i18n::describe_anyhow(&e));
+        std::process::exit(1);
+    }
+    if let Some(n) = warnings::denied() {
+        eprintln!("{}: {}", Msg::ErrorPrefix, Msg::DeniedWarnings(n));
+        std::process::exit(1);
+    }
+}
+
+/// Несколько путей или каталог — режим корпуса
+fn is_corpus(args: &Args) -> bool {
+    args.text_paths.len() > 1 || args.text_paths.iter().any(|p| p.is_dir())
+}
+
+fn walk_options(args: &Args) -> corpus::WalkOptions {
+    corpus::WalkOptions {
+        no_ignore: args.no_ignore,
+        exclude: args.exclude.clone(),
+    }
+}
 
-    let mut sum = 0.0f64;
-    let mut cnt = 0usize;
+/// Проверки сочетаний флагов, которые не выразить ограничениями clap. Общие для обычного запуска
+/// и --check-config, чтобы они не расходились; тексты не читаются, пути --text только
+/// проверяются на каталоги (режим корпуса).
+fn validate_args(args: &Args) -> Result<()> {
+    if args.template.is_some() && args.format != OutputFormat::Plain {
+        bail!("{}", Msg::TemplatePlainOnly);
+    }
+    if args.reproducible && args.seed.is_none() {
+        let sampling = [("--bootstrap", args.bootstrap.is_some()), ("--estimate", args.estimate)];
+        if let Some((flag, _)) = sampling.iter().find(|(_, used)| *used) {
+            bail!("{}", Msg::ReproducibleNeedsSeed(flag));
+        }
+    }
+    if args.self_relative && !is_corpus(args) {
+        bail!("{}", Msg::SelfRelativeCorpusOnly);
+    }
+    // Демон и --watch не доходят до проверок режима одного входа и корпуса
+    if args.daemon {
+        return Ok(());
+    }
+    if args.watch {
+        if args.text_paths.len() > 1 || args.text_paths[0].is_dir() {
+            bail!("{}", Msg::WatchSingleInput);
+        }
+        return Ok(());
+    }
+    let unsupported = if is_corpus(args) {
+        vec![
+            (args.append_score, Msg::AppendScoreSingleInput),
+            (args.annotate, Msg::AnnotateSingleInput),
+            (args.estimate, Msg::EstimateSingleInput),
+            (args.profile_out.is_some(), Msg::ProfileSingleInput),
+            (args.export_features.is_some(), Msg::FeaturesSingleInput),
+        ]
+    } else {
+        vec![
+            (args.results_out.is_some(), Msg::ResultsOutCorpusOnly),
+            (args.cache_scores.is_some(), Msg::CacheCorpusOnly),
+        ]
+    };
+    if let Some((_, msg)) = unsupported.into_iter().find(|(used, _)| *used) {
+        bail!("{msg}");
+    }
+    Ok(())
+}
+
+/// Параметры оценки из флагов; читает --contractions-file и --lang-data
+fn score_options(args: &Args) -> Result<ScoreOptions> {
+    let pipeline = args
+        .pipeline
+        .clone()
+        .unwrap_or_else(|| TokenPipeline::from_flags(
+            !args.no_directives,
+            !args.no_sanitize,
+            !args.no_dehyphenate,
+            args.skip_quotes,
+            args.expand_contractions,
+        ));
+    let contractions = contractions_table(&pipeline, args.contractions_file.as_deref())?;
+    let mut opts = ScoreOptions {
+        lang: args.lang,
+        lang_data: match &args.lang_data {
+            Some(dir) => Some(Arc::new(LangData::load(dir, args.lang)?)),
+            None => None,
+        },
+        top_text_words: args.top_text_words,
+        top_text_sentences: args.top_text_sentences,
+        contractions,
+        keep_acronyms: args.keep_acronyms,
+        acronym_weight: args.acronym_weight,
+        pipeline,
+        typographic: !args.no_typographic_normalization,
+        explain: args.explain,
+        explain_spans: args.explain_spans,
+        bootstrap: args.bootstrap.map(|resamples| bootstrap::BootstrapOptions {
+            resamples,
+            seed: args.seed.unwrap_or_else(rng::seed_from_time),
+        }),
+        min_tokens: args.min_tokens,
+        max_input_bytes: args.max_input_bytes.map(|n| n as u64),
+        max_tokens: args.max_tokens,
+        max_token_len: args.max_token_len,
+        timeout: args.timeout.map(Duration::from_secs_f64),
+        oov_weights: args
+            .classify_oov
+            .then(|| args.oov_weights.unwrap_or_default()),
+        aggregate: args
+            .aggregate_by
+            .filter(|&by| by != AggregateBy::Document)
+            .map(|by| AggregateOptions { by, function: args.aggregate }),
+        metrics: dedup_metrics(&args.metrics),
+        max_clauses: args.max_clauses,
+        overlap: args.overlap.clone(),
+        overlap_delta: args.overlap_delta,
+        thresholds: None,
+        tertiles: args.tertile_report,
+        locale: args.locale,
+        suggest: None,
+    };
+    // Синонимы нормализуются той же цепочкой, поэтому читаются уже по готовым параметрам
+    if let Some(path) = args.synonyms.as_deref().filter(|_| args.suggest) {
+        opts.suggest = Some(SuggestOptions {
+            synonyms: Arc::new(Synonyms::load(path, &opts)?),
+            threshold: args.suggest_threshold,
+            limit: args.suggest_limit,
+        });
+    }
+    Ok(opts)
+}
+
+/// Файлы словаря в порядке чтения: компоненты --dict-blend или один словарь
+fn dict_files(args: &Args, dict_path: &Path) -> Vec<PathBuf> {
+    match &args.dict_blend {
+        Some(blend) => blend.0.iter().map(|c| c.path.clone()).collect(),
+        None => vec![dict_path.to_path_buf()],
+    }
+}
+
+/// Объект "provenance" без сведений о входе (--with-provenance): файлы словаря с SHA-256,
+/// число слов, версия и отпечаток настроек. Вход добавляет вызывающий: один текст или
+/// каждый файл корпуса.
+fn provenance_json(
+    args: &Args,
+    dict_path: &Path,
+    dict_info: &DictLoadInfo,
+    dict: &FrequencyDict,
+    dict_opts: DictOptions,
+    opts: &ScoreOptions,
+    boilerplate: Option<&corpus::Boilerplate>,
+) -> Result<serde_json::Value> {
+    let files: Vec<serde_json::Value> = dict_files(args, dict_path)
+        .iter()
+        .zip(&dict_info.sha256)
+        .map(|(path, sha256)| serde_json::json!({ "path": path.display().to_string(), "sha256": sha256 }))
+        .collect();
+    Ok(serde_json::json!({
+        "dict": { "files": files, "entries": dict.len() },
+        "version": env!("CARGO_PKG_VERSION"),
+        "config_fingerprint": config_fingerprint(args, dict_opts, opts, boilerplate)?.hex(),
+    }))
+}
+
+fn dict_path(args: &Args) -> PathBuf {
+    args.dict_path.clone().unwrap_or_else(|| args.lang.default_dict_path())
+}
+
+fn dict_options(args: &Args) -> DictOptions {
+    DictOptions {
+        top_k: args.top_dict_entries,
+        coverage_target: args.dict_coverage_target,
+        duplicates: args.dict_duplicates,
+        weighting: args.weight,
+        // Ранги нужны пересечению --overlap
+        retain_stats: !args.overlap.is_empty(),
+        lenient: args.dict_lenient,
+        locale: args.locale,
+    }
+}
+
+/// --check-config: проверки обычного запуска без чтения текстов. Возвращает код выхода:
+/// 0 — конфигурация напечатана в STDOUT, 2 — ошибка напечатана в STDERR (как у ошибок clap)
+fn check_config(args: &Args, matches: &ArgMatches, ui: (i18n::UiLang, &str)) -> i32 {
+    match effective_config(args, matches, ui) {
+        Ok(config) => {
+            println!("{}", serde_json::to_string_pretty(&config).expect("JSON из serde_json::Value"));
+            0
+        }
+        Err(e) => {
+            eprintln!("{}: {}", Msg::ErrorPrefix, i18n::describe_anyhow(&e));
+            2
+        }
+    }
+}
 
-    for w in iter {
-        let wgt = dict_weights.get(w).copied().unwrap_or(0.0);
-        sum += wgt;
-        cnt += 1;
+/// Действующая конфигурация: значения всех флагов с источником (command_line, default, env),
+/// язык интерфейса, режим, словарь и цепочка нормализации. Файлы --contractions-file и
+/// --lang-data читаются целиком, словари — только до первой записи.
+fn effective_config(args: &Args, matches: &ArgMatches, ui: (i18n::UiLang, &str)) -> Result<serde_json::Value> {
+    validate_args(args)?;
+    let clock = history_clock(args.reproducible)?;
+    let opts = score_options(args)?;
+    let dict = match &args.dict_blend {
+        _ if args.self_relative => serde_json::json!({ "source": "self-relative" }),
+        Some(blend) => {
+            for c in &blend.0 {
+                dict::check_dict_header(&c.path)?;
+            }
+            serde_json::json!({ "source": "blend", "blend": blend.label() })
+        }
+        None => {
+            let path = dict_path(args);
+            dict::check_dict_header(&path)?;
+            serde_json::json!({ "source": "file", "path": path.display().to_string() })
+        }
+    };
+    let mode = match () {
+        _ if args.daemon => "daemon",
+        _ if args.watch => "watch",
+        _ if is_corpus(args) => "corpus",
+        _ => "single",
+    };
+    let mut options = serde_json::Map::new();
+    for arg in Args::command().get_arguments() {
+        let id = arg.get_id().as_str();
+        let (Some(raw), Some(source)) = (matches.get_raw(id), matches.value_source(id)) else {
+            continue;
+        };
+        let values: Vec<String> = raw.map(|v| v.to_string_lossy().into_owned()).collect();
+        let value = match values.as_slice() {
+            [one] if !matches!(arg.get_action(), ArgAction::Append) => serde_json::json!(one),
+            _ => serde_json::json!(values),
+        };
+        let source = match source {
+            ValueSource::CommandLine => "command_line",
+            ValueSource::EnvVariable => "env",
+            _ => "default",
+        };
+        options.insert(id.to_string(), serde_json::json!({ "value": value, "source": source }));
     }
+    let history_time = match clock {
+        history::Clock::System => serde_json::json!("system"),
+        history::Clock::Fixed(t) => serde_json::json!({ "source_date_epoch": t }),
+        history::Clock::Omitted => serde_json::json!("omitted"),
+    };
+    Ok(serde_json::json!({
+        "mode": mode,
+        "ui_lang": {
+            "value": ui.0.to_possible_value().map(|v| v.get_name().to_string()),
+            "source": ui.1,
+        },
+        "dict": dict,
+        "pipeline": opts.pipeline.names(),
+        "history_time": history_time,
+        "options": options,
+    }))
+}
 
-    if cnt == 0 { None } else { Some(sum / (cnt as f64)) }
+/// Время записей --history: при --reproducible — SOURCE_DATE_EPOCH или никакого
+fn history_clock(reproducible: bool) -> Result<history::Clock> {
+    if !reproducible {
+        return Ok(history::Clock::System);
+    }
+    match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(v) => v
+            .trim()
+            .parse()
+            .map(history::Clock::Fixed)
+            .with_context(|| Msg::SourceDateEpochInvalid(&v).to_string()),
+        Err(_) => Ok(history::Clock::Omitted),
+    }
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+fn run(args: Args) -> Result<()> {
+    let started = Instant::now();
+
+    match &args.command {
+        Some(Command::WordFreq(cmd)) => return word_freq::run(cmd),
+        Some(Command::Dict(cmd)) => return dict_cmd::run(cmd),
+        Some(Command::History(cmd)) => return history::run(cmd),
+        Some(Command::Word(cmd)) => return word_cmd::run(cmd),
+        None => {}
+    }
+    validate_args(&args)?;
+    warnings::init(warnings::Policy {
+        json: matches!(args.format, OutputFormat::Json | OutputFormat::Jsonl) && !args.daemon,
+        deny: args.deny_warnings,
+        allow: args.allow.clone(),
+    });
+    let clock = history_clock(args.reproducible)?;
+    let opts = score_options(&args)?;
+    let dict_path = dict_path(&args);
+    let dict_opts = dict_options(&args);
+    let corpus_counts = if args.self_relative {
+        let files = corpus::collect_inputs(&args.text_paths, &walk_options(&args))?;
+        let counts = self_relative::count_corpus(&files, &opts, dict_opts);
+        if let Some(path) = &args.save_dict {
+            counts.save(path)?;
+        }
+        Some(counts)
+    } else {
+        None
+    };
+    let (dict, dict_info) = match (&corpus_counts, &args.dict_blend) {
+        (Some(counts), _) => counts.dict()?,
+        (None, Some(blend)) => dict::load_blended_dict(&blend.0, dict_opts)?,
+        (None, None) => dict::load_frequency_dict(&dict_path, dict_opts)?,
+    };
+    let dict = if args.quantize_weights.is_some() { dict.quantized() } else { dict };
+    let opts = match args.auto_thresholds {
+        true => match Thresholds::derive(&dict, &opts) {
+            Some(t) => ScoreOptions { thresholds: Some(t), ..opts },
+            None => bail!("{}", Msg::AutoThresholdsUnavailable),
+        },
+        false => opts,
+    };
+    let dict_name = match &args.dict_blend {
+        _ if args.self_relative_exclude_self => "self-relative:exclude-self".to_string(),
+        _ if args.self_relative => "self-relative".to_string(),
+        Some(blend) => blend.label(),
+        None => dict_path.display().to_string(),
+    };
+    let dict_timings = dict_info.timings;
+    warnings::emit_run(dict_info.warnings());
+    if args.verbose {
+        let msg = Msg::DictVerbose {
+            entries: dict_info.entries,
+            duplicates: dict_info.duplicates,
+            distinct: dict.len(),
+        };
+        eprintln!("{msg}");
+        if let Some(coverage) = dict_info.coverage {
+            eprintln!("{}", Msg::DictCoverage { entries: dict_info.entries, coverage });
+        }
+        if let Some(bits) = args.quantize_weights {
+            eprintln!("{}", Msg::DictQuantized { bits, max_error: dict::QUANTIZE_MAX_ERROR });
+        }
+        eprintln!("{}", Msg::VerbosePipeline(&opts.pipeline.names()));
+        if let Some(t) = &opts.thresholds {
+            let bounds: Vec<String> = t.bounds.iter().map(|b| format!("{b:.6}")).collect();
+            eprintln!("{}", Msg::VerboseThresholds { bounds: &bounds.join(" "), sentences: t.sentences });
+        }
+    }
+    if args.daemon {
+        let mut opts = opts;
+        opts.max_input_bytes.get_or_insert(DAEMON_MAX_INPUT_BYTES);
+        opts.max_tokens.get_or_insert(DAEMON_MAX_TOKENS);
+        let source = DictSource {
+            path: dict_path.clone(),
+            blend: args.dict_blend.clone(),
+            opts: dict_opts,
+            quantize: args.quantize_weights.is_some(),
+            name: dict_name.clone(),
+            thresholds: args.auto_thresholds.then(|| opts.clone()),
+        };
+        return run_daemon(Arc::new(DaemonDict::new(dict, source)), &opts);
+    }
+
+    if args.watch {
+        return watch::run(&args.text_paths[0], &dict, &opts, args.format, args.history.as_deref());
+    }
 
-    let dict = load_frequency_dict(&args.dict_path, args.top_dict_entries)?;
-    let text = read_input_text(&args.text_path)?;
-    let tokens = tokenize_english_words(&text);
+    if is_corpus(&args) {
+        let files = corpus::collect_inputs(&args.text_paths, &walk_options(&args))?;
+        // Повторы ищутся только между файлами: при одном входе проход не нужен
+        let boilerplate = (args.dedupe_lines && files.len() > 1)
+            .then(|| corpus::Boilerplate::scan(&files, args.dedupe_threshold));
+        let checkpoint = match &args.results_out {
+            Some(path) => {
+                let fingerprint = cache_fingerprint(&args, &dict_path, dict_opts, &opts, boilerplate.as_ref())?;
+                Some(checkpoint::Checkpoint::open(path, args.resume, &fingerprint.hex(), args.lang)?)
+            }
+            None => None,
+        };
+        // Файлы, не изменившиеся с прошлого запуска, заново не оцениваются; с --with-provenance —
+        // только если у прежней записи есть "provenance"
+        let reused: Vec<Option<corpus::FileResult>> = files
+            .iter()
+            .map(|f| {
+                checkpoint
+                    .as_ref()
+                    .and_then(|c| c.reuse(f))
+                    .filter(|r| !args.with_provenance || r.provenance.is_some())
+            })
+            .collect();
+        let pending: Vec<PathBuf> = files
+            .iter()
+            .zip(&reused)
+            .filter(|(_, r)| r.is_none())
+            .map(|(f, _)| f.clone())
+            .collect();
+        let cache = match &args.cache_scores {
+            Some(path) => {
+                let fingerprint = cache_fingerprint(&args, &dict_path, dict_opts, &opts, boilerplate.as_ref())?;
+                Some(cache::ScoreCache::open(
+                    path,
+                    fingerprint,
+                    !args.no_cache_read,
+                    !args.no_cache_write,
+                    &opts,
+                ))
+            }
+            None => None,
+        };
+        let binary = (!args.force_text).then(|| corpus::BinaryFilter::new(&args.binary_extensions));
+        let provenance = args
+            .with_provenance
+            .then(|| provenance_json(&args, &dict_path, &dict_info, &dict, dict_opts, &opts, boilerplate.as_ref()))
+            .transpose()?;
+        let job = corpus::CorpusJob {
+            dict: &dict,
+            opts: &opts,
+            jobs: args.jobs,
+            strict_lang: args.strict_lang,
+            boilerplate: boilerplate.as_ref(),
+            checkpoint: checkpoint.as_ref(),
+            cache: cache.as_ref(),
+            leave_one_out: corpus_counts.as_ref().filter(|_| args.self_relative_exclude_self),
+            binary: binary.as_ref(),
+            provenance: provenance.as_ref(),
+        };
+        let mut fresh = corpus::score_files(&pending, &job).into_iter();
+        let results: Vec<corpus::FileResult> = reused
+            .into_iter()
+            .map(|r| r.or_else(|| fresh.next()).expect("для каждого неоценённого файла есть результат"))
+            .collect();
+        if let (Some(c), Some(path)) = (checkpoint, &args.results_out) {
+            c.finish(path)?;
+        }
+        if let Some(c) = cache {
+            if args.verbose || args.timings {
+                let (hits, misses) = c.stats();
+                eprintln!("{}", Msg::CacheStats { hits, misses });
+            }
+            c.save()?;
+        }
+        if let Some(h) = &args.history {
+            history::append_results(h, &results, clock)?;
+        }
+        if args.verbose {
+            corpus::print_verbose(&results, &opts);
+        }
+        let timings = args.timings.then_some((dict_timings, started.elapsed()));
+        let summary = match args.summary {
+            Some(SummaryMode::Always) => Some(corpus::summarize(&results)),
+            Some(SummaryMode::Auto) if files.len() > 1 => Some(corpus::summarize(&results)),
+            _ => None,
+        };
+        // Статистика выше посчитана по всем файлам; ранжирование влияет только на вывод
+        let shown = match args.sort {
+            Some(order) => corpus::rank_results(&results, order, args.top, args.bottom),
+            None => results.clone(),
+        };
+        corpus::print_results(&shown, summary.as_ref(), timings, args.format, args.lang, args.template.as_ref(), args.tertile_report)?;
+        if results.iter().any(|r| r.outcome.is_error()) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
-    let score = compute_readability(&tokens, &dict, args.top_text_words)
-        .ok_or_else(|| anyhow::anyhow!("Не найдено ни одного слова для оценки"))?;
+    if args.estimate {
+        return run_estimate(&args, &dict, &opts, &dict_name);
+    }
+    let text_path = args.text_paths.first().map(PathBuf::as_path);
+    let read_started = Instant::now();
+    let (raw_text, input_sha256) = match args.with_provenance {
+        true => read_input_hashed(text_path, opts.max_input_bytes).map(|(text, hash)| (text, Some(hash)))?,
+        false => (read_input_limited(text_path, opts.max_input_bytes)?, None),
+    };
+    let read_time = read_started.elapsed();
+    // Старый трейлер не должен влиять ни на оценку, ни на итоговый текст
+    let text = if args.append_score {
+        strip_score_trailer(&raw_text)
+    } else {
+        raw_text.as_str()
+    };
+    let mismatch = check_text_language(text, args.lang, args.strict_lang)?;
 
-    // Печатаем только число — удобно для пайпов и автоматизации
-    println!("{:.6}", score);
+    // Предупреждение о языке — среди предупреждений подготовки: без слов оно объясняет почему
+    let mut prepared = prepare_text(text, Some(&dict), &opts)?;
+    prepared.warnings.splice(0..0, mismatch);
+    let report = score_prepared(&prepared, &dict, &opts)?;
+    let tokens = report.as_ref().map_or(0, |r| r.tokens);
+    if opts.insufficient(tokens) {
+        exit_insufficient(args.format, tokens, args.lang, &dict_name);
+    }
+    let mut report = report.with_context(|| i18n::no_words(&prepared.warnings))?;
+    report.timings.read = read_time;
+    if let Some(h) = &args.history {
+        let input = text_path.map_or_else(|| "-".to_string(), |p| p.display().to_string());
+        history::append(h, &history::record(&input, &raw_text, &report, clock))?;
+    }
+    warnings::emit(&report.warnings, None);
+    if args.verbose {
+        for line in verbose_lines(&report, &opts) {
+            eprintln!("{line}");
+        }
+    }
+    let mut anomalies = Vec::new();
+    if let Some(path) = &args.profile_out {
+        let wopts = WindowOptions {
+            size: args.window,
+            step: args.window_step.unwrap_or(args.window),
+        };
+        let smooth = args.smooth.map(|k| (k, args.flag_anomalies));
+        anomalies = write_profile(path, &prepared, &dict, &opts, wopts, smooth)?;
+    }
+    if let Some(path) = &args.export_features {
+        write_features(path, &prepared, &dict, &opts, !args.no_token_text)?;
+    }
+
+    if args.annotate {
+        let aopts = AnnotateOptions {
+            threshold: args.annotate_threshold,
+            precision: args.annotate_precision,
+        };
+        let annotated = annotate::annotate(&prepared, &dict, &opts, aopts);
+        match &args.output_path {
+            Some(out) => std::fs::write(out, &annotated).with_context(|| Msg::FileWrite(out).to_string())?,
+            None => io::stdout().write_all(annotated.as_bytes())?,
+        }
+        return Ok(());
+    }
+
+    if args.append_score {
+        let trailer = args.trailer_style.render(&format!(
+            "readability: {:.6} dict={} tokens={}",
+            report.score,
+            dict_name,
+            report.tokens
+        ));
+        let mut annotated = String::with_capacity(text.len() + trailer.len() + 2);
+        annotated.push_str(text);
+        if !annotated.is_empty() && !annotated.ends_with('\n') {
+            annotated.push('\n');
+        }
+        annotated.push_str(&trailer);
+        annotated.push('\n');
+
+        match (&args.output_path, args.in_place) {
+            (Some(out), _) => std::fs::write(out, &annotated)
+                .with_context(|| Msg::FileWrite(out).to_string())?,
+            (None, true) => {
+                // clap гарантирует наличие --text вместе с --in-place
+                let path = text_path.expect("--in-place требует --text");
+                write_file_atomically(path, &annotated)?;
+            }
+            (None, false) => io::stdout().write_all(annotated.as_bytes())?,
+        }
+        return Ok(());
+    }
+
+    match args.format {
+        // Печатаем только число — удобно для пайпов и автоматизации
+        OutputFormat::Plain => {
+            match (report.metrics.as_slice(), report.bootstrap) {
+                _ if args.template.is_some() => {
+                    let name = text_path.unwrap_or(Path::new("-"));
+                    let template = args.template.as_ref().expect("проверено выше");
+                    println!("{}", template.render(name, 0, &report));
+                }
+                ([_, _, ..], _) => {
+                    for (m, v) in &report.metrics {
+                        println!("{}\t{}", m.name(), metric_value(*v));
+                    }
+                }
+                // Единственная метрика, кроме freq, печатается голым числом, как и оценка
+                ([(m, v)], _) if *m != Metric::Freq => println!("{}", metric_value(*v)),
+                (_, Some(b)) => println!("{:.6} {:.6} {:.6}", report.score, b.lo, b.hi),
+                (_, None) => println!("{:.6}", report.score),
+            }
+            for o in &report.overlap {
+                let next: Vec<&str> = o.next.iter().map(|m| m.word.as_str()).collect();
+                println!(
+                    "overlap@{}\twithin={}\tbeyond={}\toov={}\tdistinct={}\tnext={}",
+                    o.k,
+                    o.within,
+                    o.beyond,
+                    o.oov,
+                    o.distinct,
+                    next.join(",")
+                );
+            }
+            if let Some(label) = report.label() {
+                println!("label\t{}", label.name());
+            }
+            if let Some(t) = &report.tertiles {
+                let [front, middle, back] = t.scores.map(|s| s.map_or_else(|| "-".to_string(), |s| format!("{s:.6}")));
+                println!("tertiles\tfront={front}\tmiddle={middle}\tback={back}\tspread={:.6}", t.spread());
+            }
+            for s in &report.suggestions {
+                println!("suggest\t{}", s.display());
+            }
+            for a in &anomalies {
+                println!(
+                    "anomaly\twindow={}\tstart_token={}\tstart_char={}\tscore={:.6}\tsmoothed={:.6}\tz={:.2}\t{}",
+                    a.row.index, a.row.start_token, a.row.start_char, a.row.score, a.smoothed, a.z, a.snippet
+                );
+            }
+            report.write_explain(&mut io::stdout().lock())?;
+        }
+        OutputFormat::Csv => match &report.tertiles {
+            Some(t) => {
+                let [front, middle, back] = t.scores.map(|s| s.map_or_else(String::new, |s| s.to_string()));
+                println!("score,tokens,lang,front,middle,back,spread");
+                println!("{},{},{},{front},{middle},{back},{}", report.score, report.tokens, args.lang.code(), t.spread());
+            }
+            None => {
+                println!("score,tokens,lang");
+                println!("{},{},{}", report.score, report.tokens, args.lang.code());
+            }
+        },
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let mut json = report_json(&report, args.lang);
+            let all: Vec<Warning> = warnings::run_warnings().into_iter().chain(report.warnings.iter().cloned()).collect();
+            if let Some(all) = warnings::to_json(&all) {
+                json["warnings"] = all;
+            }
+            json["dict"] = serde_json::Value::from(dict_name.as_str());
+            json["pipeline"] = serde_json::Value::from(opts.pipeline.names());
+            if args.flag_anomalies.is_some() {
+                json["anomalies"] = anomalies.iter().map(windows::Anomaly::to_json).collect();
+            }
+            if let Some(input_sha256) = &input_sha256 {
+                let mut provenance = provenance_json(&args, &dict_path, &dict_info, &dict, dict_opts, &opts, None)?;
+                provenance["input"] = serde_json::json!({
+                    "path": text_path.map_or_else(|| "stdin".to_string(), |p| p.display().to_string()),
+                    "sha256": input_sha256,
+                });
+                json["provenance"] = provenance;
+            }
+            if args.timings {
+                let mut t = dict_timings.to_json();
+                let text_t = report.timings.to_json(report.tokens);
+                t.as_object_mut()
+                    .expect("to_json возвращает объект")
+                    .extend(text_t.as_object().expect("to_json возвращает объект").clone());
+                json["timings"] = t;
+            }
+            println!("{}", json);
+        }
+    }
+    if args.timings && matches!(args.format, OutputFormat::Plain | OutputFormat::Csv) {
+        eprintln!("timings:");
+        for line in dict_timings.lines().into_iter().chain(report.timings.lines(report.tokens)) {
+            eprintln!("  {line}");
+        }
+    }
+
+    if args.summary == Some(SummaryMode::Always) {
+        let name = text_path.map_or_else(|| PathBuf::from("-"), Path::to_path_buf);
+        let results = [corpus::FileResult {
+            path: name,
+            outcome: corpus::FileOutcome::Scored(Box::new(report)),
+            deduped: None,
+            reused: false,
+            cached: false,
+            provenance: None,
+        }];
+        corpus::print_summary(&corpus::summarize(&results), args.format)?;
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::error::ErrorKind;
+
+    use super::*;
+
+    /// Разбор и общая проверка флагов, как в `run` и `--check-config`
+    fn check(argv: &[&str]) -> Result<Args> {
+        let args = Args::try_parse_from(std::iter::once("readability").chain(argv.iter().copied()))?;
+        validate_args(&args)?;
+        Ok(args)
+    }
+
+    /// Значение, при котором флаг разбирается сам по себе: из допустимых значений или из
+    /// кандидатов под частые виды значений
+    fn sample_value(flag: &str, arg: &clap::Arg) -> String {
+        let candidates: Vec<String> = arg
+            .get_possible_values()
+            .iter()
+            .map(|v| v.get_name().to_string())
+            .chain(["1", "0.5", "x", "en=1", "a.json:1", "lowercase"].map(String::from))
+            .collect();
+        candidates
+            .into_iter()
+            .find(|v| match Args::command().try_get_matches_from(["readability", &format!("{flag}={v}")]) {
+                Ok(_) => true,
+                Err(e) => !matches!(e.kind(), ErrorKind::InvalidValue | ErrorKind::ValueValidation),
+            })
+            .unwrap_or_else(|| panic!("нет подходящего значения для {flag}"))
+    }
+
+    /// Каждая пара флагов, объявленная несовместимой (`conflicts_with`), отклоняется при разборе
+    #[test]
+    fn every_declared_conflict_is_rejected() {
+        let cmd = Args::command();
+        let argv_of = |arg: &clap::Arg| -> String {
+            let flag = format!("--{}", arg.get_long().expect("у всех флагов есть длинное имя"));
+            if arg.get_action().takes_values() {
+                format!("{flag}={}", sample_value(&flag, arg))
+            } else {
+                flag
+            }
+        };
+        let mut pairs = 0;
+        for arg in cmd.get_arguments() {
+            for other in cmd.get_arg_conflicts_with(arg) {
+                let (a, b) = (argv_of(arg), argv_of(other));
+                let err = Args::try_parse_from(["readability", &a, &b]).err();
+                assert_eq!(err.map(|e| e.kind()), Some(ErrorKind::ArgumentConflict), "{a} {b}");
+                pairs += 1;
+            }
+        }
+        assert!(pairs > 50, "{pairs}");
+    }
+
+    /// Несовместимости, которые проверяет `validate_args`, с точным сообщением
+    #[test]
+    fn validate_args_rejects_incompatible_combinations() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let corpus = ["--text", "a.txt", "--text", "b.txt"];
+        let cases: Vec<(Vec<&str>, String)> = vec![
+            (vec!["--template", "{score}", "--format", "json"], Msg::TemplatePlainOnly.to_string()),
+            (vec!["--reproducible", "--bootstrap"], Msg::ReproducibleNeedsSeed("--bootstrap").to_string()),
+            (vec!["--reproducible", "--estimate"], Msg::ReproducibleNeedsSeed("--estimate").to_string()),
+            (vec!["--self-relative", "--text", "a.txt"], Msg::SelfRelativeCorpusOnly.to_string()),
+            (vec!["--watch", "--text", "a.txt", "--text", "b.txt"], Msg::WatchSingleInput.to_string()),
+            (vec!["--watch", "--text", dir], Msg::WatchSingleInput.to_string()),
+            ([&corpus[..], &["--append-score"]].concat(), Msg::AppendScoreSingleInput.to_string()),
+            ([&corpus[..], &["--annotate"]].concat(), Msg::AnnotateSingleInput.to_string()),
+            ([&corpus[..], &["--estimate"]].concat(), Msg::EstimateSingleInput.to_string()),
+            ([&corpus[..], &["--profile-out", "p.csv"]].concat(), Msg::ProfileSingleInput.to_string()),
+            ([&corpus[..], &["--export-features", "f.csv"]].concat(), Msg::FeaturesSingleInput.to_string()),
+            (vec!["--text", "a.txt", "--results-out", "r.jsonl"], Msg::ResultsOutCorpusOnly.to_string()),
+            (vec!["--text", "a.txt", "--cache-scores", "c.json"], Msg::CacheCorpusOnly.to_string()),
+        ];
+        for (argv, expected) in cases {
+            let err = check(&argv).err().unwrap_or_else(|| panic!("{argv:?} принят"));
+            assert_eq!(err.to_string(), expected, "{argv:?}");
+        }
+    }
+
+    /// Те же флаги по отдельности или в совместимом режиме проходят проверку
+    #[test]
+    fn validate_args_accepts_compatible_combinations() {
+        for argv in [
+            &["--template", "{score}"][..],
+            &["--reproducible", "--bootstrap", "--seed", "1"],
+            &["--self-relative", "--text", "a.txt", "--text", "b.txt"],
+            &["--watch", "--text", "a.txt"],
+            &["--text", "a.txt", "--append-score"],
+            &["--text", "a.txt", "--text", "b.txt", "--results-out", "r.jsonl", "--cache-scores", "c.json"],
+        ] {
+            check(argv).unwrap_or_else(|e| panic!("{argv:?}: {e}"));
+        }
+    }
+}
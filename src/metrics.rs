@@ -0,0 +1,237 @@
+//! Метрики читаемости помимо частотной оценки: Flesch Reading Ease, Gunning Fog и плотность клауз.
+
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+use regex::Regex;
+
+use crate::clauses::ClauseReport;
+use crate::lang_data::SyllableRules;
+use crate::{PreparedText, ScoreOptions, Warning};
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    /// Частотная оценка по словарю (основная)
+    Freq,
+    /// Flesch Reading Ease: 206.835 − 1.015·(слов/предложений) − 84.6·(слогов/слов)
+    Flesch,
+    /// Gunning Fog: 0.4·(слов/предложений + 100·сложных/слов), сложное слово — от трёх слогов
+    Fog,
+    /// Среднее число клауз в предложении по эвристикам `clauses`; для всех языков
+    ClauseDensity,
+}
+
+impl Metric {
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::Freq => "freq",
+            Metric::Flesch => "flesch",
+            Metric::Fog => "fog",
+            Metric::ClauseDensity => "clause-density",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::value_variants().iter().copied().find(|v| v.name() == name)
+    }
+}
+
+/// Почему метрику не удалось посчитать
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetricGap {
+    /// Разбиение на предложения не нашло ни одного предложения
+    NoSentences,
+    /// Для языка нет правил подсчёта слогов (встроены только английские, остальные — из --lang-data)
+    NoSyllableRules,
+}
+
+impl MetricGap {
+    pub fn name(self) -> &'static str {
+        match self {
+            MetricGap::NoSentences => "no_sentences",
+            MetricGap::NoSyllableRules => "no_syllable_rules",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [MetricGap::NoSentences, MetricGap::NoSyllableRules].into_iter().find(|g| g.name() == name)
+    }
+}
+
+/// Счётчики для формул Flesch и Fog
+#[derive(Clone, Copy, Debug, Default)]
+struct TextStats {
+    sentences: usize,
+    words: usize,
+    syllables: usize,
+    complex_words: usize,
+}
+
+fn is_terminator(c: char, typographic: bool) -> bool {
+    matches!(c, '.' | '!' | '?') || (typographic && c == '…')
+}
+
+/// Закрывающие кавычки, прямые и типографские, которые могут стоять после знака конца предложения
+fn is_closing_quote(c: char) -> bool {
+    matches!(c, '"' | '\'' | '”' | '’' | '»')
+}
+
+/// Сокращения с точками (U.S., Ph.D., e.g.): те же последовательности, что токенизатор
+/// держит одним словом при --keep-acronyms, но для букв любого алфавита
+fn abbreviation_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\b(?:\p{L}{1,2}\.){2,}").unwrap())
+}
+
+/// Концы сокращений с точками, после которых предложение всё же кончается: дальше пробел
+/// и слово с заглавной, которое само не сокращение и не аббревиатура (`in the U.S. She left`).
+/// Точки внутри сокращения и сокращение перед строчной буквой (`the U.S. in May`) концом
+/// предложения не считаются. Возвращает диапазоны сокращений и признак конца предложения.
+fn abbreviations(text: &str) -> Vec<(usize, usize, bool)> {
+    abbreviation_regex()
+        .find_iter(text)
+        .map(|m| {
+            let rest = &text[m.end()..];
+            let next = rest.trim_start();
+            let word: &str = next.split(|c: char| !c.is_alphabetic() && c != '.').next().unwrap_or("");
+            let acronym = abbreviation_regex().find(word).is_some_and(|a| a.start() == 0)
+                || (word.chars().count() >= 2 && word.chars().all(|c| !c.is_lowercase()));
+            let ends = rest.starts_with(char::is_whitespace) && word.starts_with(char::is_uppercase) && !acronym;
+            (m.start(), m.end(), ends)
+        })
+        .collect()
+}
+
+/// Возможные концы предложений: сразу после знака . ! ? (с `typographic` — и многоточия …).
+/// Точки сокращений (`abbreviations`) предложение не разрывают.
+/// С `typographic` подряд идущие знаки и закрывающие кавычки за ними входят в предложение,
+/// а прямая речь со словами автора после неё (`"Stop!" he said.`) не разрывается.
+/// Тире предложения не разделяет: « — » в любом режиме лишь граница части предложения.
+fn candidate_ends(text: &str, typographic: bool) -> Vec<usize> {
+    let abbreviations = abbreviations(text);
+    let mut ends = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if !is_terminator(c, typographic) {
+            continue;
+        }
+        if c == '.' {
+            let k = abbreviations.partition_point(|&(_, end, _)| end <= i);
+            if let Some(&(_, end, ends_sentence)) = abbreviations.get(k).filter(|&&(start, _, _)| start <= i) {
+                if i + 1 < end || !ends_sentence {
+                    continue;
+                }
+            }
+        }
+        let mut end = i + c.len_utf8();
+        if !typographic {
+            ends.push(end);
+            continue;
+        }
+        while let Some((j, d)) = chars.next_if(|&(_, d)| is_terminator(d, true)) {
+            end = j + d.len_utf8();
+        }
+        let mut quoted = false;
+        while let Some((j, d)) = chars.next_if(|&(_, d)| is_closing_quote(d)) {
+            end = j + d.len_utf8();
+            quoted = true;
+        }
+        if quoted && text[end..].trim_start().starts_with(char::is_lowercase) {
+            continue;
+        }
+        ends.push(end);
+    }
+    ends
+}
+
+/// Концы предложений (байтовое смещение сразу после завершающего знака или конец текста).
+/// Предложение — фрагмент между знаками конца (подряд идущие считаются одним), в котором
+/// есть хотя бы одна буква; хвост без знака в конце тоже считается предложением.
+/// `typographic` — учитывать многоточие, кавычки после знака и прямую речь (см. `candidate_ends`);
+/// смещения в обоих режимах относятся к исходному тексту.
+pub fn sentence_ends(text: &str, typographic: bool) -> impl Iterator<Item = usize> + '_ {
+    let mut start = 0;
+    candidate_ends(text, typographic)
+        .into_iter()
+        .chain(std::iter::once(text.len()))
+        .filter(move |&end| {
+            let fragment = &text[start..end];
+            start = end;
+            fragment.chars().any(char::is_alphabetic)
+        })
+}
+
+fn count_sentences(text: &str, typographic: bool) -> usize {
+    sentence_ends(text, typographic).count()
+}
+
+fn text_stats(prepared: &PreparedText, typographic: bool, rules: &SyllableRules) -> TextStats {
+    let tokens = prepared.analyzed_tokens();
+    // При --top-text-words предложения считаем только в оцениваемой части текста
+    let end = prepared
+        .tokens
+        .get(prepared.analyzed)
+        .map_or(prepared.text.len(), |t| t.start);
+    let mut stats = TextStats {
+        sentences: count_sentences(&prepared.text[..end], typographic),
+        words: tokens.len(),
+        ..TextStats::default()
+    };
+    for t in tokens {
+        let s = rules.count(&t.word);
+        stats.syllables += s;
+        if s >= 3 {
+            stats.complex_words += 1;
+        }
+    }
+    stats
+}
+
+/// Считает метрики `opts.metrics` в порядке запроса; `freq` — уже посчитанная частотная оценка,
+/// `clauses` — разбор предложений для clause-density. Метрика, которую посчитать не удалось,
+/// получает None и предупреждение.
+pub fn compute(
+    opts: &ScoreOptions,
+    prepared: &PreparedText,
+    freq: f64,
+    clauses: Option<&ClauseReport>,
+    warnings: &mut Vec<Warning>,
+) -> Vec<(Metric, Option<f64>)> {
+    let mut stats = None;
+    opts.metrics
+        .iter()
+        .map(|&metric| {
+            match metric {
+                Metric::Freq => return (metric, Some(freq)),
+                Metric::ClauseDensity => {
+                    let value = clauses.map(|c| c.mean);
+                    if value.is_none() {
+                        warnings.push(Warning::MetricUnavailable { metric, reason: MetricGap::NoSentences });
+                    }
+                    return (metric, value);
+                }
+                Metric::Flesch | Metric::Fog => {}
+            }
+            let rules = opts.language_data().and_then(|d| d.syllables.as_ref());
+            let gap = match rules {
+                None => Some(MetricGap::NoSyllableRules),
+                Some(rules) => {
+                    let s = *stats.get_or_insert_with(|| text_stats(prepared, opts.typographic, rules));
+                    (s.sentences == 0 || s.words == 0).then_some(MetricGap::NoSentences)
+                }
+            };
+            if let Some(reason) = gap {
+                warnings.push(Warning::MetricUnavailable { metric, reason });
+                return (metric, None);
+            }
+            let s = stats.expect("посчитано выше");
+            let words_per_sentence = s.words as f64 / s.sentences as f64;
+            let value = match metric {
+                Metric::Freq | Metric::ClauseDensity => unreachable!("обработано выше"),
+                Metric::Flesch => 206.835 - 1.015 * words_per_sentence - 84.6 * (s.syllables as f64 / s.words as f64),
+                Metric::Fog => 0.4 * (words_per_sentence + 100.0 * s.complex_words as f64 / s.words as f64),
+            };
+            (metric, Some(value))
+        })
+        .collect()
+}
@@ -0,0 +1,30 @@
+//! Соответствие смещений в обработанном тексте смещениям во входном тексте.
+
+/// Кусочно-линейное отображение смещений в символах: между опорными точками символы
+/// обработанного и исходного текста идут один к одному.
+#[derive(Clone, Debug, Default)]
+pub struct OffsetMap {
+    /// Пары (смещение в обработанном тексте, смещение в исходном), по возрастанию
+    anchors: Vec<(usize, usize)>,
+}
+
+impl OffsetMap {
+    /// С позиции `processed` обработанного текста символы соответствуют исходным начиная с `original`
+    pub fn push(&mut self, processed: usize, original: usize) {
+        match self.anchors.last_mut() {
+            Some(last) if last.0 == processed => last.1 = original,
+            _ => self.anchors.push((processed, original)),
+        }
+    }
+
+    pub fn original(&self, processed: usize) -> usize {
+        let i = self.anchors.partition_point(|&(p, _)| p <= processed);
+        match i.checked_sub(1) {
+            Some(i) => {
+                let (p, o) = self.anchors[i];
+                o + (processed - p)
+            }
+            None => processed,
+        }
+    }
+}
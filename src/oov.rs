@@ -0,0 +1,198 @@
+//! Классы слов вне словаря (--classify-oov): имя, вероятная опечатка, редкое слово.
+
+use std::collections::HashSet;
+
+use crate::dict::FrequencyDict;
+use crate::{metrics, Lang, Token};
+
+/// Класс слова вне словаря. Эвристики проверяются в порядке вариантов — первая сработавшая
+/// и задаёт класс: написанное с заглавной слово, близкое к частому («Ron» — «on»), считается
+/// именем, а не опечаткой. Аббревиатуры (--keep-acronyms) классами не охватываются: у них
+/// свой вес --acronym-weight.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OovClass {
+    /// В тексте пишется с заглавной не в начале предложения и нигде — со строчной
+    Name,
+    /// На расстоянии одной правки (вставка, удаление, замена, перестановка соседних букв)
+    /// от частого слова словаря; проверяются слова от трёх букв
+    Typo,
+    /// Всё остальное: настоящее редкое слово
+    Rare,
+}
+
+impl OovClass {
+    pub fn name(self) -> &'static str {
+        match self {
+            OovClass::Name => "name",
+            OovClass::Typo => "typo",
+            OovClass::Rare => "rare",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [OovClass::Name, OovClass::Typo, OovClass::Rare].into_iter().find(|c| c.name() == name)
+    }
+}
+
+/// Веса слов вне словаря по классам, каждый в [0, 1]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OovWeights {
+    pub name: f64,
+    pub typo: f64,
+    pub rare: f64,
+}
+
+impl Default for OovWeights {
+    fn default() -> Self {
+        OovWeights {
+            name: 0.8,
+            typo: 0.3,
+            rare: 0.0,
+        }
+    }
+}
+
+impl OovWeights {
+    pub fn weight(&self, class: OovClass) -> f64 {
+        match class {
+            OovClass::Name => self.name,
+            OovClass::Typo => self.typo,
+            OovClass::Rare => self.rare,
+        }
+    }
+}
+
+/// Число слов вне словаря по классам
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OovCounts {
+    pub name: usize,
+    pub typo: usize,
+    pub rare: usize,
+}
+
+impl OovCounts {
+    pub fn add(&mut self, class: OovClass) {
+        match class {
+            OovClass::Name => self.name += 1,
+            OovClass::Typo => self.typo += 1,
+            OovClass::Rare => self.rare += 1,
+        }
+    }
+
+    pub fn to_json(self) -> serde_json::Value {
+        serde_json::json!({ "name": self.name, "typo": self.typo, "rare": self.rare })
+    }
+
+    /// Обратно из `to_json`
+    pub fn from_json(v: &serde_json::Value) -> Option<OovCounts> {
+        let count = |key: &str| v[key].as_u64().map(|n| n as usize);
+        Some(OovCounts { name: count("name")?, typo: count("typo")?, rare: count("rare")? })
+    }
+}
+
+/// Частым для поиска опечаток считается слово с весом не меньше этого
+const FREQUENT_WEIGHT: f64 = 0.001;
+/// Более короткие слова на расстоянии одной правки почти от любого частого слова
+const TYPO_MIN_CHARS: usize = 3;
+
+/// Строчные буквы языка: из них строятся вставки и замены
+fn alphabet(lang: Lang) -> &'static str {
+    match lang {
+        Lang::En => "abcdefghijklmnopqrstuvwxyz",
+        Lang::De => "abcdefghijklmnopqrstuvwxyzäöüß",
+        Lang::Ru => "абвгдеёжзийклмнопрстуфхцчшщъыьэюя",
+    }
+}
+
+/// Есть ли в словаре частое слово на расстоянии одной правки (Дамерау — Левенштейна) от `word`.
+/// Кандидаты перебираются явно и ищутся в словаре: для слова из n букв это около 2·|алфавит|·n проверок.
+pub fn near_frequent_word(word: &str, dict: &FrequencyDict, lang: Lang) -> bool {
+    let chars: Vec<char> = word.chars().collect();
+    if chars.len() < TYPO_MIN_CHARS {
+        return false;
+    }
+    let frequent = |candidate: &[char]| {
+        let s: String = candidate.iter().collect();
+        dict.weight(&s).is_some_and(|w| w >= FREQUENT_WEIGHT)
+    };
+    let letters: Vec<char> = alphabet(lang).chars().collect();
+    let mut buf = Vec::with_capacity(chars.len() + 1);
+    for i in 0..=chars.len() {
+        // Удаление i-й буквы и перестановка i-й с (i+1)-й
+        if i < chars.len() {
+            buf.clear();
+            buf.extend(chars[..i].iter().chain(&chars[i + 1..]));
+            if frequent(&buf) {
+                return true;
+            }
+        }
+        if i + 1 < chars.len() && chars[i] != chars[i + 1] {
+            buf.clear();
+            buf.extend_from_slice(&chars);
+            buf.swap(i, i + 1);
+            if frequent(&buf) {
+                return true;
+            }
+        }
+        for &c in &letters {
+            // Вставка перед i-й буквой и замена i-й буквы
+            buf.clear();
+            buf.extend(chars[..i].iter().chain(std::iter::once(&c)).chain(&chars[i..]));
+            if frequent(&buf) {
+                return true;
+            }
+            if i < chars.len() && chars[i] != c {
+                buf.clear();
+                buf.extend_from_slice(&chars);
+                buf[i] = c;
+                if frequent(&buf) {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Класс слова вне словаря (см. порядок проверок у `OovClass`)
+pub fn classify(token: &Token, dict: &FrequencyDict, lang: Lang) -> OovClass {
+    if token.proper {
+        OovClass::Name
+    } else if near_frequent_word(&token.word, dict, lang) {
+        OovClass::Typo
+    } else {
+        OovClass::Rare
+    }
+}
+
+/// Отмечает `Token::proper` у слов, которые в `text` хотя бы раз написаны с заглавной буквы
+/// не в начале предложения и ни разу — со строчной. Начало предложения — первое слово после
+/// конца предыдущего (`metrics::sentence_ends`) или первое слово текста.
+pub fn mark_proper_names(text: &str, tokens: &mut [Token], typographic: bool) {
+    let mut ends = metrics::sentence_ends(text, typographic).peekable();
+    let mut sentence_start = true;
+    let mut capitalized: HashSet<&str> = HashSet::new();
+    let mut lowercase: HashSet<&str> = HashSet::new();
+    for t in tokens.iter() {
+        while ends.next_if(|&end| end <= t.start).is_some() {
+            sentence_start = true;
+        }
+        match text[t.start..t.end].chars().next() {
+            Some(c) if c.is_uppercase() && !sentence_start => {
+                capitalized.insert(&t.word);
+            }
+            Some(c) if c.is_lowercase() => {
+                lowercase.insert(&t.word);
+            }
+            _ => {}
+        }
+        sentence_start = false;
+    }
+    let names: HashSet<String> = capitalized
+        .difference(&lowercase)
+        .map(|w| w.to_string())
+        .collect();
+    for t in tokens {
+        t.proper = names.contains(&t.word);
+    }
+}
@@ -0,0 +1,104 @@
+//! Пересечение словаря текста с первыми K словами частотного словаря (--overlap):
+//! доля различных слов текста, входящих в «первые 2000 слов» ученика.
+
+use std::collections::HashSet;
+
+use crate::dict::FrequencyDict;
+use crate::Token;
+
+/// Различное слово текста чуть за пределами K — кандидат на предварительное изучение
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NearMiss {
+    pub word: String,
+    pub rank: usize,
+}
+
+/// Различные слова текста относительно первых `k` записей словаря по рангу
+#[derive(Clone, Debug)]
+pub struct Overlap {
+    pub k: usize,
+    /// Различных слов в оцениваемой части текста
+    pub distinct: usize,
+    /// С рангом не больше `k`
+    pub within: usize,
+    /// В словаре, но с рангом больше `k`
+    pub beyond: usize,
+    /// Вне словаря
+    pub oov: usize,
+    /// Слова с рангами от k+1 до k+delta, по возрастанию ранга
+    pub next: Vec<NearMiss>,
+}
+
+impl Overlap {
+    pub fn to_json(&self) -> serde_json::Value {
+        let next: Vec<serde_json::Value> = self
+            .next
+            .iter()
+            .map(|m| serde_json::json!({ "word": m.word, "rank": m.rank }))
+            .collect();
+        serde_json::json!({
+            "k": self.k,
+            "distinct": self.distinct,
+            "within": self.within,
+            "beyond": self.beyond,
+            "oov": self.oov,
+            "next": next,
+        })
+    }
+
+    /// Обратно из `to_json`
+    pub fn from_json(v: &serde_json::Value) -> Option<Overlap> {
+        let field = |key: &str| v[key].as_u64().map(|n| n as usize);
+        let next = v["next"]
+            .as_array()?
+            .iter()
+            .map(|m| Some(NearMiss { word: m["word"].as_str()?.to_string(), rank: m["rank"].as_u64()? as usize }))
+            .collect::<Option<_>>()?;
+        Some(Overlap {
+            k: field("k")?,
+            distinct: field("distinct")?,
+            within: field("within")?,
+            beyond: field("beyond")?,
+            oov: field("oov")?,
+            next,
+        })
+    }
+}
+
+/// Считает пересечение для каждого K из `ks` в порядке запроса. Ранги берутся из словаря
+/// (`DictOptions::retain_stats`); слово словаря без ранга считается за пределами любого K.
+pub fn overlap(tokens: &[Token], dict: &FrequencyDict, ks: &[usize], delta: usize) -> Vec<Overlap> {
+    let words: HashSet<&str> = tokens.iter().map(|t| t.word.as_str()).collect();
+    // (слово, ранг; None — вне словаря, Some(None) — в словаре без ранга)
+    let mut ranked: Vec<(&str, Option<Option<usize>>)> = words
+        .into_iter()
+        .map(|w| (w, dict.contains(w).then(|| dict.stats(w).map(|s| s.rank))))
+        .collect();
+    ranked.sort_unstable_by_key(|&(w, _)| w);
+    ks.iter()
+        .map(|&k| {
+            let mut result = Overlap {
+                k,
+                distinct: ranked.len(),
+                within: 0,
+                beyond: 0,
+                oov: 0,
+                next: Vec::new(),
+            };
+            for &(word, rank) in &ranked {
+                match rank {
+                    None => result.oov += 1,
+                    Some(Some(r)) if r <= k => result.within += 1,
+                    Some(r) => {
+                        result.beyond += 1;
+                        if let Some(rank) = r.filter(|&r| r <= k.saturating_add(delta)) {
+                            result.next.push(NearMiss { word: word.to_string(), rank });
+                        }
+                    }
+                }
+            }
+            result.next.sort_by_key(|m| m.rank);
+            result
+        })
+        .collect()
+}
@@ -0,0 +1,139 @@
+//! Явная цепочка нормализации (--pipeline): какие стадии и в каком порядке применяются
+//! к тексту и потоку слов перед поиском в словаре.
+
+use crate::{Error, Result};
+
+/// Стадия нормализации. Текстовые стадии меняют текст до токенизации, стадии слов — поток
+/// слов после неё; поэтому в цепочке все текстовые стадии идут раньше стадий слов.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// Исключение фрагментов, отмеченных директивами `<!-- readability:ignore-... -->`
+    Directives,
+    /// Удаление BOM, символов нулевой ширины и мягких переносов, замена неразрывных пробелов
+    Sanitize,
+    /// Склейка слов, перенесённых через строку с дефисом
+    Dehyphenate,
+    /// Исключение прямой речи в двойных кавычках
+    SkipQuotes,
+    /// Приведение слов к нижнему регистру по правилам языка; без этой стадии слова ищутся
+    /// в словаре в том регистре, в каком написаны
+    Lowercase,
+    /// Раскрытие сокращений по таблице `ScoreOptions::contractions` (без таблицы ничего не делает).
+    /// Таблица записана в нижнем регистре: до lowercase раскрываются только слова, уже
+    /// написанные строчными, и общие окончания вроде n't
+    ExpandContractions,
+    /// Удаление служебных слов языка (встроенных для английского или из --lang-data); только
+    /// в явной --pipeline
+    Stopwords,
+}
+
+/// Все стадии в порядке по умолчанию
+const STAGES: &[Stage] = &[
+    Stage::Directives,
+    Stage::Sanitize,
+    Stage::Dehyphenate,
+    Stage::SkipQuotes,
+    Stage::Lowercase,
+    Stage::ExpandContractions,
+    Stage::Stopwords,
+];
+
+impl Stage {
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Directives => "directives",
+            Stage::Sanitize => "sanitize",
+            Stage::Dehyphenate => "dehyphenate",
+            Stage::SkipQuotes => "skip-quotes",
+            Stage::Lowercase => "lowercase",
+            Stage::ExpandContractions => "expand-contractions",
+            Stage::Stopwords => "stopwords",
+        }
+    }
+
+    /// Текстовая стадия (до токенизации)
+    pub fn is_text(self) -> bool {
+        matches!(self, Stage::Directives | Stage::Sanitize | Stage::Dehyphenate | Stage::SkipQuotes)
+    }
+
+    pub fn from_name(name: &str) -> Option<Stage> {
+        STAGES.iter().copied().find(|s| s.name() == name)
+    }
+
+    /// Имена всех стадий через запятую — для сообщений об ошибках
+    pub fn all_names() -> String {
+        STAGES.iter().map(|s| s.name()).collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// Упорядоченная цепочка стадий без повторов
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenPipeline {
+    stages: Vec<Stage>,
+}
+
+impl Default for TokenPipeline {
+    /// Цепочка без флагов командной строки: directives, sanitize, dehyphenate, lowercase
+    fn default() -> Self {
+        TokenPipeline::from_flags(true, true, true, false, false)
+    }
+}
+
+impl TokenPipeline {
+    /// Проверяет цепочку: каждая стадия не больше одного раза, текстовые — раньше стадий слов
+    pub fn new(stages: Vec<Stage>) -> Result<Self> {
+        for (i, &stage) in stages.iter().enumerate() {
+            if stages[..i].contains(&stage) {
+                return Err(Error::PipelineDuplicateStage(stage));
+            }
+            if let Some(&token) = stages[..i].iter().find(|s| !s.is_text()).filter(|_| stage.is_text()) {
+                return Err(Error::PipelineStageOrder { text: stage, token });
+            }
+        }
+        Ok(TokenPipeline { stages })
+    }
+
+    /// Разбор `lowercase,expand-contractions`: имена стадий через запятую
+    pub fn parse(s: &str) -> Result<Self> {
+        let stages = s
+            .split(',')
+            .map(|name| Stage::from_name(name.trim()).ok_or_else(|| Error::PipelineUnknownStage(name.trim().to_string())))
+            .collect::<Result<Vec<_>>>()?;
+        TokenPipeline::new(stages)
+    }
+
+    /// Цепочка, которую задают отдельные флаги: включённые стадии на местах по умолчанию
+    pub fn from_flags(
+        directives: bool,
+        sanitize: bool,
+        dehyphenate: bool,
+        skip_quotes: bool,
+        expand_contractions: bool,
+    ) -> Self {
+        let enabled = |s: &Stage| match s {
+            Stage::Directives => directives,
+            Stage::Sanitize => sanitize,
+            Stage::Dehyphenate => dehyphenate,
+            Stage::SkipQuotes => skip_quotes,
+            Stage::Lowercase => true,
+            Stage::ExpandContractions => expand_contractions,
+            Stage::Stopwords => false,
+        };
+        TokenPipeline {
+            stages: STAGES.iter().copied().filter(enabled).collect(),
+        }
+    }
+
+    pub fn stages(&self) -> &[Stage] {
+        &self.stages
+    }
+
+    pub fn contains(&self, stage: Stage) -> bool {
+        self.stages.contains(&stage)
+    }
+
+    /// Запись цепочки в том же виде, что принимает `parse`
+    pub fn names(&self) -> String {
+        self.stages.iter().map(|s| s.name()).collect::<Vec<_>>().join(",")
+    }
+}
@@ -0,0 +1,117 @@
+//! Исключение прямой речи (--skip-quotes): удаление фрагментов в двойных кавычках.
+
+use crate::offsets::OffsetMap;
+use crate::Warning;
+
+/// Результат удаления цитат из текста
+#[derive(Clone, Debug)]
+pub struct StrippedQuotes {
+    /// Текст без цитат; каждый удалённый фрагмент заменён пробелом, чтобы не склеивать соседние слова
+    pub text: String,
+    /// Удалённые фрагменты (вместе с кавычками)
+    pub removed: Vec<String>,
+    /// Предупреждение о несбалансированных кавычках
+    pub warning: Option<Warning>,
+    /// Смещения в `text` → смещения во входном тексте
+    pub offsets: OffsetMap,
+}
+
+fn is_open_quote(c: char) -> bool {
+    matches!(c, '"' | '“' | '«')
+}
+
+/// Если с позиции `i` (сразу после перевода строки) начинается пустая строка, возвращает
+/// позицию первого непробельного символа следующего абзаца.
+fn paragraph_break_at(text: &str, i: usize) -> Option<usize> {
+    let rest = &text[i..];
+    let line_end = rest.find('\n')?;
+    if !rest[..line_end].trim().is_empty() {
+        return None;
+    }
+    let after = &rest[line_end..];
+    let skipped = after.len() - after.trim_start().len();
+    Some(i + line_end + skipped)
+}
+
+/// Удаляет фрагменты в двойных кавычках: прямых ("), типографских (“ ”) и ёлочках (« »).
+/// Одинарные кавычки и апострофы не трогаем — внутри прямой речи они удаляются вместе с ней.
+///
+/// Цитата, не закрытая к концу абзаца, продолжается, если следующий абзац открывается кавычкой
+/// (типографская традиция для многоабзацной речи). Иначе кавычки считаются несбалансированными:
+/// удаляются только фрагменты до последней полностью закрытой пары, остальной текст остаётся.
+pub fn strip_quotes(text: &str) -> StrippedQuotes {
+    let mut spans: Vec<(usize, usize)> = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0usize;
+    // Открывающая кавычка нового абзаца, которая лишь продолжает текущую цитату
+    let mut continuation: Option<usize> = None;
+    let mut warning = None;
+
+    for (i, c) in text.char_indices() {
+        if c == '\n' && depth > 0 {
+            if let Some(next) = paragraph_break_at(text, i + 1) {
+                match text[next..].chars().next() {
+                    Some(q) if is_open_quote(q) => continuation = Some(next),
+                    _ => {
+                        warning = Some(Warning::UnclosedQuoteParagraph { at: start });
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+        if continuation == Some(i) {
+            continuation = None;
+            continue;
+        }
+        let closes = match c {
+            '"' => depth > 0,
+            '”' | '»' => true,
+            '“' | '«' => false,
+            _ => continue,
+        };
+        if closes {
+            if depth == 0 {
+                warning = Some(Warning::StrayClosingQuote { at: i });
+                break;
+            }
+            depth -= 1;
+            if depth == 0 {
+                spans.push((start, i + c.len_utf8()));
+            }
+        } else {
+            if depth == 0 {
+                start = i;
+            }
+            depth += 1;
+        }
+    }
+    if depth > 0 && warning.is_none() {
+        warning = Some(Warning::UnclosedQuoteAtEnd { at: start });
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut removed = Vec::with_capacity(spans.len());
+    let mut offsets = OffsetMap::default();
+    let (mut processed, mut original) = (0usize, 0usize);
+    let mut pos = 0;
+    for (s, e) in spans {
+        let before = text[pos..s].chars().count();
+        out.push_str(&text[pos..s]);
+        out.push(' ');
+        removed.push(text[s..e].to_string());
+        // Пробел на месте цитаты соответствует её началу, следующий символ — концу
+        processed += before + 1;
+        original += before + text[s..e].chars().count();
+        offsets.push(processed, original);
+        pos = e;
+    }
+    out.push_str(&text[pos..]);
+
+    StrippedQuotes {
+        text: out,
+        removed,
+        warning,
+        offsets,
+    }
+}
@@ -0,0 +1,36 @@
+//! Очистка входного текста от невидимых символов перед токенизацией (отключается --no-sanitize).
+
+use std::borrow::Cow;
+
+use crate::offsets::OffsetMap;
+
+/// Удаляемые символы: BOM (он же неразрывный пробел нулевой ширины), пробелы и соединители
+/// нулевой ширины, мягкий перенос — внутри слова они разбивают его на части
+fn is_removed(c: char) -> bool {
+    matches!(c, '\u{FEFF}' | '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{00AD}')
+}
+
+/// Неразрывные пробелы, которые считаются обычными
+fn is_nbsp(c: char) -> bool {
+    matches!(c, '\u{00A0}' | '\u{2007}' | '\u{202F}')
+}
+
+/// Очищенный текст и соответствие его смещений исходным; без изменений текст не копируется.
+pub fn sanitize(text: &str) -> (Cow<'_, str>, OffsetMap) {
+    let mut offsets = OffsetMap::default();
+    if !text.contains(|c| is_removed(c) || is_nbsp(c)) {
+        return (Cow::Borrowed(text), offsets);
+    }
+    let mut out = String::with_capacity(text.len());
+    let (mut processed, mut original) = (0usize, 0usize);
+    for c in text.chars() {
+        original += 1;
+        if is_removed(c) {
+            offsets.push(processed, original);
+            continue;
+        }
+        out.push(if is_nbsp(c) { ' ' } else { c });
+        processed += 1;
+    }
+    (Cow::Owned(out), offsets)
+}
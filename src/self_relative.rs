@@ -0,0 +1,71 @@
+//! Оценка относительно самого корпуса (--self-relative): словарь строится из входных файлов.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use readability::dict::{build_frequency_dict, DictLoadInfo, DictOptions, FrequencyDict};
+use readability::{prepare_text, read_input_limited, ScoreOptions, Token};
+
+use crate::dict_cmd::{render_entries, sort_entries, DictFormat};
+use crate::i18n::Msg;
+
+/// Частоты слов всего корпуса и параметры, с которыми из них строится словарь
+pub struct CorpusCounts {
+    counts: HashMap<String, u64>,
+    dict_opts: DictOptions,
+}
+
+/// Первый проход: слова всех файлов с теми же настройками токенизации, что и при оценке
+/// (очистка, цитаты, сокращения, аббревиатуры). Нечитаемые файлы пропускаются:
+/// ошибку покажет второй проход.
+pub fn count_corpus(files: &[PathBuf], opts: &ScoreOptions, dict_opts: DictOptions) -> CorpusCounts {
+    let mut counts: HashMap<String, u64> = HashMap::new();
+    for path in files {
+        let Ok(text) = read_input_limited(Some(path), opts.max_input_bytes) else { continue };
+        let Ok(prepared) = prepare_text(&text, None, opts) else { continue };
+        for t in prepared.tokens {
+            *counts.entry(t.word).or_insert(0) += 1;
+        }
+    }
+    CorpusCounts { counts, dict_opts }
+}
+
+impl CorpusCounts {
+    /// Пары (слово, частота) по убыванию частоты — в этом порядке действует --top-dict-entries
+    fn entries(&self, own: &HashMap<&str, u64>) -> Vec<(String, u64)> {
+        let mut items: Vec<(String, u64)> = self
+            .counts
+            .iter()
+            .map(|(w, &c)| (w.clone(), c.saturating_sub(own.get(w.as_str()).copied().unwrap_or(0))))
+            .filter(|&(_, c)| c > 0)
+            .collect();
+        sort_entries(&mut items);
+        items
+    }
+
+    /// Словарь всего корпуса
+    pub fn dict(&self) -> Result<(FrequencyDict, DictLoadInfo)> {
+        Ok(build_frequency_dict(self.entries(&HashMap::new()), self.dict_opts)?)
+    }
+
+    /// Словарь корпуса без вклада одного файла (--self-relative-exclude-self): слова, которые
+    /// встречаются только в нём, оказываются вне словаря
+    pub fn dict_without(&self, tokens: &[Token]) -> readability::Result<FrequencyDict> {
+        let mut own: HashMap<&str, u64> = HashMap::new();
+        for t in tokens {
+            *own.entry(t.word.as_str()).or_insert(0) += 1;
+        }
+        Ok(build_frequency_dict(self.entries(&own), self.dict_opts)?.0)
+    }
+
+    /// Сохраняет частоты корпуса (--save-dict) в формате, который читает --dict:
+    /// .csv — CSV, иначе JSON
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let is_csv = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+        let format = if is_csv { DictFormat::Csv } else { DictFormat::Json };
+        let content = render_entries(&self.entries(&HashMap::new()), format)?;
+        std::fs::write(path, content).with_context(|| Msg::DictWrite(path).to_string())
+    }
+}
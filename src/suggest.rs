@@ -0,0 +1,164 @@
+//! Подсказки замены редких слов более частыми синонимами (--suggest): синонимы берутся из
+//! TSV-файла `слово<TAB>синоним, синоним` (--synonyms), их вес — из того же словаря.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::dict::FrequencyDict;
+use crate::{prepare_text, token_weight, Error, FileKind, Result, ScoreOptions, Token};
+
+/// Во сколько раз синоним должен весить больше слова, чтобы его стоило предложить
+const MIN_GAIN: f64 = 2.0;
+
+/// Таблица синонимов: нормализованное слово → нормализованные синонимы в порядке файла
+#[derive(Clone, Debug, Default)]
+pub struct Synonyms {
+    table: HashMap<String, Vec<String>>,
+}
+
+impl Synonyms {
+    /// Читает TSV-файл `слово<TAB>синоним, синоним`. Слова и синонимы проходят те же стадии
+    /// нормализации, что и текст (`opts.pipeline`), поэтому словоформы в файле находят слова
+    /// текста. Поля, которые после нормализации не сводятся к одному слову (несколько слов,
+    /// стоп-слово), пропускаются. Пустые строки и строки с # пропускаются; повтор слова
+    /// дополняет его синонимы.
+    pub fn load(path: &Path, opts: &ScoreOptions) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|source| Error::Io {
+            kind: FileKind::Synonyms,
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let field_opts = ScoreOptions {
+            top_text_words: None,
+            top_text_sentences: None,
+            max_input_bytes: None,
+            max_tokens: None,
+            ..opts.clone()
+        };
+        let normalize = |field: &str| -> Result<Option<String>> {
+            let mut tokens = prepare_text(field.trim(), None, &field_opts)?.tokens;
+            Ok(match tokens.len() {
+                1 => tokens.pop().map(|t| t.word),
+                _ => None,
+            })
+        };
+        let mut table: HashMap<String, Vec<String>> = HashMap::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((word, alternatives)) = line.split_once('\t').filter(|(_, alts)| !alts.trim().is_empty()) else {
+                return Err(Error::SynonymsBadLine {
+                    path: path.to_path_buf(),
+                    line: i + 1,
+                });
+            };
+            let Some(word) = normalize(word)? else {
+                continue;
+            };
+            let entry = table.entry(word.clone()).or_default();
+            for alt in alternatives.split(',') {
+                if let Some(alt) = normalize(alt)?.filter(|a| *a != word && !entry.contains(a)) {
+                    entry.push(alt);
+                }
+            }
+        }
+        table.retain(|_, alts| !alts.is_empty());
+        Ok(Synonyms { table })
+    }
+
+    pub fn get(&self, word: &str) -> &[String] {
+        self.table.get(word).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Параметры --suggest
+#[derive(Clone, Debug)]
+pub struct SuggestOptions {
+    pub synonyms: Arc<Synonyms>,
+    /// Подсказки ищутся для слов с весом ниже порога (--suggest-threshold)
+    pub threshold: f64,
+    /// Сколько слов с подсказками выводить (--suggest-limit)
+    pub limit: usize,
+}
+
+/// Массив `[{"word": ..., "weight": ...}]` синонимов из JSON-отчёта
+pub fn weighted_words_from_json(v: &serde_json::Value) -> Option<Vec<(String, f64)>> {
+    v.as_array()?
+        .iter()
+        .map(|w| Some((w["word"].as_str()?.to_string(), w["weight"].as_f64()?)))
+        .collect()
+}
+
+/// Слово текста и его более частые синонимы
+#[derive(Clone, Debug, PartialEq)]
+pub struct Suggestion {
+    pub word: String,
+    pub weight: f64,
+    /// Синонимы с весами, от самого частого
+    pub alternatives: Vec<(String, f64)>,
+}
+
+impl Suggestion {
+    pub fn to_json(&self) -> serde_json::Value {
+        let alternatives: Vec<serde_json::Value> = self
+            .alternatives
+            .iter()
+            .map(|(word, weight)| serde_json::json!({"word": word, "weight": weight}))
+            .collect();
+        serde_json::json!({
+            "word": self.word,
+            "weight": self.weight,
+            "alternatives": alternatives,
+        })
+    }
+
+    /// Обратно из `to_json`
+    pub fn from_json(v: &serde_json::Value) -> Option<Suggestion> {
+        Some(Suggestion {
+            word: v["word"].as_str()?.to_string(),
+            weight: v["weight"].as_f64()?,
+            alternatives: weighted_words_from_json(&v["alternatives"])?,
+        })
+    }
+
+    /// `utilize (0.003000) → use (0.920000), employ (0.050000)`
+    pub fn display(&self) -> String {
+        let alternatives: Vec<String> = self.alternatives.iter().map(|(w, x)| format!("{w} ({x:.6})")).collect();
+        format!("{} ({:.6}) → {}", self.word, self.weight, alternatives.join(", "))
+    }
+}
+
+/// Подсказки для различных слов `tokens` с весом ниже порога: синонимы из словаря, которые
+/// весят хотя бы вдвое больше слова. Слова без таких синонимов не попадают в список;
+/// порядок — от самых трудных слов, не больше `limit` слов.
+pub fn suggest(tokens: &[Token], dict: &FrequencyDict, opts: &ScoreOptions, s: &SuggestOptions) -> Vec<Suggestion> {
+    let mut seen = HashSet::new();
+    let mut out: Vec<Suggestion> = Vec::new();
+    for t in tokens {
+        if !seen.insert(t.word.as_str()) {
+            continue;
+        }
+        let weight = token_weight(t, dict, opts);
+        if weight >= s.threshold {
+            continue;
+        }
+        let mut alternatives: Vec<(String, f64)> = s
+            .synonyms
+            .get(&t.word)
+            .iter()
+            .filter_map(|alt| dict.weight(alt).map(|w| (alt.clone(), w)))
+            .filter(|&(_, w)| w > 0.0 && w >= weight * MIN_GAIN)
+            .collect();
+        if alternatives.is_empty() {
+            continue;
+        }
+        alternatives.sort_by(|a, b| b.1.total_cmp(&a.1));
+        out.push(Suggestion { word: t.word.clone(), weight, alternatives });
+    }
+    out.sort_by(|a, b| a.weight.total_cmp(&b.weight).then_with(|| a.word.cmp(&b.word)));
+    out.truncate(s.limit);
+    out
+}
@@ -0,0 +1,212 @@
+//! Шаблон plain-строки результата (--template): `{path}\t{score:.3}\t{oov_rate:.1%}`.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use readability::oov::OovCounts;
+use readability::ScoreReport;
+
+use crate::i18n::Msg;
+
+/// Поле отчёта, доступное в шаблоне
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Path,
+    Index,
+    Score,
+    Tokens,
+    OovTokens,
+    OovRate,
+    ContractionsExpanded,
+    QuotedCharsExcluded,
+    QuotedTokensExcluded,
+    CiLo,
+    CiHi,
+    OovName,
+    OovTypo,
+    OovRare,
+}
+
+/// Имена полей в порядке, в котором они перечисляются в сообщении об ошибке
+const FIELDS: &[(&str, Field)] = &[
+    ("path", Field::Path),
+    ("index", Field::Index),
+    ("score", Field::Score),
+    ("tokens", Field::Tokens),
+    ("oov_tokens", Field::OovTokens),
+    ("oov_rate", Field::OovRate),
+    ("contractions_expanded", Field::ContractionsExpanded),
+    ("quoted_chars_excluded", Field::QuotedCharsExcluded),
+    ("quoted_tokens_excluded", Field::QuotedTokensExcluded),
+    ("ci_lo", Field::CiLo),
+    ("ci_hi", Field::CiHi),
+    ("oov_name", Field::OovName),
+    ("oov_typo", Field::OovTypo),
+    ("oov_rare", Field::OovRare),
+];
+
+/// Значение поля для одного входа
+enum Value {
+    Text(String),
+    Int(usize),
+    Num(f64),
+    /// Поле не посчитано (например, ci_lo без --bootstrap): печатается `-`
+    Missing,
+}
+
+/// Формат числа: `.N` — знаков после запятой, `%` — доля в процентах
+#[derive(Clone, Copy, Debug, Default)]
+struct Spec {
+    precision: Option<usize>,
+    percent: bool,
+}
+
+#[derive(Clone, Debug)]
+enum Part {
+    Literal(String),
+    Field(Field, Spec),
+}
+
+/// Разобранный шаблон. Ошибки (неизвестное поле, формат, незакрытая скобка) выявляются
+/// при разборе аргументов, до чтения словаря и текстов.
+#[derive(Clone, Debug)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+/// Дописывает литерал к последней литеральной части или начинает новую
+fn push_literal(parts: &mut Vec<Part>, c: char) {
+    match parts.last_mut() {
+        Some(Part::Literal(s)) => s.push(c),
+        _ => parts.push(Part::Literal(c.to_string())),
+    }
+}
+
+fn parse_spec(spec: &str) -> Option<Spec> {
+    let (digits, percent) = match spec.strip_suffix('%') {
+        Some(rest) => (rest, true),
+        None => (spec, false),
+    };
+    let precision = match digits {
+        "" => None,
+        _ => Some(digits.strip_prefix('.')?.parse().ok()?),
+    };
+    Some(Spec { precision, percent })
+}
+
+fn parse_placeholder(body: &str) -> Result<Part, String> {
+    let (name, spec) = match body.split_once(':') {
+        Some((name, spec)) => (name, Some(spec)),
+        None => (body, None),
+    };
+    let field = FIELDS.iter().find(|(n, _)| *n == name).map(|&(_, f)| f).ok_or_else(|| {
+        let valid: Vec<&str> = FIELDS.iter().map(|(n, _)| *n).collect();
+        Msg::TemplateUnknownField { name, valid: &valid.join(", ") }.to_string()
+    })?;
+    let Some(spec) = spec else {
+        return Ok(Part::Field(field, Spec::default()));
+    };
+    if field == Field::Path {
+        return Err(Msg::TemplateSpecOnText(name).to_string());
+    }
+    let spec = parse_spec(spec).ok_or_else(|| Msg::TemplateBadSpec(spec).to_string())?;
+    Ok(Part::Field(field, spec))
+}
+
+/// Разбор шаблона: `{поле}` или `{поле:формат}`, `{{` и `}}` — литеральные скобки,
+/// `\t`, `\n` и `\\` — табуляция, перевод строки и обратная косая черта.
+pub fn parse_template(s: &str) -> Result<Template, String> {
+    let mut parts = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.next_if_eq(&'{').is_some() => push_literal(&mut parts, '{'),
+            '}' if chars.next_if_eq(&'}').is_some() => push_literal(&mut parts, '}'),
+            '{' => {
+                let mut body = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => body.push(c),
+                        None => return Err(Msg::TemplateUnclosed.to_string()),
+                    }
+                }
+                parts.push(parse_placeholder(&body)?);
+            }
+            '}' => return Err(Msg::TemplateUnclosed.to_string()),
+            '\\' => match chars.next() {
+                Some('t') => push_literal(&mut parts, '\t'),
+                Some('n') => push_literal(&mut parts, '\n'),
+                Some('\\') => push_literal(&mut parts, '\\'),
+                other => {
+                    let escape: String = std::iter::once('\\').chain(other).collect();
+                    return Err(Msg::TemplateBadEscape(&escape).to_string());
+                }
+            },
+            c => push_literal(&mut parts, c),
+        }
+    }
+    Ok(Template { parts })
+}
+
+fn value(field: Field, path: &Path, index: usize, report: &ScoreReport) -> Value {
+    let oov = |f: fn(&OovCounts) -> usize| report.oov_classes.map_or(Value::Missing, |c| Value::Int(f(&c)));
+    match field {
+        Field::Path => Value::Text(path.display().to_string()),
+        Field::Index => Value::Int(index),
+        Field::Score => Value::Num(report.score),
+        Field::Tokens => Value::Int(report.tokens),
+        Field::OovTokens => Value::Int(report.oov_tokens),
+        Field::OovRate if report.tokens == 0 => Value::Missing,
+        Field::OovRate => Value::Num(report.oov_tokens as f64 / report.tokens as f64),
+        Field::ContractionsExpanded => Value::Int(report.contractions_expanded),
+        Field::QuotedCharsExcluded => Value::Int(report.quoted_chars_excluded),
+        Field::QuotedTokensExcluded => Value::Int(report.quoted_tokens_excluded),
+        Field::CiLo => report.bootstrap.map_or(Value::Missing, |b| Value::Num(b.lo)),
+        Field::CiHi => report.bootstrap.map_or(Value::Missing, |b| Value::Num(b.hi)),
+        Field::OovName => oov(|c| c.name),
+        Field::OovTypo => oov(|c| c.typo),
+        Field::OovRare => oov(|c| c.rare),
+    }
+}
+
+impl Template {
+    /// Строка результата для одного входа; `index` — номер входа среди оценённых, с нуля.
+    /// Числа без формата печатаются как в обычном plain-выводе: дробные — с шестью знаками,
+    /// целые — как есть; с `.N` или `%` целые печатаются как дробные.
+    pub fn render(&self, path: &Path, index: usize, report: &ScoreReport) -> String {
+        let mut out = String::new();
+        for part in &self.parts {
+            let (field, spec) = match part {
+                Part::Literal(s) => {
+                    out.push_str(s);
+                    continue;
+                }
+                Part::Field(field, spec) => (*field, *spec),
+            };
+            let number = match value(field, path, index, report) {
+                Value::Text(s) => {
+                    out.push_str(&s);
+                    continue;
+                }
+                Value::Missing => {
+                    out.push('-');
+                    continue;
+                }
+                Value::Int(n) if !spec.percent && spec.precision.is_none() => {
+                    let _ = write!(out, "{n}");
+                    continue;
+                }
+                Value::Int(n) => n as f64,
+                Value::Num(v) => v,
+            };
+            let precision = spec.precision.unwrap_or(6);
+            if spec.percent {
+                let _ = write!(out, "{:.*}%", precision, number * 100.0);
+            } else {
+                let _ = write!(out, "{number:.precision$}");
+            }
+        }
+        out
+    }
+}
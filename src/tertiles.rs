@@ -0,0 +1,72 @@
+//! Оценки первой, средней и последней трети оцениваемых слов (--tertile-report): дешёвая
+//! проверка, не копится ли трудная лексика к концу текста, без полного профиля по окнам.
+//! Границы третей известны только по числу слов, поэтому на вход идут уже токенизированные
+//! слова текста; отдельной копии для третей не делается.
+
+use crate::dict::FrequencyDict;
+use crate::{token_weight, ScoreOptions, Token};
+
+/// Оценки третей по числу слов
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tertiles {
+    /// Оценки первой, средней и последней трети; None — в трети нет слов (меньше трёх слов в тексте)
+    pub scores: [Option<f64>; 3],
+    pub tokens: [usize; 3],
+}
+
+impl Tertiles {
+    /// Наибольшая разница между оценками непустых третей
+    pub fn spread(&self) -> f64 {
+        let scores = self.scores.iter().flatten();
+        let max = scores.clone().copied().fold(f64::NEG_INFINITY, f64::max);
+        let min = scores.copied().fold(f64::INFINITY, f64::min);
+        (max - min).max(0.0)
+    }
+
+    /// Обратно из `to_json` (записи --results-out и --cache-scores)
+    pub fn from_json(v: &serde_json::Value) -> Option<Tertiles> {
+        let score = |key: &str| v[key].as_f64();
+        let tokens: Vec<usize> = v["tokens"].as_array()?.iter().map(|n| n.as_u64().map(|n| n as usize)).collect::<Option<_>>()?;
+        Some(Tertiles {
+            scores: [score("front"), score("middle"), score("back")],
+            tokens: tokens.try_into().ok()?,
+        })
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "front": self.scores[0],
+            "middle": self.scores[1],
+            "back": self.scores[2],
+            "tokens": self.tokens,
+            "spread": self.spread(),
+        })
+    }
+}
+
+/// Размеры третей для `n` слов: остаток от деления на 3 достаётся первым третям по одному
+/// слову (10 слов — 4, 3, 3; 11 слов — 4, 4, 3)
+fn sizes(n: usize) -> [usize; 3] {
+    let (q, r) = (n / 3, n % 3);
+    [q + usize::from(r > 0), q + usize::from(r > 1), q]
+}
+
+/// Оценки третей `tokens` за один проход с тремя частичными суммами. Среднее оценок,
+/// взвешенное числом слов, равно оценке по всем словам (без --aggregate-by).
+pub fn tertiles(tokens: &[Token], dict: &FrequencyDict, opts: &ScoreOptions) -> Tertiles {
+    let counts = sizes(tokens.len());
+    let mut sums = [0.0f64; 3];
+    let (mut part, mut left) = (0, counts[0]);
+    for t in tokens {
+        while left == 0 {
+            part += 1;
+            left = counts[part];
+        }
+        sums[part] += token_weight(t, dict, opts);
+        left -= 1;
+    }
+    Tertiles {
+        scores: [0, 1, 2].map(|i| (counts[i] > 0).then(|| sums[i] / counts[i] as f64)),
+        tokens: counts,
+    }
+}
@@ -0,0 +1,105 @@
+//! Замеры длительности этапов обработки (--timings).
+
+use std::time::Duration;
+
+/// Этапы загрузки словаря
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DictTimings {
+    pub read: Duration,
+    pub parse: Duration,
+    pub normalize: Duration,
+}
+
+/// Этапы обработки одного текста
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TextTimings {
+    pub read: Duration,
+    /// Токенизация вместе с предобработкой (цитаты, сокращения)
+    pub tokenize: Duration,
+    pub score: Duration,
+}
+
+fn ms(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+/// Токенов в секунду; None, если этап занял нулевое время
+fn throughput(tokens: usize, d: Duration) -> Option<f64> {
+    let secs = d.as_secs_f64();
+    (secs > 0.0).then(|| tokens as f64 / secs)
+}
+
+fn format_phase(name: &str, d: Duration, tokens: Option<usize>) -> String {
+    match tokens.and_then(|n| throughput(n, d)) {
+        Some(tps) => format!("{name}: {:.3} ms ({tps:.0} tokens/s)", ms(d)),
+        None => format!("{name}: {:.3} ms", ms(d)),
+    }
+}
+
+impl DictTimings {
+    pub fn to_json(self) -> serde_json::Value {
+        serde_json::json!({
+            "dict_read_ms": ms(self.read),
+            "dict_parse_ms": ms(self.parse),
+            "dict_normalize_ms": ms(self.normalize),
+        })
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        vec![
+            format_phase("dict read", self.read, None),
+            format_phase("dict parse", self.parse, None),
+            format_phase("dict normalize", self.normalize, None),
+        ]
+    }
+}
+
+impl TextTimings {
+    pub fn total(&self) -> Duration {
+        self.read + self.tokenize + self.score
+    }
+
+    pub fn to_json(self, tokens: usize) -> serde_json::Value {
+        serde_json::json!({
+            "text_read_ms": ms(self.read),
+            "tokenize_ms": ms(self.tokenize),
+            "tokenize_tokens_per_sec": throughput(tokens, self.tokenize),
+            "score_ms": ms(self.score),
+            "score_tokens_per_sec": throughput(tokens, self.score),
+        })
+    }
+
+    pub fn lines(&self, tokens: usize) -> Vec<String> {
+        vec![
+            format_phase("text read", self.read, None),
+            format_phase("tokenize", self.tokenize, Some(tokens)),
+            format_phase("score", self.score, Some(tokens)),
+        ]
+    }
+
+    /// Краткая строка для режима корпуса
+    pub fn short_line(&self) -> String {
+        format!(
+            "tokenize {:.3} ms, score {:.3} ms",
+            ms(self.tokenize),
+            ms(self.score)
+        )
+    }
+}
+
+/// Итог по корпусу: сумма по файлам и общее время работы
+pub fn corpus_total_json(dict: &DictTimings, files: &[TextTimings], wall: Duration) -> serde_json::Value {
+    let mut json = dict.to_json();
+    let sum: Duration = files.iter().map(TextTimings::total).sum();
+    json["files_ms"] = serde_json::Value::from(ms(sum));
+    json["total_ms"] = serde_json::Value::from(ms(wall));
+    json
+}
+
+pub fn corpus_total_lines(dict: &DictTimings, files: &[TextTimings], wall: Duration) -> Vec<String> {
+    let mut lines = dict.lines();
+    let sum: Duration = files.iter().map(TextTimings::total).sum();
+    lines.push(format_phase("files (sum)", sum, None));
+    lines.push(format_phase("total", wall, None));
+    lines
+}
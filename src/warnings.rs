@@ -0,0 +1,87 @@
+//! Предупреждения запуска: строки `warning[W004]: ...` в STDERR или массив "warnings" в JSON,
+//! разрешения --allow и --deny-warnings.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use readability::Warning;
+
+use crate::warning_text;
+
+/// Что делать с предупреждениями
+#[derive(Clone, Debug, Default)]
+pub struct Policy {
+    /// Вывод в JSON: предупреждения идут в массив "warnings", а не в STDERR
+    pub json: bool,
+    pub deny: bool,
+    /// Коды --allow: такие предупреждения не выводятся и не учитываются --deny-warnings
+    pub allow: Vec<&'static str>,
+}
+
+static POLICY: OnceLock<Policy> = OnceLock::new();
+/// Сколько предупреждений выдано за запуск (для --deny-warnings)
+static EMITTED: AtomicUsize = AtomicUsize::new(0);
+/// Предупреждения, не относящиеся к отдельному тексту (загрузка словаря)
+static RUN: Mutex<Vec<Warning>> = Mutex::new(Vec::new());
+
+pub fn init(policy: Policy) {
+    POLICY.get_or_init(|| policy);
+}
+
+fn policy() -> &'static Policy {
+    POLICY.get_or_init(Policy::default)
+}
+
+fn allowed(w: &Warning) -> bool {
+    policy().allow.contains(&w.code())
+}
+
+/// Учитывает предупреждения текста (или файла `path` корпуса) и, если вывод не в JSON,
+/// печатает их в STDERR
+pub fn emit(warnings: &[Warning], path: Option<&Path>) {
+    for w in warnings.iter().filter(|w| !allowed(w)) {
+        EMITTED.fetch_add(1, Ordering::Relaxed);
+        if policy().json {
+            continue;
+        }
+        match path {
+            Some(path) => eprintln!("warning[{}]: {}: {}", w.code(), path.display(), warning_text(w)),
+            None => eprintln!("warning[{}]: {}", w.code(), warning_text(w)),
+        }
+    }
+}
+
+/// Предупреждения всего запуска: выдаются как `emit` и попадают в JSON вывода
+pub fn emit_run(warnings: Vec<Warning>) {
+    emit(&warnings, None);
+    RUN.lock().unwrap_or_else(|e| e.into_inner()).extend(warnings);
+}
+
+pub fn run_warnings() -> Vec<Warning> {
+    RUN.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Массив "warnings": код, имя, текст на языке интерфейса и поля предупреждения в "context".
+/// None — нечего выводить (в том числе если все разрешены --allow).
+pub fn to_json(warnings: &[Warning]) -> Option<serde_json::Value> {
+    let items: Vec<serde_json::Value> = warnings
+        .iter()
+        .filter(|w| !allowed(w))
+        .map(|w| {
+            serde_json::json!({
+                "code": w.code(),
+                "name": w.name(),
+                "message": warning_text(w),
+                "context": w.context(),
+            })
+        })
+        .collect();
+    (!items.is_empty()).then_some(serde_json::Value::Array(items))
+}
+
+/// Число выданных предупреждений, если задан --deny-warnings и они были
+pub fn denied() -> Option<usize> {
+    let n = EMITTED.load(Ordering::Relaxed);
+    (policy().deny && n > 0).then_some(n)
+}
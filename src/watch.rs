@@ -0,0 +1,90 @@
+//! Режим наблюдения (--watch): переоценка файла при каждом его сохранении.
+
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+
+use readability::dict::FrequencyDict;
+use readability::{language_mismatch, read_input_limited, score_text, ScoreOptions};
+
+use crate::i18n::{self, Msg};
+use crate::{history, report_json, warnings, OutputFormat};
+
+/// Как часто проверять время изменения файла
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Оценивает файл при запуске и после каждого изменения, печатая оценку и её изменение
+/// относительно предыдущей: `0.431000 (+0.004000)`. Работает до прерывания; ошибки чтения
+/// (файл мог быть на середине сохранения) печатаются, но не останавливают наблюдение.
+pub fn run(
+    path: &Path,
+    dict: &FrequencyDict,
+    opts: &ScoreOptions,
+    format: OutputFormat,
+    history_path: Option<&Path>,
+) -> Result<()> {
+    let mut seen = None;
+    let mut previous: Option<f64> = None;
+    loop {
+        let stamp = modified(path);
+        if stamp.is_none() || stamp == seen {
+            std::thread::sleep(POLL_INTERVAL);
+            continue;
+        }
+        seen = stamp;
+
+        let text = match read_input_limited(Some(path), opts.max_input_bytes) {
+            Ok(t) => t,
+            Err(e) => {
+                eprintln!("{}: {}", Msg::ErrorPrefix, i18n::describe(&e));
+                continue;
+            }
+        };
+        let mismatch = language_mismatch(&text, opts.lang);
+        let report = match score_text(&text, dict, opts) {
+            Ok(report) => report,
+            Err(e) => {
+                eprintln!("{}: {}", Msg::ErrorPrefix, i18n::describe(&e));
+                continue;
+            }
+        };
+        let tokens = report.as_ref().map_or(0, |r| r.tokens);
+        let report = match report {
+            Some(r) if !opts.insufficient(tokens) => r,
+            _ => {
+                warnings::emit(mismatch.as_slice(), None);
+                eprintln!("{}", Msg::SkippedInsufficient(tokens));
+                continue;
+            }
+        };
+        let mut report = report;
+        report.warnings.splice(0..0, mismatch);
+        warnings::emit(&report.warnings, None);
+
+        let delta = previous.map(|p| report.score - p);
+        let mut out = io::stdout().lock();
+        match format {
+            OutputFormat::Json | OutputFormat::Jsonl => {
+                let mut json = report_json(&report, opts.lang);
+                json["path"] = serde_json::Value::from(path.display().to_string());
+                json["delta"] = serde_json::json!(delta);
+                writeln!(out, "{json}")?;
+            }
+            OutputFormat::Plain | OutputFormat::Csv => match delta {
+                Some(d) => writeln!(out, "{:.6} ({d:+.6})", report.score)?,
+                None => writeln!(out, "{:.6}", report.score)?,
+            },
+        }
+        out.flush()?;
+        if let Some(h) = history_path {
+            history::append(h, &history::record(&path.display().to_string(), &text, &report, history::Clock::System))?;
+        }
+        previous = Some(report.score);
+    }
+}
@@ -0,0 +1,156 @@
+//! Оценка по скользящим окнам слов: кривая трудности текста (--profile-out), её сглаживание
+//! (--smooth) и окна, выбивающиеся из сглаженной кривой (--flag-anomalies).
+
+use crate::dict::FrequencyDict;
+use crate::{token_weight, PreparedText, ScoreOptions};
+
+/// Размер окна и шаг, в словах
+#[derive(Clone, Copy, Debug)]
+pub struct WindowOptions {
+    pub size: usize,
+    pub step: usize,
+}
+
+/// Одно окно профиля
+#[derive(Clone, Copy, Debug)]
+pub struct WindowRow {
+    pub index: usize,
+    /// Номер первого слова окна (с 0) среди оцениваемых слов
+    pub start_token: usize,
+    /// Смещение первого слова в символах входного текста (до очистки и исключения цитат)
+    pub start_char: usize,
+    pub score: f64,
+    /// Доля слов окна, которых нет в словаре
+    pub oov_rate: f64,
+}
+
+/// Передаёт окна в `f` по одному, не накапливая их: окно начинается с каждого `step`-го
+/// слова и содержит `size` слов. Неполное окно в конце пропускается, кроме случая,
+/// когда текст короче одного окна — тогда окно одно, из всех слов.
+pub fn for_each_window<E>(
+    prepared: &PreparedText,
+    dict: &FrequencyDict,
+    opts: &ScoreOptions,
+    wopts: WindowOptions,
+    mut f: impl FnMut(WindowRow) -> Result<(), E>,
+) -> Result<(), E> {
+    let tokens = prepared.analyzed_tokens();
+    if tokens.is_empty() {
+        return Ok(());
+    }
+    let size = wopts.size.min(tokens.len());
+    // Префиксные суммы весов и числа слов вне словаря: каждое окно считается за O(1)
+    let mut weight_sums = Vec::with_capacity(tokens.len() + 1);
+    let mut oov_counts = Vec::with_capacity(tokens.len() + 1);
+    weight_sums.push(0.0f64);
+    oov_counts.push(0usize);
+    for t in tokens {
+        weight_sums.push(weight_sums.last().unwrap() + token_weight(t, dict, opts));
+        oov_counts.push(oov_counts.last().unwrap() + usize::from(!dict.contains(&t.word)));
+    }
+
+    // Смещения в символах считаем нарастающим итогом: начала окон только растут
+    let text = prepared.text.as_ref();
+    let (mut byte_pos, mut char_pos) = (0usize, 0usize);
+    let mut start = 0;
+    let mut index = 0;
+    while start + size <= tokens.len() {
+        let token_byte = tokens[start].start;
+        char_pos += text[byte_pos..token_byte].chars().count();
+        byte_pos = token_byte;
+        let end = start + size;
+        f(WindowRow {
+            index,
+            start_token: start,
+            start_char: prepared.original_char(char_pos),
+            score: (weight_sums[end] - weight_sums[start]) / size as f64,
+            oov_rate: (oov_counts[end] - oov_counts[start]) as f64 / size as f64,
+        })?;
+        index += 1;
+        start += wopts.step;
+    }
+    Ok(())
+}
+
+/// Сколько первых слов окна показывать в отчёте об аномалии
+const SNIPPET_WORDS: usize = 10;
+
+/// Центрированное скользящее среднее по `k` соседним окнам (--smooth). У краёв окно
+/// усреднения сжимается до имеющихся соседей; при чётном `k` лишнее окно берётся справа.
+pub fn smooth(scores: &[f64], k: usize) -> Vec<f64> {
+    let mut sums = Vec::with_capacity(scores.len() + 1);
+    sums.push(0.0f64);
+    for s in scores {
+        sums.push(sums.last().unwrap() + s);
+    }
+    let (left, right) = ((k.max(1) - 1) / 2, k.max(1) / 2);
+    (0..scores.len())
+        .map(|i| {
+            let (lo, hi) = (i.saturating_sub(left), (i + right + 1).min(scores.len()));
+            (sums[hi] - sums[lo]) / (hi - lo) as f64
+        })
+        .collect()
+}
+
+/// Окно, оценка которого отклоняется от сглаженной кривой сильнее порога
+#[derive(Clone, Debug)]
+pub struct Anomaly {
+    pub row: WindowRow,
+    pub smoothed: f64,
+    /// Отклонение от сглаженной кривой в стандартных отклонениях остатков (со знаком: меньше
+    /// нуля — окно труднее соседей)
+    pub z: f64,
+    /// Первые слова окна, как они записаны в тексте
+    pub snippet: String,
+}
+
+impl Anomaly {
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "window_index": self.row.index,
+            "start_token": self.row.start_token,
+            "start_char": self.row.start_char,
+            "score": self.row.score,
+            "smoothed": self.smoothed,
+            "z": self.z,
+            "snippet": self.snippet,
+        })
+    }
+}
+
+/// Окна, чья оценка отличается от сглаженной (`smoothed`, по одной на окно) больше чем на
+/// `z` стандартных отклонений остатков (--flag-anomalies). Если все остатки равны,
+/// аномалий нет.
+pub fn anomalies(prepared: &PreparedText, rows: &[WindowRow], smoothed: &[f64], z: f64) -> Vec<Anomaly> {
+    let residuals: Vec<f64> = rows.iter().zip(smoothed).map(|(r, s)| r.score - s).collect();
+    if residuals.is_empty() {
+        return Vec::new();
+    }
+    let mean = residuals.iter().sum::<f64>() / residuals.len() as f64;
+    let sd = (residuals.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / residuals.len() as f64).sqrt();
+    if sd == 0.0 {
+        return Vec::new();
+    }
+    rows.iter()
+        .zip(smoothed)
+        .zip(&residuals)
+        .filter(|(_, r)| r.abs() > z * sd)
+        .map(|((row, &smoothed), r)| Anomaly {
+            row: *row,
+            smoothed,
+            z: r / sd,
+            snippet: snippet(prepared, row.start_token),
+        })
+        .collect()
+}
+
+/// Первые `SNIPPET_WORDS` слов, начиная со слова `start`, с пробелами вместо переводов строк
+fn snippet(prepared: &PreparedText, start: usize) -> String {
+    let tokens = prepared.analyzed_tokens();
+    let end = (start + SNIPPET_WORDS).min(tokens.len());
+    if start >= end {
+        return String::new();
+    }
+    let text = &prepared.text[tokens[start].start..tokens[end - 1].end];
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
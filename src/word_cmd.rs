@@ -0,0 +1,187 @@
+//! Подкоманда word: вес, ранг и частота отдельных слов и коротких фраз.
+
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Args, ValueEnum};
+
+use readability::dict::{self, DictOptions, DuplicatePolicy, FrequencyDict, Weighting};
+use readability::pipeline::TokenPipeline;
+use readability::{prepare_text, Lang, ScoreOptions};
+
+use crate::dict_cmd::read_words;
+use crate::{contractions_table, parse_pipeline};
+
+/// Наибольшее расстояние правки до предлагаемых слов
+const SUGGEST_MAX_DISTANCE: usize = 2;
+
+#[derive(Args, Debug)]
+pub struct WordArgs {
+    /// Слова или фразы; если не указаны — читаются из STDIN по одному запросу на строку
+    words: Vec<String>,
+
+    /// Словарь (JSON или .csv); по умолчанию — словарь языка, как при оценке
+    #[arg(long = "dict")]
+    dict_path: Option<PathBuf>,
+
+    /// Язык: шаблон слов, правила регистра и словарь по умолчанию
+    #[arg(long = "lang", value_enum, default_value_t = Lang::En)]
+    lang: Lang,
+
+    /// Использовать только первые K записей словаря
+    #[arg(long = "top-dict-entries")]
+    top_dict_entries: Option<usize>,
+
+    /// Что делать со словом, встретившимся в словаре несколько раз
+    #[arg(long = "dict-duplicates", value_enum, default_value_t = DuplicatePolicy::Sum)]
+    dict_duplicates: DuplicatePolicy,
+
+    /// Функция перевода частоты в вес
+    #[arg(long = "weight", value_enum, default_value_t = Weighting::Linear)]
+    weight: Weighting,
+
+    /// Стадии нормализации, как у --pipeline оценки
+    #[arg(
+        long = "pipeline",
+        value_parser = parse_pipeline,
+        conflicts_with_all = ["no_sanitize", "expand_contractions"]
+    )]
+    pipeline: Option<TokenPipeline>,
+
+    /// Не очищать запрос перед токенизацией, как --no-sanitize оценки
+    #[arg(long = "no-sanitize")]
+    no_sanitize: bool,
+
+    /// Раскрывать сокращения, как --expand-contractions оценки
+    #[arg(long = "expand-contractions")]
+    expand_contractions: bool,
+
+    /// Дополнительная таблица сокращений, как у оценки
+    #[arg(long = "contractions-file")]
+    contractions_file: Option<PathBuf>,
+
+    /// Распознавать аббревиатуры и сокращения с точками, как --keep-acronyms оценки
+    #[arg(long = "keep-acronyms")]
+    keep_acronyms: bool,
+
+    /// Сколько ближайших слов словаря предлагать для слова вне словаря
+    #[arg(long = "suggestions", default_value_t = 3)]
+    suggestions: usize,
+
+    /// plain — строки `запрос<TAB>слово<TAB>вес<TAB>ранг<TAB>частота`, json — массив объектов
+    #[arg(long = "format", value_enum, default_value_t = WordFormat::Plain)]
+    format: WordFormat,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WordFormat {
+    Plain,
+    Json,
+}
+
+/// Одно слово запроса после нормализации
+struct Lookup<'d> {
+    word: String,
+    /// Вес, ранг и частота; None — слова нет в словаре
+    found: Option<(f64, usize, u64)>,
+    suggestions: Vec<&'d str>,
+}
+
+impl Lookup<'_> {
+    fn to_json(&self) -> serde_json::Value {
+        match self.found {
+            Some((weight, rank, count)) => serde_json::json!({
+                "word": self.word,
+                "weight": weight,
+                "rank": rank,
+                "count": count,
+            }),
+            None => serde_json::json!({
+                "word": self.word,
+                "oov": true,
+                "suggestions": self.suggestions,
+            }),
+        }
+    }
+}
+
+/// Слова запроса — те же, что получила бы оценка текста из него: запрос проходит ту же
+/// цепочку нормализации (очистку, регистр, сокращения), поэтому фраза или «Don’t» дают
+/// несколько слов
+fn lookup<'d>(query: &str, dict: &'d FrequencyDict, opts: &ScoreOptions, suggestions: usize) -> Result<Vec<Lookup<'d>>> {
+    let prepared = prepare_text(query, Some(dict), opts)?;
+    Ok(prepared
+        .tokens
+        .into_iter()
+        .map(|t| {
+            let found = dict.weight(&t.word).zip(dict.stats(&t.word)).map(|(w, s)| (w, s.rank, s.count));
+            let suggestions = match found {
+                Some(_) => Vec::new(),
+                None => dict
+                    .nearest(&t.word, SUGGEST_MAX_DISTANCE, suggestions)
+                    .into_iter()
+                    .map(|(w, _)| w)
+                    .collect(),
+            };
+            Lookup { word: t.word, found, suggestions }
+        })
+        .collect())
+}
+
+/// Печатает результат по каждому запросу; код выхода 1, если хотя бы одного слова нет
+/// в словаре (или в запросе нет ни одного слова)
+pub fn run(args: &WordArgs) -> Result<()> {
+    let pipeline = args
+        .pipeline
+        .clone()
+        .unwrap_or_else(|| TokenPipeline::from_flags(false, !args.no_sanitize, false, false, args.expand_contractions));
+    let opts = ScoreOptions {
+        lang: args.lang,
+        keep_acronyms: args.keep_acronyms,
+        contractions: contractions_table(&pipeline, args.contractions_file.as_deref())?,
+        pipeline,
+        ..ScoreOptions::default()
+    };
+    let dict_path = args.dict_path.clone().unwrap_or_else(|| args.lang.default_dict_path());
+    let dict_opts = DictOptions {
+        top_k: args.top_dict_entries,
+        coverage_target: None,
+        duplicates: args.dict_duplicates,
+        weighting: args.weight,
+        retain_stats: true,
+        lenient: false,
+        locale: None,
+    };
+    let (dict, _) = dict::load_frequency_dict(&dict_path, dict_opts)?;
+
+    let mut out = io::stdout().lock();
+    let mut missing = false;
+    let mut records = Vec::new();
+    for query in read_words(&args.words)? {
+        let words = lookup(&query, &dict, &opts, args.suggestions)?;
+        missing |= words.is_empty() || words.iter().any(|w| w.found.is_none());
+        if args.format == WordFormat::Json {
+            let words: Vec<serde_json::Value> = words.iter().map(Lookup::to_json).collect();
+            records.push(serde_json::json!({ "query": query, "words": words }));
+            continue;
+        }
+        if words.is_empty() {
+            writeln!(out, "{query}\t-\tNO_WORDS")?;
+        }
+        for w in &words {
+            match w.found {
+                Some((weight, rank, count)) => writeln!(out, "{query}\t{}\t{weight}\t{rank}\t{count}", w.word)?,
+                None => writeln!(out, "{query}\t{}\tMISSING\t{}", w.word, w.suggestions.join(","))?,
+            }
+        }
+    }
+    if args.format == WordFormat::Json {
+        writeln!(out, "{}", serde_json::Value::Array(records))?;
+    }
+    out.flush()?;
+    if missing {
+        std::process::exit(1);
+    }
+    Ok(())
+}
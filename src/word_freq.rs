@@ -0,0 +1,87 @@
+//! Подкоманда word-freq: частотная таблица слов самого входного текста.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Args, ValueEnum};
+
+use readability::{read_input_text, tokenize_words, Lang};
+
+#[derive(Args, Debug)]
+pub struct WordFreqArgs {
+    /// Путь к текстовому файлу; если не указан — читаем текст из STDIN
+    #[arg(long = "text")]
+    text_path: Option<PathBuf>,
+
+    /// Язык текста (тот же шаблон слов и регистр, что и при оценке)
+    #[arg(long = "lang", value_enum, default_value_t = Lang::En)]
+    lang: Lang,
+
+    /// json — массив [["word", count], ...], загружаемый через --dict; tsv — строки word<TAB>count
+    #[arg(long = "format", value_enum, default_value_t = WordFreqFormat::Json)]
+    format: WordFreqFormat,
+
+    /// Вывести только первые K слов
+    #[arg(long = "limit")]
+    limit: Option<usize>,
+
+    /// Пропустить слова, встретившиеся реже N раз
+    #[arg(long = "min-count", default_value_t = 1)]
+    min_count: u64,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WordFreqFormat {
+    Json,
+    Tsv,
+}
+
+/// Считает вхождения слов и сортирует по убыванию частоты, при равенстве — по алфавиту,
+/// чтобы таблицы разных документов можно было сравнивать через diff.
+pub fn count_words(tokens: &[String]) -> Vec<(String, u64)> {
+    let mut counts: HashMap<&str, u64> = HashMap::new();
+    for t in tokens {
+        *counts.entry(t.as_str()).or_insert(0) += 1;
+    }
+    let mut items: Vec<(String, u64)> = counts
+        .into_iter()
+        .map(|(w, c)| (w.to_string(), c))
+        .collect();
+    items.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    items
+}
+
+pub fn run(args: &WordFreqArgs) -> Result<()> {
+    let text = read_input_text(args.text_path.as_deref())?;
+    let tokens = tokenize_words(&text, args.lang);
+
+    let mut items = count_words(&tokens);
+    items.retain(|(_, c)| *c >= args.min_count);
+    if let Some(k) = args.limit {
+        items.truncate(k);
+    }
+
+    let mut out = io::stdout().lock();
+    match args.format {
+        WordFreqFormat::Json => {
+            // Тот же вид, что и у word_frequencies.json
+            let json = serde_json::to_string_pretty(&items_to_json(&items))?;
+            writeln!(out, "{json}")?;
+        }
+        WordFreqFormat::Tsv => {
+            for (w, c) in &items {
+                writeln!(out, "{w}\t{c}")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn items_to_json(items: &[(String, u64)]) -> serde_json::Value {
+    items
+        .iter()
+        .map(|(w, c)| serde_json::json!([w, c]))
+        .collect()
+}
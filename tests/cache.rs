@@ -0,0 +1,72 @@
+//! Кэш оценок (--cache-scores): второй запуск по неизменившемуся корпусу ничего не оценивает
+//! и выводит то же, что и первый.
+
+mod common;
+
+use common::{run_ok, TempDir};
+
+/// Корпус, в отчётах которого заполнены и счётчики, и предупреждения
+fn corpus(dir: &TempDir) -> String {
+    let corpus = dir.path().join("corpus");
+    std::fs::create_dir(&corpus).unwrap();
+    std::fs::write(
+        corpus.join("a.txt"),
+        "The quick brown fox jumps over the lazy dog. Zorblax quixotically flummoxed the perspicacious \
+         xylophonist yesterday.\n\nAnother paragraph with simple words here.\n",
+    )
+    .unwrap();
+    std::fs::write(corpus.join("b.txt"), "He said hello\u{201d} to everyone in the room, and it was nice to see them.\n").unwrap();
+    std::fs::write(corpus.join("c.txt"), "Simple text for the second file, with Bob and teh typo.\n").unwrap();
+    corpus.to_str().unwrap().to_string()
+}
+
+#[test]
+fn second_run_is_served_from_cache_and_prints_the_same() {
+    let dir = TempDir::new("cache");
+    let corpus = corpus(&dir);
+    let cache = dir.path().join("cache.json");
+    let cache = cache.to_str().unwrap();
+    let common = [
+        "--text",
+        &corpus,
+        "--cache-scores",
+        cache,
+        "--verbose",
+        "--skip-quotes",
+        "--classify-oov",
+        "--auto-thresholds",
+        "--tertile-report",
+        "--aggregate-by",
+        "sentence",
+        "--overlap",
+        "100",
+    ];
+    for extra in [&["--template", "{path}\t{score}\t{oov_rate:.1%}\t{quoted_tokens_excluded}\t{oov_typo}"][..], &["--format", "jsonl", "--metric", "flesch", "--metric", "freq"]] {
+        std::fs::remove_file(cache).ok();
+        let args: Vec<&str> = common.iter().chain(extra).copied().collect();
+        let fresh = run_ok(&args);
+        assert!(String::from_utf8_lossy(&fresh.stderr).contains("cache: 0 hits, 3 misses"));
+
+        let cached = run_ok(&args);
+        let stderr = String::from_utf8_lossy(&cached.stderr);
+        assert!(stderr.contains("cache: 3 hits, 0 misses"), "{stderr}");
+        // Единственное отличие записи из кэша — пометка об этом в JSON
+        let cached_stdout = String::from_utf8(cached.stdout).unwrap().replace("\"cached\":true,", "");
+        assert_eq!(String::from_utf8(fresh.stdout).unwrap(), cached_stdout, "{extra:?}");
+    }
+}
+
+#[test]
+fn changed_settings_miss_the_cache() {
+    let dir = TempDir::new("cache-settings");
+    let corpus = corpus(&dir);
+    let cache = dir.path().join("cache.json");
+    let cache = cache.to_str().unwrap();
+    run_ok(&["--text", &corpus, "--cache-scores", cache]);
+    for changed in [&["--auto-thresholds"][..], &["--strict-lang"]] {
+        let args: Vec<&str> = ["--text", &corpus, "--cache-scores", cache, "--no-cache-write", "--verbose"].iter().chain(changed).copied().collect();
+        let out = run_ok(&args);
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        assert!(stderr.contains("cache: 0 hits, 3 misses"), "{changed:?}: {stderr}");
+    }
+}
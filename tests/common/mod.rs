@@ -0,0 +1,62 @@
+//! Общие помощники интеграционных тестов: запуск собранной программы и временные каталоги.
+
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Словарь из корня репозитория
+pub fn dict() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("word_frequencies.json")
+}
+
+/// Команда программы с английским интерфейсом, чтобы тексты сообщений не зависели от LANG
+pub fn readability() -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_readability"));
+    cmd.env_remove("LANG").arg("--lang-ui").arg("en");
+    cmd
+}
+
+/// Запуск с `args` и словарём по умолчанию
+pub fn run(args: &[&str]) -> Output {
+    readability().arg("--dict").arg(dict()).args(args).output().expect("программа запускается")
+}
+
+/// То же, что `run`, но падает, если программа завершилась с ошибкой
+pub fn run_ok(args: &[&str]) -> Output {
+    let out = run(args);
+    assert!(out.status.success(), "{args:?}: {}", String::from_utf8_lossy(&out.stderr));
+    out
+}
+
+/// Временный каталог, удаляемый вместе со значением
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let n = NEXT.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("readability-{name}-{}-{n}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("временный каталог создаётся");
+        TempDir(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Пишет файл `name` внутри каталога и возвращает его путь
+    pub fn write(&self, name: &str, text: &str) -> PathBuf {
+        let path = self.0.join(name);
+        std::fs::write(&path, text).expect("файл записывается");
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,69 @@
+//! Демон (--daemon): запросы JSON Lines через канал, по ответу на каждый, сразу.
+
+mod common;
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{ChildStdin, ChildStdout, Stdio};
+
+use common::{dict, readability};
+
+/// Отправляет строку запроса и ждёт ответа на неё: ответ должен прийти, пока канал открыт
+fn exchange(stdin: &mut ChildStdin, stdout: &mut BufReader<ChildStdout>, line: &str) -> serde_json::Value {
+    writeln!(stdin, "{line}").unwrap();
+    stdin.flush().unwrap();
+    let mut resp = String::new();
+    stdout.read_line(&mut resp).unwrap();
+    serde_json::from_str(&resp).unwrap_or_else(|e| panic!("{line}: {resp:?}: {e}"))
+}
+
+#[test]
+fn daemon_answers_every_request_and_survives_bad_ones() {
+    let mut daemon = readability()
+        .arg("--dict")
+        .arg(dict())
+        .arg("--daemon")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = daemon.stdin.take().unwrap();
+    let mut stdout = BufReader::new(daemon.stdout.take().unwrap());
+
+    let first = exchange(&mut stdin, &mut stdout, r#"{"id": 1, "text": "the cat sat on the mat"}"#);
+    assert_eq!(first["id"], 1);
+    assert_eq!(first["tokens"], 6);
+    let score = first["score"].as_f64().unwrap();
+    assert!(score > 0.0 && score <= 1.0);
+
+    let bad = exchange(&mut stdin, &mut stdout, "not json");
+    assert_eq!(bad["id"], serde_json::Value::Null);
+    assert!(bad["error"].as_str().unwrap().starts_with("Invalid request JSON"), "{bad}");
+
+    for (line, error) in [
+        (r#"{"id": 3}"#, "Field \"text\" is missing"),
+        (r#"{"id": 4, "text": "a b", "top_text_words": "x"}"#, "Field \"top_text_words\""),
+        (r#"{"id": 5, "cmd": "nope"}"#, "Unknown command"),
+    ] {
+        let resp = exchange(&mut stdin, &mut stdout, line);
+        assert_eq!(resp["id"], serde_json::from_str::<serde_json::Value>(line).unwrap()["id"]);
+        assert!(resp["error"].as_str().unwrap().starts_with(error), "{resp}");
+        assert!(resp.get("score").is_none());
+    }
+
+    // После ошибок демон оценивает как прежде, в том числе с параметрами запроса
+    let again = exchange(&mut stdin, &mut stdout, r#"{"id": "a", "text": "the cat sat on the mat"}"#);
+    assert_eq!(again["id"], "a");
+    assert_eq!(again["score"], first["score"]);
+    let top = exchange(&mut stdin, &mut stdout, r#"{"id": 7, "text": "the cat sat on the mat", "top_text_words": 2}"#);
+    assert_eq!(top["tokens"], 2);
+
+    let status = exchange(&mut stdin, &mut stdout, r#"{"id": 8, "cmd": "status"}"#);
+    assert_eq!(status["entries"], 100000);
+
+    // Конец ввода — штатное завершение, без лишнего вывода
+    drop(stdin);
+    let mut rest = String::new();
+    std::io::Read::read_to_string(&mut stdout, &mut rest).unwrap();
+    assert_eq!(rest, "");
+    assert!(daemon.wait().unwrap().success());
+}
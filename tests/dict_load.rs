@@ -0,0 +1,175 @@
+//! Потоковая загрузка словаря: тот же словарь, что и сборка из всех записей сразу, а с
+//! --top-dict-entries разбор останавливается на K-й записи. Так же и с --dict-coverage-target,
+//! где проходов по файлу два.
+
+mod common;
+
+use std::path::Path;
+
+use readability::dict::{build_frequency_dict, load_frequency_dict, read_dict_entries, DictOptions, DuplicatePolicy, FrequencyDict, Weighting};
+use readability::Error;
+use sha2::{Digest, Sha256};
+
+use common::TempDir;
+
+fn opts(duplicates: DuplicatePolicy) -> DictOptions {
+    DictOptions {
+        top_k: None,
+        coverage_target: None,
+        duplicates,
+        weighting: Weighting::Linear,
+        retain_stats: true,
+        lenient: false,
+        locale: None,
+    }
+}
+
+/// Слова, которые проверяются в обоих словарях
+const WORDS: &[&str] = &["the", "The", "a", "fox", "dog", "Straße", "missing"];
+
+fn assert_same(streamed: &FrequencyDict, built: &FrequencyDict) {
+    assert_eq!(streamed.len(), built.len());
+    for w in WORDS {
+        assert_eq!(streamed.weight(w), built.weight(w), "{w}");
+        assert_eq!(streamed.stats(w), built.stats(w), "{w}");
+    }
+}
+
+#[test]
+fn streamed_load_matches_build_from_all_entries() {
+    let dir = TempDir::new("dict-load");
+    let json = dir.write(
+        "d.json",
+        r#"[["the", 9], ["a", 7], ["The", 4], ["fox", "5"], ["the", 2], ["dog", 1], ["Straße", 1]]"#,
+    );
+    let csv = dir.write("d.csv", "the,9\na,7\nThe,4\nfox,5\nthe,2\ndog,1\nStraße,1\n");
+    for path in [&json, &csv] {
+        for duplicates in [DuplicatePolicy::First, DuplicatePolicy::Last, DuplicatePolicy::Sum, DuplicatePolicy::Max] {
+            for top_k in [None, Some(1), Some(4), Some(100)] {
+                for locale in [None, Some(readability::casefold::Locale::Default)] {
+                    let opts = DictOptions { top_k, locale, ..opts(duplicates) };
+                    let (streamed, info) = load_frequency_dict(path, opts).unwrap();
+                    let (built, expected) = build_frequency_dict(read_dict_entries(path).unwrap(), opts).unwrap();
+                    assert_same(&streamed, &built);
+                    assert_eq!(
+                        (info.entries, info.duplicates, info.folded),
+                        (expected.entries, expected.duplicates, expected.folded),
+                        "{path:?} {opts:?}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn duplicate_word_is_reported_with_its_entry() {
+    let dir = TempDir::new("dict-load");
+    let path = dir.write("d.json", r#"[["the", 9], ["a", 7], ["the", 2]]"#);
+    match load_frequency_dict(&path, opts(DuplicatePolicy::Error)) {
+        Err(Error::DuplicateWord { word, entry }) => assert_eq!((word.as_str(), entry), ("the", 3)),
+        other => panic!("{:?}", other.map(|(_, info)| info)),
+    }
+}
+
+/// Разбор останавливается на K-й записи: испорченный хвост файла не мешает, а SHA-256 всё
+/// равно считается по всему файлу
+#[test]
+fn top_entries_stop_parsing_but_hash_whole_file() {
+    let dir = TempDir::new("dict-load");
+    let text = r#"[["the", 9], ["a", 7], ["fox", 5], {"broken"#;
+    let path = dir.write("d.json", text);
+    assert!(load_frequency_dict(&path, opts(DuplicatePolicy::Sum)).is_err());
+
+    let opts = DictOptions { top_k: Some(2), ..opts(DuplicatePolicy::Sum) };
+    let (dict, info) = load_frequency_dict(&path, opts).unwrap();
+    assert_eq!((dict.len(), info.entries), (2, 2));
+    let expected: String = Sha256::digest(text.as_bytes()).iter().map(|b| format!("{b:02x}")).collect();
+    assert_eq!(info.sha256, [expected]);
+}
+
+/// Нестрогий разбор пропускает некорректные записи и при потоковой загрузке
+#[test]
+fn lenient_load_skips_malformed_entries() {
+    let dir = TempDir::new("dict-load");
+    for (name, text, first) in [
+        ("d.json", r#"[["the", 9], ["bad"], ["a", "x"], ["fox", 5]]"#, vec![2, 3]),
+        ("d.csv", "the,9\nbad\na,x\nfox,5\n", vec![2, 3]),
+    ] {
+        let path = dir.write(name, text);
+        let opts = DictOptions { lenient: true, ..opts(DuplicatePolicy::Sum) };
+        let (dict, info) = load_frequency_dict(&path, opts).unwrap();
+        assert_eq!(dict.len(), 2, "{name}");
+        assert_eq!((info.malformed.count, info.malformed.first), (2, first), "{name}");
+        assert!(load_frequency_dict(&path, DictOptions { lenient: false, ..opts }).is_err(), "{name}");
+    }
+}
+
+/// Доля частот: упорядоченный словарь отбирается вторым потоковым проходом, неупорядоченный —
+/// в памяти; оба дают то же, что отбор из всех записей
+#[test]
+fn coverage_target_matches_build_from_all_entries() {
+    let dir = TempDir::new("dict-load");
+    let sorted = dir.write("sorted.json", r#"[["the", 50], ["a", 20], ["fox", 10], ["the", 10], ["dog", 5], ["bad"], ["Straße", 5]]"#);
+    let unsorted = dir.write("unsorted.csv", "dog,5
+fox,10
+the,50
+bad
+Straße,5
+a,20
+the,10
+");
+    for path in [&sorted, &unsorted] {
+        for target in [0.01, 0.5, 0.7, 0.9, 0.95, 1.0] {
+            let opts = DictOptions { coverage_target: Some(target), lenient: true, ..opts(DuplicatePolicy::Sum) };
+            let (streamed, info) = load_frequency_dict(path, opts).unwrap();
+            let (built, expected) = build_frequency_dict(read_dict_entries_lenient(path), opts).unwrap();
+            assert_same(&streamed, &built);
+            assert_eq!((info.entries, info.duplicates, info.coverage), (expected.entries, expected.duplicates, expected.coverage), "{path:?} {target}");
+            assert_eq!(info.malformed.count, 1, "{path:?} {target}");
+        }
+    }
+    let opts = DictOptions { coverage_target: Some(0.7), ..opts(DuplicatePolicy::Sum) };
+    let sorted = dir.write("sorted.csv", "the,50
+a,20
+fox,10
+");
+    let (dict, info) = load_frequency_dict(&sorted, opts).unwrap();
+    assert_eq!((dict.len(), info.entries, info.coverage), (2, 2, Some(70.0 / 80.0)));
+}
+
+/// Записи словаря без некорректных, как их видит нестрогая загрузка
+fn read_dict_entries_lenient(path: &Path) -> Vec<(String, u64)> {
+    let text = std::fs::read_to_string(path).unwrap();
+    if path.extension().is_some_and(|e| e == "csv") {
+        return text
+            .lines()
+            .filter_map(|l| l.split_once(',').and_then(|(w, c)| Some((w.to_string(), c.parse().ok()?))))
+            .collect();
+    }
+    let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+    json.as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|e| Some((e.get(0)?.as_str()?.to_string(), e.get(1)?.as_u64()?)))
+        .collect()
+}
+
+#[test]
+fn bundled_dictionary_streams_like_in_memory_build() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("word_frequencies.json");
+    let opts = DictOptions { weighting: Weighting::Log, ..opts(DuplicatePolicy::Sum) };
+    let (streamed, info) = load_frequency_dict(&path, opts).unwrap();
+    let (built, expected) = build_frequency_dict(read_dict_entries(&path).unwrap(), opts).unwrap();
+    assert_same(&streamed, &built);
+    assert_eq!(info.entries, expected.entries);
+
+    for target in [0.5, 0.9, 0.99] {
+        let opts = DictOptions { coverage_target: Some(target), ..opts };
+        let (streamed, info) = load_frequency_dict(&path, opts).unwrap();
+        let (built, expected) = build_frequency_dict(read_dict_entries(&path).unwrap(), opts).unwrap();
+        assert_same(&streamed, &built);
+        assert_eq!((info.entries, info.coverage), (expected.entries, expected.coverage), "{target}");
+        assert!(info.coverage.unwrap() >= target, "{target}");
+    }
+}
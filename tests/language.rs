@@ -0,0 +1,53 @@
+//! Текст на другом языке, чем словарь: без единого слова для оценки сообщение говорит о языке.
+
+mod common;
+
+use std::io::Write;
+use std::process::Stdio;
+
+use common::{dict, readability, run, run_ok, TempDir};
+
+/// 60 русских слов: английский токенизатор не находит в них ни одного слова
+fn russian_text() -> String {
+    let words = "он сказал что завтра мы пойдём в лес за грибами и ягодами если будет хорошая погода";
+    let mut text: Vec<&str> = words.split(' ').cycle().take(60).collect();
+    text.push(".");
+    text.join(" ")
+}
+
+const HINT: &str = "No words found to score: the text appears to be Russian, but the dictionary is English";
+
+#[test]
+fn single_text_reports_the_language() {
+    let dir = TempDir::new("language-single");
+    let path = dir.write("ru.txt", &russian_text());
+    let out = run(&["--text", path.to_str().unwrap()]);
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains(HINT), "{stderr}");
+}
+
+#[test]
+fn corpus_and_daemon_report_the_language() {
+    let dir = TempDir::new("language-corpus");
+    dir.write("ru.txt", &russian_text());
+    dir.write("en.txt", "The quick brown fox jumps over the lazy dog.\n");
+    let out = run_ok(&["--text", dir.path().to_str().unwrap(), "--format", "jsonl"]);
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    let ru = stdout.lines().find(|l| l.contains("ru.txt")).unwrap();
+    assert!(ru.contains(HINT), "{ru}");
+
+    let mut daemon = readability()
+        .arg("--dict")
+        .arg(dict())
+        .arg("--daemon")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let request = serde_json::json!({ "id": 1, "text": russian_text() });
+    writeln!(daemon.stdin.take().unwrap(), "{request}").unwrap();
+    let out = daemon.wait_with_output().unwrap();
+    let resp: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert!(resp["error"].as_str().unwrap().starts_with(HINT), "{resp}");
+}
@@ -0,0 +1,53 @@
+//! Квантование весов (--quantize-weights 16): на настоящем словаре оценки отличаются от оценок
+//! с полными весами меньше чем на 1e-4.
+
+use std::path::Path;
+
+use readability::dict::{load_frequency_dict, DictOptions, DuplicatePolicy, Weighting, QUANTIZE_MAX_ERROR};
+use readability::{score_text, ScoreOptions};
+
+/// Тексты из слов словаря разной частоты: каждое 7-е слово всего словаря, первая тысяча слов
+/// и редкий хвост, и эталонные предложения
+fn texts(dict_path: &Path) -> Vec<String> {
+    let entries: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dict_path).unwrap()).unwrap();
+    let words: Vec<&str> = entries.as_array().unwrap().iter().map(|e| e[0].as_str().unwrap()).collect();
+    let reference = std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("data/en/reference.txt")).unwrap();
+    vec![
+        words.iter().step_by(7).copied().collect::<Vec<_>>().join(" "),
+        words[..1000].join(" "),
+        words[words.len() - 1000..].join(" "),
+        reference,
+    ]
+}
+
+#[test]
+fn quantized_scores_stay_within_bound() {
+    const { assert!(QUANTIZE_MAX_ERROR < 1e-4) };
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("word_frequencies.json");
+    let texts = texts(&path);
+    let score_opts = ScoreOptions::default();
+    for weighting in [Weighting::Linear, Weighting::Log, Weighting::Rank] {
+        let opts = DictOptions {
+            top_k: None,
+            coverage_target: None,
+            duplicates: DuplicatePolicy::Sum,
+            weighting,
+            retain_stats: false,
+            lenient: false,
+            locale: None,
+        };
+        let (full, _) = load_frequency_dict(&path, opts).unwrap();
+        let (quantized, _) = load_frequency_dict(&path, opts).unwrap();
+        let quantized = quantized.quantized();
+        assert!(quantized.is_quantized());
+        for text in &texts {
+            let exact = score_text(text, &full, &score_opts).unwrap().unwrap();
+            let approx = score_text(text, &quantized, &score_opts).unwrap().unwrap();
+            assert_eq!(exact.tokens, approx.tokens);
+            let diff = (exact.score - approx.score).abs();
+            // Оценка — среднее весов, её ошибка не больше ошибки одного веса (с точностью до округления f64)
+            assert!(diff <= QUANTIZE_MAX_ERROR + 1e-12, "{weighting:?}: {} vs {}", exact.score, approx.score);
+            assert!(diff < 1e-4);
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! Продолжение оценки корпуса по файлу результатов (--results-out, --resume).
+
+mod common;
+
+use common::{run, run_ok, TempDir};
+
+#[test]
+fn resume_reuses_full_reports_and_refuses_other_settings() {
+    let dir = TempDir::new("resume");
+    let corpus = dir.path().join("corpus");
+    std::fs::create_dir(&corpus).unwrap();
+    std::fs::write(corpus.join("a.txt"), "Zorblax quixotically flummoxed the perspicacious xylophonist.\n").unwrap();
+    std::fs::write(corpus.join("b.txt"), "Simple text with Bob and teh typo.\n").unwrap();
+    let (corpus, out) = (corpus.to_str().unwrap(), dir.path().join("results.jsonl"));
+    let out = out.to_str().unwrap();
+    let args = ["--text", corpus, "--format", "jsonl", "--classify-oov", "--auto-thresholds", "--results-out", out];
+
+    let fresh = String::from_utf8(run_ok(&args).stdout).unwrap();
+    let resumed = String::from_utf8(run_ok(&[&args[..], &["--resume"]].concat()).stdout).unwrap();
+    assert_eq!(resumed.matches("\"reused\":true").count(), 2, "{resumed}");
+    assert_eq!(fresh, resumed.replace("\"reused\":true,", ""));
+
+    // Без --auto-thresholds оценки те же, но отчёты другие: прежний файл не продолжается
+    let other = run(&["--text", corpus, "--format", "jsonl", "--classify-oov", "--results-out", out, "--resume"]);
+    assert!(!other.status.success());
+    assert!(String::from_utf8_lossy(&other.stderr).contains("run without --resume"));
+}